@@ -15,6 +15,7 @@ pub(crate) struct Gui {
     last_frame: Instant,
     last_cursor: Option<imgui::MouseCursor>,
     open_file_dialog: bool,
+    file_dialog: imfile::FileDialog,
 }
 
 impl Gui {
@@ -59,6 +60,11 @@ impl Gui {
         // Return GUI context
         Self {
             open_file_dialog: true,
+            file_dialog: imfile::FileDialog::new()
+                .accept_text("Open file")
+                .for_save()
+                .cancel_text("Close")
+                .title("Open File"),
             imgui,
             platform,
             renderer,
@@ -97,15 +103,22 @@ impl Gui {
         }
 
         if self.open_file_dialog == true {
-            if let Some(file) = imfile::FileDialog::new()
-                .accept_text("Open file")
-                .for_save()
-                .cancel_text("Close")
-                .title("Open File")
-                .spawn(&ui)
-            {
-                println!("Filename: {}", file.display());
-                self.open_file_dialog = false;
+            match self.file_dialog.spawn_borrowed(ui) {
+                imfile::DialogResult::StillOpen => {}
+                imfile::DialogResult::Selected(file) => {
+                    println!("Filename: {}", file.display());
+                    self.open_file_dialog = false;
+                }
+                imfile::DialogResult::SelectedMany(files) => {
+                    for file in files {
+                        println!("Filename: {}", file.display());
+                    }
+                    self.open_file_dialog = false;
+                }
+                imfile::DialogResult::Cancelled => {
+                    println!("No file selected.");
+                    self.open_file_dialog = false;
+                }
             }
         }
 
@@ -0,0 +1,146 @@
+use std::cmp::Ordering;
+
+/// Orders names according to the alphabet of a language, instead of by code point.
+///
+/// Accented letters sort next to their base letter (`ä` next to `a` in German), unless the
+/// language treats them as letters of their own: Swedish and Finnish sort `å`, `ä` and `ö`
+/// after `z`, Danish and Norwegian `æ`, `ø` and `å`. Differences in accents only matter between
+/// names that are otherwise equal, and differences in case are left to the default comparator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Collator {
+    /// Letters sorted after `z`, in alphabetical order.
+    trailing_letters: &'static [char],
+}
+
+/// The collation key of a single character: its position in the alphabet (the code point of its
+/// base letter, plus an offset for letters sorted after it) and its accent.
+type Weight = ((char, u8), char);
+
+impl Collator {
+    /// Creates a collator for a locale such as `"sv"`, `"de-DE"` or `"nb_NO"`.
+    ///
+    /// Only the language part of the locale is used. Languages without specific rules sort
+    /// accented letters next to their base letter.
+    pub(crate) fn new(locale: &str) -> Self {
+        let language = locale.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+        let trailing_letters: &'static [char] = match language.as_str() {
+            "sv" | "fi" => &['å', 'ä', 'ö'],
+            "da" | "nb" | "nn" | "no" => &['æ', 'ø', 'å'],
+            _ => &[],
+        };
+        Self { trailing_letters }
+    }
+
+    /// Compares two names, returning [`Ordering::Equal`] for names differing only by case.
+    pub(crate) fn compare(&self, a: &str, b: &str) -> Ordering {
+        let (a, b) = (self.weights(a), self.weights(b));
+        a.iter().map(|(primary, _)| primary).cmp(b.iter().map(|(primary, _)| primary))
+            .then_with(|| a.iter().map(|(_, accent)| accent).cmp(b.iter().map(|(_, accent)| accent)))
+    }
+
+    fn weights(&self, name: &str) -> Vec<Weight> {
+        let mut weights = Vec::with_capacity(name.len());
+        for c in name.chars().flat_map(char::to_lowercase) {
+            if let Some(i) = self.trailing_letters.iter().position(|&letter| letter == c) {
+                weights.push((('z', i as u8 + 1), '\0'));
+                continue;
+            }
+            match c {
+                'ß' => weights.extend([(('s', 0), '\0'), (('s', 0), '\0')]),
+                'æ' => weights.extend([(('a', 0), '\0'), (('e', 0), '\0')]),
+                'œ' => weights.extend([(('o', 0), '\0'), (('e', 0), '\0')]),
+                'ø' => weights.push((('o', 0), '\u{338}')),
+                _ => match LATIN_DECOMPOSITIONS.binary_search_by_key(&c, |&(composed, _, _)| composed) {
+                    Ok(i) => weights.push(((LATIN_DECOMPOSITIONS[i].1, 0), LATIN_DECOMPOSITIONS[i].2)),
+                    Err(_) => weights.push(((c, 0), '\0')),
+                },
+            }
+        }
+        weights
+    }
+}
+
+/// Accented lowercase Latin letters with their base letter and combining accent, sorted by the accented letter.
+const LATIN_DECOMPOSITIONS: &[(char, char, char)] = &[
+    ('à', 'a', '\u{300}'), ('á', 'a', '\u{301}'), ('â', 'a', '\u{302}'), ('ã', 'a', '\u{303}'), ('ä', 'a', '\u{308}'),
+    ('å', 'a', '\u{30a}'), ('ç', 'c', '\u{327}'), ('è', 'e', '\u{300}'), ('é', 'e', '\u{301}'), ('ê', 'e', '\u{302}'),
+    ('ë', 'e', '\u{308}'), ('ì', 'i', '\u{300}'), ('í', 'i', '\u{301}'), ('î', 'i', '\u{302}'), ('ï', 'i', '\u{308}'),
+    ('ñ', 'n', '\u{303}'), ('ò', 'o', '\u{300}'), ('ó', 'o', '\u{301}'), ('ô', 'o', '\u{302}'), ('õ', 'o', '\u{303}'),
+    ('ö', 'o', '\u{308}'), ('ù', 'u', '\u{300}'), ('ú', 'u', '\u{301}'), ('û', 'u', '\u{302}'), ('ü', 'u', '\u{308}'),
+    ('ý', 'y', '\u{301}'), ('ÿ', 'y', '\u{308}'), ('ā', 'a', '\u{304}'), ('ă', 'a', '\u{306}'), ('ą', 'a', '\u{328}'),
+    ('ć', 'c', '\u{301}'), ('ĉ', 'c', '\u{302}'), ('ċ', 'c', '\u{307}'), ('č', 'c', '\u{30c}'), ('ď', 'd', '\u{30c}'),
+    ('ē', 'e', '\u{304}'), ('ĕ', 'e', '\u{306}'), ('ė', 'e', '\u{307}'), ('ę', 'e', '\u{328}'), ('ě', 'e', '\u{30c}'),
+    ('ĝ', 'g', '\u{302}'), ('ğ', 'g', '\u{306}'), ('ġ', 'g', '\u{307}'), ('ģ', 'g', '\u{327}'), ('ĥ', 'h', '\u{302}'),
+    ('ĩ', 'i', '\u{303}'), ('ī', 'i', '\u{304}'), ('ĭ', 'i', '\u{306}'), ('į', 'i', '\u{328}'), ('ĵ', 'j', '\u{302}'),
+    ('ķ', 'k', '\u{327}'), ('ĺ', 'l', '\u{301}'), ('ļ', 'l', '\u{327}'), ('ľ', 'l', '\u{30c}'), ('ń', 'n', '\u{301}'),
+    ('ņ', 'n', '\u{327}'), ('ň', 'n', '\u{30c}'), ('ō', 'o', '\u{304}'), ('ŏ', 'o', '\u{306}'), ('ő', 'o', '\u{30b}'),
+    ('ŕ', 'r', '\u{301}'), ('ŗ', 'r', '\u{327}'), ('ř', 'r', '\u{30c}'), ('ś', 's', '\u{301}'), ('ŝ', 's', '\u{302}'),
+    ('ş', 's', '\u{327}'), ('š', 's', '\u{30c}'), ('ţ', 't', '\u{327}'), ('ť', 't', '\u{30c}'), ('ũ', 'u', '\u{303}'),
+    ('ū', 'u', '\u{304}'), ('ŭ', 'u', '\u{306}'), ('ů', 'u', '\u{30a}'), ('ű', 'u', '\u{30b}'), ('ų', 'u', '\u{328}'),
+    ('ŵ', 'w', '\u{302}'), ('ŷ', 'y', '\u{302}'), ('ź', 'z', '\u{301}'), ('ż', 'z', '\u{307}'), ('ž', 'z', '\u{30c}'),
+    ('ǎ', 'a', '\u{30c}'), ('ǐ', 'i', '\u{30c}'), ('ǒ', 'o', '\u{30c}'), ('ǔ', 'u', '\u{30c}'), ('ǧ', 'g', '\u{30c}'),
+    ('ǩ', 'k', '\u{30c}'), ('ǫ', 'o', '\u{328}'), ('ǰ', 'j', '\u{30c}'), ('ǵ', 'g', '\u{301}'), ('ǹ', 'n', '\u{300}'),
+    ('ȟ', 'h', '\u{30c}'), ('ȧ', 'a', '\u{307}'), ('ȩ', 'e', '\u{327}'), ('ȯ', 'o', '\u{307}'), ('ȳ', 'y', '\u{304}'),
+    ('ḃ', 'b', '\u{307}'), ('ḋ', 'd', '\u{307}'), ('ḑ', 'd', '\u{327}'), ('ḟ', 'f', '\u{307}'), ('ḡ', 'g', '\u{304}'),
+    ('ḣ', 'h', '\u{307}'), ('ḧ', 'h', '\u{308}'), ('ḩ', 'h', '\u{327}'), ('ḱ', 'k', '\u{301}'), ('ḿ', 'm', '\u{301}'),
+    ('ṁ', 'm', '\u{307}'), ('ṅ', 'n', '\u{307}'), ('ṕ', 'p', '\u{301}'), ('ṗ', 'p', '\u{307}'), ('ṙ', 'r', '\u{307}'),
+    ('ṡ', 's', '\u{307}'), ('ṫ', 't', '\u{307}'), ('ṽ', 'v', '\u{303}'), ('ẁ', 'w', '\u{300}'), ('ẃ', 'w', '\u{301}'),
+    ('ẅ', 'w', '\u{308}'), ('ẇ', 'w', '\u{307}'), ('ẋ', 'x', '\u{307}'), ('ẍ', 'x', '\u{308}'), ('ẏ', 'y', '\u{307}'),
+    ('ẑ', 'z', '\u{302}'), ('ẗ', 't', '\u{308}'), ('ẘ', 'w', '\u{30a}'), ('ẙ', 'y', '\u{30a}'), ('ẽ', 'e', '\u{303}'),
+    ('ỳ', 'y', '\u{300}'), ('ỹ', 'y', '\u{303}'),
+];
+
+#[cfg(all(test, feature = "collation"))]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 7] = ["zebra", "öl", "Åsa", "ask", "äpple", "ost", "apple"];
+
+    fn sorted(locale: &str) -> Vec<&'static str> {
+        let collator = Collator::new(locale);
+        let mut names = NAMES.to_vec();
+        names.sort_by(|a, b| collator.compare(a, b).then_with(|| a.cmp(b)));
+        names
+    }
+
+    #[test]
+    fn accents_next_to_their_letter() {
+        assert_eq!(sorted("de"), ["apple", "äpple", "Åsa", "ask", "öl", "ost", "zebra"]);
+        assert_eq!(sorted("de-AT"), sorted("de"));
+    }
+
+    #[test]
+    fn swedish_letters_after_z() {
+        assert_eq!(sorted("sv"), ["apple", "ask", "ost", "zebra", "Åsa", "äpple", "öl"]);
+        assert_eq!(sorted("sv_SE"), sorted("sv"));
+        assert_eq!(sorted("fi"), sorted("sv"));
+    }
+
+    #[test]
+    fn danish_letters_after_z() {
+        assert_eq!(sorted("da"), ["apple", "äpple", "ask", "öl", "ost", "zebra", "Åsa"]);
+        assert_eq!(sorted("nb-NO"), sorted("da"));
+    }
+
+    #[test]
+    fn other_languages_sort_like_german() {
+        assert_eq!(sorted("en"), sorted("de"));
+        assert_eq!(sorted(""), sorted("de"));
+    }
+
+    #[test]
+    fn case_and_ligatures() {
+        let collator = Collator::new("de");
+        assert_eq!(collator.compare("Apple", "apple"), Ordering::Equal);
+        assert_eq!(collator.compare("straße", "strasse"), Ordering::Equal);
+        assert_eq!(collator.compare("Æble", "aeble"), Ordering::Equal);
+        assert_eq!(collator.compare("øre", "ore"), Ordering::Greater);
+    }
+
+    #[test]
+    fn default_order_is_by_code_point() {
+        let mut names = NAMES.to_vec();
+        names.sort_by(|a, b| crate::sort::compare_names(a, b));
+        assert_eq!(names, ["apple", "ask", "ost", "zebra", "äpple", "Åsa", "öl"]);
+    }
+}
@@ -0,0 +1,448 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+use crate::filter::FileFilter;
+#[cfg(feature = "icons")]
+use crate::icons::IconSet;
+use crate::sort::{DotfilesPosition, SortBy, SortOrder};
+
+/// Whether the dialog picks an existing entry or a place to save to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DialogMode {
+    /// Pick an existing file (or directory).
+    #[default]
+    Open,
+    /// Pick a file name to save to.
+    Save,
+}
+
+/// How the listing lays entries out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ViewMode {
+    /// One entry per row, with columns for their size and modification time.
+    #[default]
+    List,
+    /// Entries in as many columns as fit, with their name wrapped under them.
+    Grid,
+}
+
+/// A named boolean option displayed as a checkbox in the controls row.
+/// See [`FileDialog::option_checkbox()`](crate::FileDialog::option_checkbox).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OptionCheckbox {
+    /// Identifies the option when reading its value back.
+    pub id: String,
+    /// The text displayed next to the checkbox.
+    pub label: String,
+    /// Whether the checkbox is checked.
+    pub value: bool,
+}
+
+/// The declarative configuration of a [`FileDialog`](crate::FileDialog).
+///
+/// This is an alternative to long builder chains, convenient when dialogs are configured
+/// from data (user settings, plugin manifests, ...). The builder methods set the fields of this
+/// struct, so both ways always produce the same dialog. Most fields match a builder method of the
+/// same name, except for:
+/// - `mode`: [`for_save()`](crate::FileDialog::for_save)
+/// - `dirs_only`: [`dir_only()`](crate::FileDialog::dir_only)
+/// - `filename`: [`default_filename()`](crate::FileDialog::default_filename)
+/// - `extensions` and `globs`: [`filter()`](crate::FileDialog::filter) and [`filter_glob()`](crate::FileDialog::filter_glob)
+/// - `filters`: [`add_filter()`](crate::FileDialog::add_filter)
+/// - `sort_by` and `sort_order`: both set by [`sort_by()`](crate::FileDialog::sort_by)
+/// - `show_preview` and `show_details`: [`with_preview()`](crate::FileDialog::with_preview) and
+///   [`with_details()`](crate::FileDialog::with_details)
+/// - `starred`: [`star()`](crate::FileDialog::star)
+/// - `options`: [`option_checkbox()`](crate::FileDialog::option_checkbox)
+/// - `active_filter` and `show_hidden_files`, which the user changes from the controls row and have no builder method
+///
+/// ```no_run
+/// use imfile::{DialogConfig, DialogMode, FileDialog};
+///
+/// let dialog = FileDialog::from_config(DialogConfig {
+///     mode: DialogMode::Save,
+///     title: String::from("Export"),
+///     ..Default::default()
+/// });
+/// ```
+/// Options taking closures or trait objects, like [`places_provider()`](crate::FileDialog::places_provider),
+/// are only available from the builder. With the `serde` feature enabled, the struct can be
+/// deserialized, with missing fields taking their default value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct DialogConfig {
+    /// Whether the dialog opens or saves. Default is [`DialogMode::Open`].
+    pub mode: DialogMode,
+    /// The directory the dialog opens in. Default is the working directory of the process.
+    pub start_directory: Option<PathBuf>,
+    /// The title of the dialog window.
+    pub title: String,
+    /// The size of the dialog window the first time it is displayed, in pixels.
+    /// ImGui remembers the size the user gives it afterwards.
+    pub window_size: [f32; 2],
+    /// The text of the accept button.
+    pub accept_text: String,
+    /// The text of the cancel button.
+    pub cancel_text: String,
+    /// The text of the accept button when it selects the current directory.
+    pub select_folder_text: String,
+    /// The file name of a save dialog.
+    pub filename: String,
+    /// The extension appended to file names typed without one, lowercase and without the dot.
+    pub default_extension: Option<String>,
+    /// Let the user rename and delete entries.
+    pub allow_file_operations: bool,
+    /// Delete entries permanently when they can't be moved to the trash.
+    pub permanent_delete_fallback: bool,
+    /// Display the "New Folder" button.
+    pub allow_new_folder: bool,
+    /// Ask before a save dialog returns an existing file.
+    pub confirm_overwrite: bool,
+    /// Warn before returning a file that can't be read, or written by save dialogs.
+    pub verify_readable: bool,
+    /// Only list directories.
+    pub dirs_only: bool,
+    /// Only list files with one of these extensions, lowercase and without the dot,
+    /// or matching one of `globs`. Both empty lists every file.
+    pub extensions: Vec<String>,
+    /// Only list files whose name matches one of these glob patterns, or with one of `extensions`.
+    pub globs: Vec<String>,
+    /// Filters the user can choose from with the combo box of the controls row.
+    pub filters: Vec<FileFilter>,
+    /// The index of the filter in use in `filters`.
+    pub active_filter: usize,
+    /// Let the accept button select the current directory in open mode.
+    pub accept_dirs_too: bool,
+    /// Open entries with a single click instead of a double click.
+    pub single_click_activates: bool,
+    /// Let the user select several entries.
+    pub multi_select: bool,
+    /// Return the target of symbolic links instead of the links themselves.
+    pub resolve_symlinks: bool,
+    /// Let open dialogs select symbolic links whose target doesn't exist.
+    pub select_broken_symlinks: bool,
+    /// Only let the user dismiss the dialog through its buttons: no collapsing, close button or Escape.
+    pub force_decision: bool,
+    /// Give the window a close button, which cancels the dialog. Ignored with `force_decision`.
+    pub close_button: bool,
+    /// List hidden files.
+    pub show_hidden_files: bool,
+    /// Match the query of the search box case-sensitively. The "Aa" button next to the search box toggles it.
+    pub case_sensitive_search: bool,
+    /// Match names against the query of the search box as a regular expression. The ".*" button next to
+    /// the search box toggles it.
+    #[cfg(feature = "regex")]
+    pub regex_search: bool,
+    /// The column the listing is sorted by. Clicking the headers of the listing changes it.
+    pub sort_by: SortBy,
+    /// The direction the listing is sorted in.
+    pub sort_order: SortOrder,
+    /// List directories before files.
+    pub dirs_first: bool,
+    /// Compare the numbers in names by value, so that `2` comes before `10`.
+    pub natural_sort: bool,
+    /// Where dotfiles are placed in the listing.
+    pub dotfiles_position: DotfilesPosition,
+    /// The locale used to sort names, if any.
+    #[cfg(feature = "collation")]
+    pub collation_locale: Option<String>,
+    /// Display the path bar.
+    pub show_path_bar: bool,
+    /// Display the side panel.
+    pub show_sidebar: bool,
+    /// List the common places (home, desktop, ...) in the side panel.
+    pub common_places: bool,
+    /// How often the places of the side panel are queried again while the dialog is open, if ever.
+    pub places_refresh_interval: Option<Duration>,
+    /// The glyphs drawn in front of the entries of the listing, if their font is loaded. Text prefixes are drawn otherwise.
+    #[cfg(feature = "icons")]
+    pub icons: Option<IconSet>,
+    /// Display the beginning of the selected file next to the listing.
+    pub show_preview: bool,
+    /// Display the metadata of the selected entry next to the listing.
+    pub show_details: bool,
+    /// How the listing lays entries out.
+    pub view_mode: ViewMode,
+    /// Display the modification time of the entries in the listing.
+    pub show_modified_column: bool,
+    /// Display the controls row.
+    pub show_controls: bool,
+    /// Display the status bar, counting the entries listed and hidden.
+    pub show_status_bar: bool,
+    /// Files listed in the "Starred" section of the side panel.
+    pub starred: Vec<PathBuf>,
+    /// Checkboxes displayed in the controls row.
+    pub options: Vec<OptionCheckbox>,
+}
+
+impl Default for DialogConfig {
+    fn default() -> Self {
+        Self {
+            mode: DialogMode::Open,
+            start_directory: None,
+            title: String::from("Open File"),
+            window_size: [600.0, 400.0],
+            accept_text: String::from("Open"),
+            cancel_text: String::from("Cancel"),
+            select_folder_text: String::from("Select this folder"),
+            filename: String::new(),
+            default_extension: None,
+            allow_file_operations: false,
+            permanent_delete_fallback: false,
+            allow_new_folder: true,
+            confirm_overwrite: true,
+            verify_readable: false,
+            dirs_only: false,
+            extensions: Vec::new(),
+            globs: Vec::new(),
+            filters: Vec::new(),
+            active_filter: 0,
+            accept_dirs_too: false,
+            single_click_activates: false,
+            multi_select: false,
+            resolve_symlinks: false,
+            select_broken_symlinks: false,
+            force_decision: false,
+            close_button: false,
+            show_hidden_files: false,
+            case_sensitive_search: false,
+            #[cfg(feature = "regex")]
+            regex_search: false,
+            sort_by: SortBy::default(),
+            sort_order: SortOrder::default(),
+            dirs_first: true,
+            natural_sort: true,
+            dotfiles_position: DotfilesPosition::default(),
+            #[cfg(feature = "collation")]
+            collation_locale: None,
+            show_path_bar: true,
+            show_sidebar: true,
+            common_places: true,
+            places_refresh_interval: None,
+            #[cfg(feature = "icons")]
+            icons: None,
+            show_preview: false,
+            show_details: false,
+            view_mode: ViewMode::default(),
+            show_modified_column: true,
+            show_controls: true,
+            show_status_bar: true,
+            starred: Vec::new(),
+            options: Vec::new(),
+        }
+    }
+}
+
+impl DialogConfig {
+    /// Checks that the options make sense together. See [`ConfigError`] for the rules.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.mode == DialogMode::Save && self.dirs_only {
+            return Err(ConfigError::DirOnlyInSaveMode);
+        }
+        if self.mode == DialogMode::Save && self.accept_dirs_too {
+            return Err(ConfigError::AcceptDirsTooInSaveMode);
+        }
+        if self.mode == DialogMode::Save && self.multi_select {
+            return Err(ConfigError::MultiSelectInSaveMode);
+        }
+        if self.mode == DialogMode::Save && !self.show_controls {
+            return Err(ConfigError::SaveWithoutControls);
+        }
+        if self.dirs_only && !(self.extensions.is_empty() && self.globs.is_empty() && self.filters.is_empty()) {
+            return Err(ConfigError::DirOnlyWithFilters);
+        }
+        if self.dirs_only && !self.show_controls {
+            return Err(ConfigError::DirOnlyWithoutControls);
+        }
+        if self.multi_select && self.single_click_activates {
+            return Err(ConfigError::MultiSelectWithSingleClick);
+        }
+        if self.multi_select && !self.show_controls {
+            return Err(ConfigError::MultiSelectWithoutControls);
+        }
+        if self.accept_dirs_too && !self.show_controls {
+            return Err(ConfigError::AcceptDirsTooWithoutControls);
+        }
+        if self.options.iter().any(|option| option.id.is_empty()) {
+            return Err(ConfigError::EmptyOptionId);
+        }
+        if !self.filters.is_empty() && self.active_filter >= self.filters.len() {
+            return Err(ConfigError::ActiveFilterOutOfRange);
+        }
+        Ok(())
+    }
+}
+
+/// A combination of builder options that can't work together.
+///
+/// Returned by [`FileDialog::validate()`](crate::FileDialog::validate). Debug builds also
+/// check the configuration when the dialog is spawned and panic with this error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// [`dir_only()`](crate::FileDialog::dir_only) was set after [`for_save()`](crate::FileDialog::for_save).
+    /// Save dialogs need a file name, so they can't be restricted to directories.
+    DirOnlyInSaveMode,
+    /// [`dir_only()`](crate::FileDialog::dir_only) lists no files, so the file filters would have no effect.
+    DirOnlyWithFilters,
+    /// [`dir_only()`](crate::FileDialog::dir_only) selects the current directory with the accept button,
+    /// which [`show_controls(false)`](crate::FileDialog::show_controls) removes.
+    DirOnlyWithoutControls,
+    /// [`for_save()`](crate::FileDialog::for_save) takes the file name in the controls row,
+    /// which [`show_controls(false)`](crate::FileDialog::show_controls) removes.
+    SaveWithoutControls,
+    /// [`accept_dirs_too()`](crate::FileDialog::accept_dirs_too) only applies to open dialogs.
+    AcceptDirsTooInSaveMode,
+    /// [`accept_dirs_too()`](crate::FileDialog::accept_dirs_too) selects the current directory with the
+    /// accept button, which [`show_controls(false)`](crate::FileDialog::show_controls) removes.
+    AcceptDirsTooWithoutControls,
+    /// [`multi_select()`](crate::FileDialog::multi_select) only applies to open dialogs, a file is saved to a single path.
+    MultiSelectInSaveMode,
+    /// [`multi_select()`](crate::FileDialog::multi_select) returns the selection with the accept button,
+    /// which [`show_controls(false)`](crate::FileDialog::show_controls) removes.
+    MultiSelectWithoutControls,
+    /// [`single_click_activates()`](crate::FileDialog::single_click_activates) opens what is clicked,
+    /// while [`multi_select()`](crate::FileDialog::multi_select) adds it to the selection.
+    MultiSelectWithSingleClick,
+    /// An [`option_checkbox()`](crate::FileDialog::option_checkbox) was added with an empty ID,
+    /// so its value can't be told apart or read back reliably.
+    EmptyOptionId,
+    /// [`DialogConfig::active_filter`] doesn't point to one of the [`DialogConfig::filters`].
+    ActiveFilterOutOfRange,
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::DirOnlyInSaveMode => write!(f, "dir_only() can't be used with for_save()"),
+            ConfigError::DirOnlyWithFilters => write!(f, "dir_only() can't be used with file filters"),
+            ConfigError::DirOnlyWithoutControls => write!(f, "dir_only() needs the controls row, but show_controls(false) hides it"),
+            ConfigError::SaveWithoutControls => write!(f, "for_save() needs the controls row, but show_controls(false) hides it"),
+            ConfigError::AcceptDirsTooInSaveMode => write!(f, "accept_dirs_too() can't be used with for_save()"),
+            ConfigError::AcceptDirsTooWithoutControls => write!(f, "accept_dirs_too() needs the controls row, but show_controls(false) hides it"),
+            ConfigError::MultiSelectInSaveMode => write!(f, "multi_select() can't be used with for_save()"),
+            ConfigError::MultiSelectWithoutControls => write!(f, "multi_select() needs the controls row, but show_controls(false) hides it"),
+            ConfigError::MultiSelectWithSingleClick => write!(f, "single_click_activates() can't be used with multi_select()"),
+            ConfigError::EmptyOptionId => write!(f, "option_checkbox() was given an empty ID"),
+            ConfigError::ActiveFilterOutOfRange => write!(f, "active_filter is not the index of one of the filters"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(config: DialogConfig) -> Result<(), ConfigError> {
+        config.validate()
+    }
+
+    #[test]
+    fn default_config_is_valid() {
+        assert_eq!(validate(DialogConfig::default()), Ok(()));
+        assert_eq!(validate(DialogConfig { mode: DialogMode::Save, ..DialogConfig::default() }), Ok(()));
+        assert_eq!(validate(DialogConfig { dirs_only: true, ..DialogConfig::default() }), Ok(()));
+    }
+
+    #[test]
+    fn dir_only_in_save_mode() {
+        let config = DialogConfig { mode: DialogMode::Save, dirs_only: true, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::DirOnlyInSaveMode));
+    }
+
+    #[test]
+    fn dir_only_with_filters() {
+        let extensions = DialogConfig { dirs_only: true, extensions: vec![String::from("png")], ..DialogConfig::default() };
+        assert_eq!(validate(extensions), Err(ConfigError::DirOnlyWithFilters));
+        let globs = DialogConfig { dirs_only: true, globs: vec![String::from("*.png")], ..DialogConfig::default() };
+        assert_eq!(validate(globs), Err(ConfigError::DirOnlyWithFilters));
+        let filters = DialogConfig { dirs_only: true, filters: vec![FileFilter::new("Images", &["png"])], ..DialogConfig::default() };
+        assert_eq!(validate(filters), Err(ConfigError::DirOnlyWithFilters));
+    }
+
+    #[test]
+    fn dir_only_without_controls() {
+        let config = DialogConfig { dirs_only: true, show_controls: false, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::DirOnlyWithoutControls));
+    }
+
+    #[test]
+    fn save_without_controls() {
+        let config = DialogConfig { mode: DialogMode::Save, show_controls: false, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::SaveWithoutControls));
+    }
+
+    #[test]
+    fn accept_dirs_too_in_save_mode() {
+        let config = DialogConfig { mode: DialogMode::Save, accept_dirs_too: true, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::AcceptDirsTooInSaveMode));
+    }
+
+    #[test]
+    fn accept_dirs_too_without_controls() {
+        let config = DialogConfig { accept_dirs_too: true, show_controls: false, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::AcceptDirsTooWithoutControls));
+    }
+
+    #[test]
+    fn multi_select_in_save_mode() {
+        let config = DialogConfig { mode: DialogMode::Save, multi_select: true, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::MultiSelectInSaveMode));
+    }
+
+    #[test]
+    fn multi_select_without_controls() {
+        let config = DialogConfig { multi_select: true, show_controls: false, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::MultiSelectWithoutControls));
+    }
+
+    #[test]
+    fn multi_select_with_single_click() {
+        let config = DialogConfig { multi_select: true, single_click_activates: true, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::MultiSelectWithSingleClick));
+    }
+
+    #[test]
+    fn empty_option_id() {
+        let options = vec![OptionCheckbox { id: String::new(), label: String::from("Read-only"), value: false }];
+        assert_eq!(validate(DialogConfig { options, ..DialogConfig::default() }), Err(ConfigError::EmptyOptionId));
+    }
+
+    #[test]
+    fn active_filter_out_of_range() {
+        let filters = vec![FileFilter::new("Images", &["png"]), FileFilter::new("All files", &[])];
+        let config = DialogConfig { filters: filters.clone(), active_filter: 2, ..DialogConfig::default() };
+        assert_eq!(validate(config), Err(ConfigError::ActiveFilterOutOfRange));
+        assert_eq!(validate(DialogConfig { filters, active_filter: 1, ..DialogConfig::default() }), Ok(()));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let config = DialogConfig {
+            mode: DialogMode::Save,
+            title: String::from("Export"),
+            window_size: [800.0, 500.0],
+            start_directory: Some(PathBuf::from("/home/me/Pictures")),
+            filters: vec![FileFilter::new("Images", &["png", "jpg"])],
+            sort_by: SortBy::Size,
+            dotfiles_position: DotfilesPosition::Mixed,
+            options: vec![OptionCheckbox { id: String::from("layers"), label: String::from("Keep layers"), value: true }],
+            ..DialogConfig::default()
+        };
+        let json = serde_json::to_string(&config).unwrap();
+        assert_eq!(serde_json::from_str::<DialogConfig>(&json).unwrap(), config);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn missing_fields_take_their_default() {
+        assert_eq!(serde_json::from_str::<DialogConfig>("{}").unwrap(), DialogConfig::default());
+        let config: DialogConfig = serde_json::from_str(r#"{"mode": "Save", "window_size": [800.0, 500.0]}"#).unwrap();
+        assert_eq!(config, DialogConfig { mode: DialogMode::Save, window_size: [800.0, 500.0], ..DialogConfig::default() });
+    }
+}
@@ -0,0 +1,354 @@
+use std::ffi::OsString;
+use std::fs::{self, DirEntry};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::normalize::nfc;
+
+/// The kind of filesystem object an entry of the listing refers to.
+///
+/// The kind is captured once, when the directory is listed, so that sorting and rendering
+/// always agree on what an entry is even if the filesystem changes in the meantime. It is
+/// only read again when the entry is activated, to act on what the entry is by then.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    /// A directory.
+    Dir,
+    /// A regular file.
+    File,
+    /// A symbolic link to an existing file or directory.
+    Symlink {
+        /// Whether the link points to a directory.
+        to_dir: bool,
+    },
+    /// A symbolic link whose target doesn't exist.
+    BrokenSymlink,
+    /// Anything else: sockets, devices, ...
+    Other,
+}
+
+impl EntryKind {
+    /// Classifies a directory entry. The file type of a `DirEntry` doesn't follow symbolic links, like
+    /// `symlink_metadata()`, so only links are followed, to find out what they point to.
+    pub(crate) fn of(entry: &DirEntry) -> Self {
+        match entry.file_type() {
+            Ok(file_type) if file_type.is_dir() => EntryKind::Dir,
+            Ok(file_type) if file_type.is_file() => EntryKind::File,
+            Ok(file_type) if file_type.is_symlink() => Self::link_to(fs::metadata(entry.path())),
+            _ => EntryKind::Other,
+        }
+    }
+
+    /// Classifies the file at `path` like [`EntryKind::of()`], reading its metadata again.
+    /// Returns `None` if there is nothing at `path`.
+    pub(crate) fn at(path: &Path) -> Option<Self> {
+        let file_type = fs::symlink_metadata(path).ok()?.file_type();
+        Some(if file_type.is_dir() {
+            EntryKind::Dir
+        } else if file_type.is_file() {
+            EntryKind::File
+        } else if file_type.is_symlink() {
+            Self::link_to(fs::metadata(path))
+        } else {
+            EntryKind::Other
+        })
+    }
+
+    /// Classifies a symbolic link from the metadata of its target.
+    fn link_to(target: io::Result<fs::Metadata>) -> Self {
+        match target {
+            Ok(target) if target.is_dir() || target.is_file() => EntryKind::Symlink { to_dir: target.is_dir() },
+            Ok(_) => EntryKind::Other,
+            Err(_) => EntryKind::BrokenSymlink,
+        }
+    }
+
+    /// Returns whether the entry can be navigated into.
+    #[inline]
+    pub fn is_dir(self) -> bool {
+        matches!(self, EntryKind::Dir | EntryKind::Symlink { to_dir: true })
+    }
+
+    /// Returns whether the entry can be returned as a file.
+    #[inline]
+    pub fn is_file(self) -> bool {
+        matches!(self, EntryKind::File | EntryKind::Symlink { to_dir: false })
+    }
+
+    /// Returns whether the entry is a symbolic link, broken or not.
+    #[inline]
+    pub fn is_symlink(self) -> bool {
+        matches!(self, EntryKind::Symlink { .. } | EntryKind::BrokenSymlink)
+    }
+}
+
+/// A single entry of a directory listing, see [`FileDialog::entries()`](crate::FileDialog::entries).
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub(crate) path: PathBuf,
+    pub(crate) name: OsString,
+    /// The name normalized to NFC, used for display and comparisons.
+    pub(crate) display_name: String,
+    pub(crate) kind: EntryKind,
+    /// The size of the file in bytes. `None` for directories and files whose metadata can't be read.
+    pub(crate) size: Option<u64>,
+    /// When the entry was last modified, if its metadata can be read.
+    pub(crate) modified: Option<SystemTime>,
+    /// Where the entry points to, if it is a symbolic link.
+    pub(crate) link_target: Option<PathBuf>,
+    /// Whether the entry is hidden unless the user asks for hidden files.
+    pub(crate) hidden: bool,
+    /// The text color the application picked for the entry, if any.
+    pub(crate) color: Option<[f32; 4]>,
+}
+
+impl Entry {
+    pub(crate) fn new(entry: &DirEntry) -> Self {
+        let name = entry.file_name();
+        let kind = EntryKind::of(entry);
+        /* The metadata of a DirEntry doesn't follow symbolic links, the size of their target is wanted */
+        let metadata = if kind.is_symlink() { fs::metadata(entry.path()) } else { entry.metadata() };
+        let metadata = metadata.ok();
+        Self {
+            size: metadata.as_ref().filter(|_| kind.is_file()).map(|metadata| metadata.len()),
+            modified: metadata.and_then(|metadata| metadata.modified().ok()),
+            link_target: if kind.is_symlink() { fs::read_link(entry.path()).ok() } else { None },
+            path: entry.path(),
+            display_name: nfc(&name.to_string_lossy()).into_owned(),
+            hidden: name.as_encoded_bytes().starts_with(b".") || has_hidden_attribute(entry),
+            name,
+            kind,
+            color: None,
+        }
+    }
+}
+
+impl Entry {
+    /// Returns the path of the entry, in the directory displayed by the dialog.
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the name of the entry as displayed, normalized to NFC.
+    #[inline]
+    pub fn name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Returns what the entry was when the directory was read.
+    #[inline]
+    pub fn kind(&self) -> EntryKind {
+        self.kind
+    }
+
+    /// Returns the size of the file in bytes. `None` for directories and files whose metadata can't be read.
+    #[inline]
+    pub fn size(&self) -> Option<u64> {
+        self.size
+    }
+
+    /// Returns when the entry was last modified, if its metadata can be read.
+    #[inline]
+    pub fn modified(&self) -> Option<SystemTime> {
+        self.modified
+    }
+
+    /// Returns where the entry points to, if it is a symbolic link.
+    #[inline]
+    pub fn link_target(&self) -> Option<&Path> {
+        self.link_target.as_deref()
+    }
+
+    /// Returns whether the entry is hidden unless the user asks for hidden files, like dotfiles.
+    #[inline]
+    pub fn is_hidden(&self) -> bool {
+        self.hidden
+    }
+}
+
+#[cfg(test)]
+impl Entry {
+    /// Creates an entry of the current directory without touching the filesystem.
+    pub(crate) fn fake(name: &str, kind: EntryKind) -> Self {
+        Self {
+            path: PathBuf::from(name),
+            name: OsString::from(name),
+            display_name: nfc(name).into_owned(),
+            kind,
+            size: None,
+            modified: None,
+            link_target: None,
+            hidden: name.starts_with('.'),
+            color: None,
+        }
+    }
+}
+
+/// Formats a file size for display, in bytes below a KiB and with one decimal above.
+pub(crate) fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["KiB", "MiB", "GiB", "TiB", "PiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    /* Sizes that would round up to 1024.0 are displayed in the next unit */
+    while size >= 1023.95 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Formats a modification time for display as `YYYY-MM-DD HH:MM`, in UTC.
+/// Returns `None` for times before the Unix epoch.
+pub(crate) fn format_time(time: SystemTime) -> Option<String> {
+    let (year, month, day, seconds) = utc_date(time)?;
+    Some(format!("{:04}-{:02}-{:02} {:02}:{:02}", year, month, day, seconds / 3600, seconds % 3600 / 60))
+}
+
+/// Splits a time into its UTC year, month, day and second of the day.
+/// Returns `None` for times before the Unix epoch.
+pub(crate) fn utc_date(time: SystemTime) -> Option<(i64, i64, i64, u64)> {
+    let seconds = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+    let (days, seconds) = (seconds / 86400, seconds % 86400);
+    /* Converts days since the epoch to a civil date, see http://howardhinnant.github.io/date_algorithms.html */
+    let z = days as i64 + 719468;
+    let era = z / 146097;
+    let day_of_era = z - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    Some((year, month, day, seconds))
+}
+
+/// Returns whether the entry carries the hidden attribute of Windows.
+///
+/// The metadata of a `DirEntry` comes with the directory listing on Windows, so this doesn't
+/// cost an extra call to the filesystem.
+#[cfg(windows)]
+fn has_hidden_attribute(entry: &DirEntry) -> bool {
+    use std::os::windows::fs::MetadataExt;
+    const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+    entry.metadata().is_ok_and(|metadata| metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0)
+}
+
+/// Only Windows has a hidden attribute, names starting with a dot are handled separately.
+#[cfg(not(windows))]
+fn has_hidden_attribute(_entry: &DirEntry) -> bool {
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Lists a directory, returning the name of each entry with whether it is hidden.
+    fn hidden_entries(dir: &Path) -> Vec<(String, bool)> {
+        let mut entries: Vec<_> = fs::read_dir(dir).unwrap()
+            .map(|entry| Entry::new(&entry.unwrap()))
+            .map(|entry| (entry.display_name, entry.hidden))
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    #[test]
+    fn names_starting_with_a_dot_are_hidden() {
+        let root = std::env::temp_dir().join(format!("imfile-hidden-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::create_dir_all(root.join(".config")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        for file in ["..data", "visible.txt", "not.hidden", ".config/settings.toml", ".config/.token"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        let listed = hidden_entries(&root);
+        let nested = hidden_entries(&root.join(".config"));
+        let _ = fs::remove_dir_all(&root);
+
+        assert_eq!(listed, [
+            (String::from("..data"), true),
+            (String::from(".config"), true),
+            (String::from(".git"), true),
+            (String::from("not.hidden"), false),
+            (String::from("src"), false),
+            (String::from("visible.txt"), false),
+        ]);
+        /* Only the name of the entry counts, not the directories it is in */
+        assert_eq!(nested, [(String::from(".token"), true), (String::from("settings.toml"), false)]);
+    }
+
+    #[test]
+    fn sizes_below_a_kib_are_in_bytes() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1), "1 B");
+        assert_eq!(format_size(1023), "1023 B");
+    }
+
+    #[test]
+    fn sizes_change_unit_at_each_power_of_1024() {
+        assert_eq!(format_size(1024), "1.0 KiB");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(1024 * 1024 - 1), "1.0 MiB");
+        assert_eq!(format_size(1024 * 1024), "1.0 MiB");
+        assert_eq!(format_size(1024 * 1024 * 1024 - 1), "1.0 GiB");
+        assert_eq!(format_size(5 * 1024 * 1024 * 1024 + 300 * 1024 * 1024), "5.3 GiB");
+        assert_eq!(format_size(1 << 40), "1.0 TiB");
+        assert_eq!(format_size(1 << 50), "1.0 PiB");
+    }
+
+    #[test]
+    fn sizes_round_to_one_decimal() {
+        assert_eq!(format_size(1024 + 51), "1.0 KiB");
+        assert_eq!(format_size(1024 + 52), "1.1 KiB");
+        assert_eq!(format_size(1023 * 1024), "1023.0 KiB");
+        assert_eq!(format_size(1023 * 1024 + 1000), "1.0 MiB");
+    }
+
+    #[test]
+    fn largest_sizes_stay_in_pib() {
+        assert_eq!(format_size(1 << 60), "1024.0 PiB");
+        assert_eq!(format_size(u64::MAX), "16384.0 PiB");
+    }
+
+    fn at(seconds: u64) -> SystemTime {
+        UNIX_EPOCH + std::time::Duration::from_secs(seconds)
+    }
+
+    #[test]
+    fn epoch() {
+        assert_eq!(utc_date(UNIX_EPOCH), Some((1970, 1, 1, 0)));
+        assert_eq!(format_time(UNIX_EPOCH).as_deref(), Some("1970-01-01 00:00"));
+        assert_eq!(utc_date(at(86399)), Some((1970, 1, 1, 86399)));
+        assert_eq!(utc_date(at(86400)), Some((1970, 1, 2, 0)));
+    }
+
+    #[test]
+    fn leap_days() {
+        assert_eq!(utc_date(at(1709210096)), Some((2024, 2, 29, 45296)));
+        assert_eq!(format_time(at(1709210096)).as_deref(), Some("2024-02-29 12:34"));
+        assert_eq!(utc_date(at(951782400)), Some((2000, 2, 29, 0)));
+        /* 2100 is not a leap year */
+        assert_eq!(utc_date(at(4107456000)), Some((2100, 2, 28, 0)));
+        assert_eq!(utc_date(at(4107456000 + 86400)), Some((2100, 3, 1, 0)));
+    }
+
+    #[test]
+    fn end_of_year() {
+        assert_eq!(utc_date(at(946684799)), Some((1999, 12, 31, 86399)));
+        assert_eq!(format_time(at(946684799)).as_deref(), Some("1999-12-31 23:59"));
+        assert_eq!(utc_date(at(946684800)), Some((2000, 1, 1, 0)));
+    }
+
+    #[test]
+    fn times_before_the_epoch() {
+        let before = UNIX_EPOCH - std::time::Duration::from_secs(1);
+        assert_eq!(utc_date(before), None);
+        assert_eq!(format_time(before), None);
+    }
+}
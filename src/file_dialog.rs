@@ -1,7 +1,77 @@
-use imgui::Condition;
-use std::cmp::Ordering;
+use imgui::{
+    Condition, ItemHoveredFlags, Key, MouseButton, MouseCursor, SelectableFlags, StyleColor, TableColumnFlags, TableColumnSetup,
+    TableFlags, TableSortDirection, WindowFlags, WindowFocusedFlags,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
 use std::fs;
-use std::path::{PathBuf};
+use std::io::Read;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use crate::config::{ConfigError, DialogConfig, DialogMode, OptionCheckbox, ViewMode};
+use crate::entry::{format_size, format_time, Entry, EntryKind};
+use crate::filter::{matches_patterns, normalize_extension, swap_extension, FileFilter};
+use crate::memory::DialogMemory;
+use crate::navigation::{NavEvent, NavigationLog, NavTarget};
+use crate::places::{added_devices, collect_places, home_dir, CommonPlaces, Place, PlaceSection, PlacesProvider};
+use crate::search::{Debounce, SearchHistory, SearchMatcher, SEARCH_DEBOUNCE};
+use crate::sort::{DotfilesPosition, NameComparator, SortBy, SortOptions, SortOrder};
+use crate::state::DialogState;
+use crate::trash;
+
+/// ID of the popup asking whether to replace an existing file.
+const OVERWRITE_POPUP: &str = "File exists##Overwrite";
+/// ID of the popup warning that the chosen file can't be read or written.
+const ACCESS_POPUP: &str = "Cannot use file##Access";
+/// ID of the popup confirming the deletion of an entry.
+const DELETE_POPUP: &str = "Delete##Delete entry";
+/// ID of the popup listing the path segments collapsed out of the path bar.
+const CRUMBS_POPUP: &str = "Collapsed path";
+/// ID of the popup listing the keyboard shortcuts.
+const HELP_POPUP: &str = "Keyboard shortcuts##Help";
+/// ID of the popup displaying the details of an entry.
+const PROPERTIES_POPUP: &str = "Properties##Entry properties";
+/// ID of the context menu of the current directory, opened between the entries of the listing.
+const LISTING_POPUP: &str = "Directory menu";
+/// Label of the button standing for the collapsed path segments.
+const ELLIPSIS: &str = "…";
+/// Color of the error messages displayed in the dialog.
+const ERROR_COLOR: [f32; 4] = [1.0, 0.4, 0.4, 1.0];
+/// Color mixed into the text color of the style for directories.
+const DIR_TINT: [f32; 4] = [0.3, 0.55, 1.0, 1.0];
+/// Height of the path bar at the top of the dialog.
+const PATH_BAR_HEIGHT: f32 = 32.0;
+/// Height reserved for the controls row at the bottom of the dialog.
+const CONTROLS_HEIGHT: f32 = 32.0;
+/// Height added to the controls row for the widgets of [`FileDialog::footer()`].
+const FOOTER_HEIGHT: f32 = 28.0;
+/// Height reserved for the status bar below the controls row.
+const STATUS_BAR_HEIGHT: f32 = 24.0;
+/// Width of the side panel.
+const SIDEBAR_WIDTH: f32 = 160.0;
+/// Width of the filename field of save dialogs.
+const FILENAME_WIDTH: f32 = 200.0;
+/// How many bytes of a file the preview pane displays at most.
+const PREVIEW_BYTES: u64 = 16 * 1024;
+/// Initial width of the preview pane.
+const PREVIEW_WIDTH: f32 = 200.0;
+/// Width of the splitter resizing the preview pane.
+const SPLITTER_WIDTH: f32 = 4.0;
+/// Smallest width of the cells of the listing in grid view.
+const GRID_CELL_WIDTH: f32 = 110.0;
+/// Most lines a name is wrapped to in grid view.
+const GRID_NAME_LINES: usize = 3;
+/// Width of the column of keys in the popup listing the keyboard shortcuts.
+const HELP_KEYS_WIDTH: f32 = 80.0;
+/// How long a rename or a move to the trash can be undone.
+const UNDO_TIMEOUT: Duration = Duration::from_secs(5);
+/// How long devices that appear while the dialog is open stay highlighted in the side panel.
+const NEW_DEVICE_HIGHLIGHT: Duration = Duration::from_secs(10);
+/// Color of the devices that appeared while the dialog is open.
+const NEW_DEVICE_COLOR: [f32; 4] = [0.4, 0.9, 0.5, 1.0];
 
 /// The file dialog offered by the crate for use with ImGui.
 ///
@@ -19,18 +89,1039 @@ use std::path::{PathBuf};
 /// or [`spawn`](crate::file_dialog::FileDialog::spawn), the former intended to be used when you wish to reuse the same dialog
 /// multiple times:
 /// ```no_run
-/// if let Some(filename) = file_dialog.spawn_borrowed(&ui) {
-///     println!("Filename given: {}", filename.display());
+/// # use imfile::{DialogResult, FileDialog};
+/// # fn draw(ui: &imgui::Ui, file_dialog: &mut FileDialog) {
+/// // Called every frame, with the same `file_dialog`
+/// match file_dialog.spawn_borrowed(ui) {
+///     DialogResult::StillOpen => {}
+///     DialogResult::Selected(filename) => println!("Filename given: {}", filename.display()),
+///     DialogResult::SelectedMany(filenames) => println!("{} files given", filenames.len()),
+///     DialogResult::Cancelled => println!("No file selected."),
 /// }
+/// # }
 /// ```
+/// A configured dialog can also be used as a template and cloned for every use. Closures and
+/// [`PlacesProvider`]s are shared between the clones rather than duplicated. A clone opens in the
+/// directory of the original with the same configuration, but none of what the user was doing in it:
+/// the selection, the search and its history, the navigation history and any popup are left behind.
 pub struct FileDialog {
-    accept_text: String,
-    cancel_text: String,
-    title: String,
-    filename: String, 
-    is_open: bool,
-    dirs_only: bool,
-    show_hidden_files: bool,
+    config: DialogConfig,
+    current_dir: PathBuf,
+    listing: Listing,
+    dirty: bool,
+    draw_stats: DrawStats,
+    selection: Vec<PathBuf>,
+    /// A choice stopped on its way out by a question to the user.
+    pending_choice: Option<PendingAccept>,
+    focus_filename: bool,
+    new_folder: Option<NameInput>,
+    renaming: Option<Rename>,
+    /// The path typed in the path bar, replacing its buttons while it is edited.
+    editing_path: Option<PathInput>,
+    pending_delete: Option<Delete>,
+    /// The last rename or move to the trash, offered to be undone for a few seconds.
+    undo: Option<UndoToast>,
+    /// The details of the entry displayed in the properties popup.
+    properties: Vec<(&'static str, String)>,
+    /// An entry that should take keyboard focus the next time it is drawn.
+    pending_focus: Option<PathBuf>,
+    /// Accept the highlighted entry or the selection the next time the dialog is drawn.
+    pending_accept: bool,
+    search: String,
+    /// Matches the names against the last valid query of the search box.
+    search_matcher: SearchMatcher,
+    /// Why the query of the search box isn't a valid regular expression, if it isn't.
+    search_error: Option<String>,
+    /// Filters the listing once the user stops typing in the search box.
+    search_debounce: Debounce,
+    search_box: SearchBox,
+    /// The last queries of the search box, offered under it while it is empty.
+    search_history: SearchHistory,
+    /// Whether the search history is shown under the search box.
+    history_open: bool,
+    /// The query of the search history chosen with the arrow keys, accepted with Enter.
+    history_highlight: Option<usize>,
+    /// The entry clicked last, when not selecting several entries.
+    highlighted: Option<PathBuf>,
+    places_providers: Vec<Rc<dyn PlacesProvider>>,
+    /// What the places providers returned last. `None` until the dialog is drawn.
+    places: Option<CachedPlaces>,
+    /// Listed first, keeping what it read from the filesystem between queries.
+    common_places: CommonPlaces,
+    accept_button_fn: Option<AcceptButtonFn>,
+    cancel_button_fn: Option<CancelButtonFn>,
+    footer: Option<FooterFn>,
+    validator: Option<ValidatorFn>,
+    entry_color: Option<EntryColorFn>,
+    /// Why the validator refused the path the user chose last.
+    validation_error: Option<String>,
+    name_comparator: Option<NameComparator>,
+    thumbnails: Option<Thumbnails>,
+    /// The file displayed in the preview pane.
+    preview: Option<Preview>,
+    preview_width: f32,
+    /// Where the dialog records its last directory, and under which purpose.
+    memory: Option<(DialogMemory, String)>,
+    #[cfg(feature = "regex")]
+    regex_filters: Vec<regex::Regex>,
+    navigation_log: NavigationLog,
+    accessibility: AccessibilityState,
+    /// The ImGui frame the dialog was last drawn in.
+    last_frame: Option<i32>,
+    /// Whether the dialog was drawn or navigated, after which it no longer moves to its start directory.
+    started: bool,
+    /// Whether the window was collapsed when drawn last, which keeps the dialog from reading the filesystem.
+    collapsed: bool,
+}
+
+/// The details and the beginning of the entry displayed in the preview pane, gathered when it gets selected.
+#[derive(Debug, Clone)]
+struct Preview {
+    path: PathBuf,
+    /// The metadata of the entry and their labels, empty unless details are displayed.
+    details: Vec<(&'static str, String)>,
+    /// The text of the file, or why it can't be displayed. `None` for directories,
+    /// or unless the preview is displayed.
+    text: Option<Result<String, String>>,
+}
+
+impl Preview {
+    /// Gathers what the pane displays about `path`.
+    fn load(path: PathBuf, details: bool, text: bool) -> Self {
+        Self {
+            details: if details { entry_details(&path) } else { Vec::new() },
+            text: (text && path.is_file()).then(|| read_preview(&path)),
+            path,
+        }
+    }
+}
+
+/// Returns the metadata displayed in the details of an entry.
+fn entry_details(path: &Path) -> Vec<(&'static str, String)> {
+    let mut details = vec![("Path", path.display().to_string())];
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(err) => {
+            details.push(("Error", format!("Cannot read metadata: {}", err.kind())));
+            return details;
+        }
+    };
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(path).map_or_else(|err| format!("? ({})", err.kind()), |target| target.display().to_string());
+        details.push(("Link to", target));
+    }
+    /* The size and times of the target matter more than the ones of the link */
+    let target = fs::metadata(path).unwrap_or_else(|_| metadata.clone());
+    if target.is_file() {
+        details.push(("Size", format!("{} bytes ({})", target.len(), format_size(target.len()))));
+    }
+    let time = |time: std::io::Result<std::time::SystemTime>| {
+        time.ok().and_then(format_time).map_or_else(|| String::from("—"), |time| format!("{} UTC", time))
+    };
+    details.push(("Created", time(target.created())));
+    details.push(("Modified", time(target.modified())));
+    details.push(("Accessed", time(target.accessed())));
+    #[cfg(unix)]
+    details.push(("Permissions", format_permissions(&metadata)));
+    details
+}
+
+/// Formats the type and permissions of an entry like `ls -l` does, eg. `drwxr-xr-x`.
+#[cfg(unix)]
+fn format_permissions(metadata: &fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = metadata.permissions().mode();
+    let kind = match metadata.file_type() {
+        file_type if file_type.is_dir() => 'd',
+        file_type if file_type.is_symlink() => 'l',
+        _ => '-',
+    };
+    let permissions = (0..9).rev().map(|bit| match mode & (1 << bit) {
+        0 => '-',
+        _ => ['x', 'w', 'r'][bit % 3],
+    });
+    std::iter::once(kind).chain(permissions).collect()
+}
+
+/// Reads the first [`PREVIEW_BYTES`] of a file, so that large files don't stall the frame.
+fn read_preview(path: &Path) -> Result<String, String> {
+    let mut bytes = Vec::new();
+    match fs::File::open(path).and_then(|file| file.take(PREVIEW_BYTES).read_to_end(&mut bytes)) {
+        Err(err) => Err(format!("Cannot read file: {}", err.kind())),
+        /* Text files don't contain NUL bytes, most binary formats do early on */
+        Ok(_) if bytes.contains(&0) => Err(String::from("Binary file")),
+        Ok(read) => {
+            let mut text = String::from_utf8_lossy(&bytes).into_owned();
+            if read as u64 == PREVIEW_BYTES {
+                text.push_str("\n...");
+            }
+            Ok(text)
+        }
+    }
+}
+
+/// Returns the texture of the thumbnail of a file, if it has one.
+type ThumbnailFn = Rc<dyn Fn(&Path) -> Option<imgui::TextureId>>;
+
+/// The thumbnails drawn in grid view, see [`FileDialog::thumbnail_provider()`].
+#[derive(Clone)]
+struct Thumbnails {
+    provider: ThumbnailFn,
+    size: [f32; 2],
+    /// The thumbnails of the current directory, including the files without one.
+    cache: HashMap<PathBuf, Option<imgui::TextureId>>,
+}
+
+/// Draws the accept button and returns whether it was activated.
+type AcceptButtonFn = Rc<RefCell<dyn FnMut(&imgui::Ui, &AcceptState) -> bool>>;
+
+/// Picks the text color of an entry of the listing, see [`FileDialog::entry_color()`].
+type EntryColorFn = Rc<dyn Fn(&Path, bool) -> Option<[f32; 4]>>;
+
+/// Checks a path before the dialog returns it, see [`FileDialog::validator()`].
+type ValidatorFn = Rc<dyn Fn(&Path) -> Result<(), String>>;
+
+/// Draws the cancel button, given its label, and returns whether it was activated.
+type CancelButtonFn = Rc<RefCell<dyn FnMut(&imgui::Ui, &str) -> bool>>;
+
+/// Draws the widgets of the application above the buttons of the controls row.
+type FooterFn = Rc<RefCell<dyn FnMut(&imgui::Ui)>>;
+
+/// The state of the accept button, handed to the closure set with
+/// [`FileDialog::accept_button_fn()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AcceptState {
+    /// The text the button should display. It counts the selected entries of multi-select dialogs, like "Open (3)".
+    pub label: String,
+    /// Whether the button may be activated. Draw it disabled when this is `false`.
+    pub enabled: bool,
+    /// Explains why the button is disabled, if there's a reason worth showing.
+    pub tooltip: Option<String>,
+}
+
+/// What the dialog did to draw the last frame, see [`FileDialog::draw_stats()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct DrawStats {
+    /// How many entries of the listing were drawn.
+    pub entries_drawn: usize,
+    /// How many times the listing was filtered again, after reading the directory, sorting it, or changing the
+    /// search or the filters. Zero when nothing changed since the previous frame.
+    pub rebuilds: usize,
+    /// How many of the entries drawn came from the listing of the previous frames, without any rebuild.
+    pub cache_hits: usize,
+}
+
+/// What happened to the dialog during a frame, returned by the `spawn*` family of functions.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum DialogResult {
+    /// The user hasn't decided yet. Keep spawning the dialog.
+    #[default]
+    StillOpen,
+    /// The user chose the given path.
+    Selected(PathBuf),
+    /// The user chose the given paths, in the order they were selected.
+    /// Only returned by dialogs with [`multi_select()`](FileDialog::multi_select).
+    SelectedMany(Vec<PathBuf>),
+    /// The user pressed the cancel button or Escape, or closed the window.
+    Cancelled,
+}
+
+impl DialogResult {
+    /// Returns the chosen path, if any.
+    ///
+    /// This discards the difference between [`StillOpen`](DialogResult::StillOpen) and
+    /// [`Cancelled`](DialogResult::Cancelled), like `spawn` did before returning a [`DialogResult`].
+    /// Several chosen paths are [`paths()`](DialogResult::paths) territory; this returns `None` for them.
+    #[inline]
+    pub fn path(self) -> Option<PathBuf> {
+        match self {
+            DialogResult::Selected(path) => Some(path),
+            _ => None,
+        }
+    }
+
+    /// Returns the chosen paths, empty if the user didn't choose anything.
+    pub fn paths(self) -> Vec<PathBuf> {
+        match self {
+            DialogResult::Selected(path) => vec![path],
+            DialogResult::SelectedMany(paths) => paths,
+            _ => Vec::new(),
+        }
+    }
+
+    /// Returns `true` if the user hasn't decided yet.
+    #[inline]
+    pub fn is_open(&self) -> bool {
+        *self == DialogResult::StillOpen
+    }
+}
+
+impl Clone for FileDialog {
+    fn clone(&self) -> Self {
+        Self {
+            config: self.config.clone(),
+            current_dir: self.current_dir.clone(),
+            listing: Listing::default(),
+            dirty: true,
+            draw_stats: DrawStats::default(),
+            selection: Vec::new(),
+            pending_choice: None,
+            focus_filename: true,
+            new_folder: None,
+            renaming: None,
+            editing_path: None,
+            pending_delete: None,
+            undo: None,
+            properties: Vec::new(),
+            pending_focus: None,
+            pending_accept: false,
+            search: String::new(),
+            search_matcher: SearchMatcher::new("", false),
+            search_error: None,
+            search_debounce: Debounce::default(),
+            search_box: SearchBox::default(),
+            search_history: SearchHistory::default(),
+            history_open: false,
+            history_highlight: None,
+            highlighted: None,
+            places_providers: self.places_providers.clone(),
+            places: None,
+            common_places: CommonPlaces::new(),
+            accept_button_fn: self.accept_button_fn.clone(),
+            cancel_button_fn: self.cancel_button_fn.clone(),
+            footer: self.footer.clone(),
+            validator: self.validator.clone(),
+            entry_color: self.entry_color.clone(),
+            validation_error: None,
+            name_comparator: self.name_comparator.clone(),
+            thumbnails: self.thumbnails.as_ref().map(|thumbnails| Thumbnails {
+                provider: thumbnails.provider.clone(),
+                size: thumbnails.size,
+                cache: HashMap::new(),
+            }),
+            preview: None,
+            preview_width: PREVIEW_WIDTH,
+            memory: self.memory.clone(),
+            #[cfg(feature = "regex")]
+            regex_filters: self.regex_filters.clone(),
+            navigation_log: NavigationLog::default(),
+            accessibility: AccessibilityState::default(),
+            last_frame: None,
+            started: self.started,
+            collapsed: false,
+        }
+    }
+}
+
+impl fmt::Debug for FileDialog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("FileDialog");
+        debug
+            .field("config", &self.config)
+            .field("current_dir", &self.current_dir)
+            .field("listing", &self.listing.entries.len())
+            .field("dirty", &self.dirty)
+            .field("draw_stats", &self.draw_stats)
+            .field("selection", &self.selection)
+            .field("pending_choice", &self.pending_choice)
+            .field("focus_filename", &self.focus_filename)
+            .field("new_folder", &self.new_folder)
+            .field("renaming", &self.renaming)
+            .field("editing_path", &self.editing_path)
+            .field("pending_delete", &self.pending_delete)
+            .field("undo", &self.undo)
+            .field("properties", &self.properties)
+            .field("pending_focus", &self.pending_focus)
+            .field("pending_accept", &self.pending_accept)
+            .field("search", &self.search)
+            .field("search_matcher", &self.search_matcher)
+            .field("search_error", &self.search_error)
+            .field("search_debounce", &self.search_debounce)
+            .field("search_box", &self.search_box)
+            .field("search_history", &self.search_history)
+            .field("history_open", &self.history_open)
+            .field("history_highlight", &self.history_highlight)
+            .field("highlighted", &self.highlighted)
+            .field("places_providers", &self.places_providers.len())
+            .field("places", &self.places.as_ref().map(|places| places.sections.len()))
+            .field("accept_button_fn", &self.accept_button_fn.is_some())
+            .field("cancel_button_fn", &self.cancel_button_fn.is_some())
+            .field("footer", &self.footer.is_some())
+            .field("validator", &self.validator.is_some())
+            .field("entry_color", &self.entry_color.is_some())
+            .field("validation_error", &self.validation_error)
+            .field("name_comparator", &self.name_comparator.is_some())
+            .field("thumbnails", &self.thumbnails.as_ref().map(|thumbnails| thumbnails.cache.len()))
+            .field("preview", &self.preview.as_ref().map(|preview| &preview.path))
+            .field("memory", &self.memory.as_ref().map(|(_, purpose)| purpose));
+        #[cfg(feature = "regex")]
+        debug.field("regex_filters", &self.regex_filters);
+        debug
+            .field("navigation_log", &self.navigation_log)
+            .field("accessibility", &self.accessibility)
+            .field("last_frame", &self.last_frame)
+            .field("started", &self.started)
+            .field("collapsed", &self.collapsed)
+            .finish()
+    }
+}
+
+/// What the dialog displayed during the last frame, for [`FileDialog::accessibility_summary()`].
+#[derive(Debug, Clone, Default)]
+struct AccessibilityState {
+    counts: ListingCounts,
+    focused: Option<String>,
+}
+
+/// The places returned by the places providers, grouped by section.
+#[derive(Debug, Clone)]
+struct CachedPlaces {
+    queried: Instant,
+    /// Set by [`refresh_places()`](FileDialog::refresh_places), which queries them again but keeps them to
+    /// compare the devices with.
+    outdated: bool,
+    sections: Vec<(PlaceSection, Vec<Place>)>,
+    /// The devices that weren't listed by the previous query of this opening of the dialog, with when they
+    /// appeared. Dropped once [`NEW_DEVICE_HIGHLIGHT`] elapsed.
+    new_devices: Vec<(PathBuf, Instant)>,
+    /// The recently used paths of the memory of the dialog, and what they were when the places were queried.
+    recent: Vec<(PathBuf, EntryKind)>,
+}
+
+/// The entries of the current directory, sorted when the directory was last read.
+#[derive(Clone, Default)]
+struct Listing {
+    entries: Vec<Entry>,
+    /// Indices of the entries passing the filters, in display order.
+    visible: Vec<usize>,
+    /// The parts of the names of the visible entries matching the search, in the same order.
+    spans: Vec<Vec<Range<usize>>>,
+    counts: ListingCounts,
+    /// Why the directory couldn't be read, displayed instead of the entries.
+    error: Option<String>,
+}
+
+/// A name being typed in the dialog, eg. for a new folder, with the error of the last attempt to use it.
+#[derive(Debug, Clone, Default)]
+struct NameInput {
+    name: String,
+    error: Option<String>,
+}
+
+/// An entry of the listing being renamed.
+#[derive(Debug, Clone)]
+struct Rename {
+    path: PathBuf,
+    input: NameInput,
+    /// Whether the name field still has to take keyboard focus.
+    focus: bool,
+}
+
+/// The path typed in the path bar.
+#[derive(Debug, Clone)]
+struct PathInput {
+    input: NameInput,
+    /// Whether the path field still has to take keyboard focus.
+    focus: bool,
+}
+
+impl PathInput {
+    /// Starts editing the path bar, with the field filled with `dir`.
+    fn new(dir: &Path) -> Self {
+        Self {
+            input: NameInput { name: dir.display().to_string(), error: None },
+            focus: true,
+        }
+    }
+}
+
+/// What activating the accept button does.
+#[derive(Debug, Clone)]
+enum AcceptAction {
+    Return(PathBuf),
+    ReturnMany(Vec<PathBuf>),
+    /// Navigate into the directory instead of returning it.
+    Enter(PathBuf),
+}
+
+/// What happened to the window of the dialog during a frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WindowEvent {
+    Drawn,
+    /// Collapsed, or out of sight: its contents weren't drawn.
+    Collapsed,
+    /// Closed with the close button of [`with_close_button()`](FileDialog::with_close_button).
+    Closed,
+}
+
+impl WindowEvent {
+    /// Tells what happened from whether the contents of the window were drawn and whether it is still open.
+    fn of(drawn: bool, opened: bool) -> Self {
+        match (drawn, opened) {
+            (_, false) => Self::Closed,
+            (true, true) => Self::Drawn,
+            (false, true) => Self::Collapsed,
+        }
+    }
+}
+
+/// A step between the user accepting and the dialog returning, in the order of [`ACCEPT_STAGES`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum AcceptStage {
+    /// Resolves symbolic links if [`resolve_symlinks()`](FileDialog::resolve_symlinks) was set, so that the
+    /// later stages check the exact paths returned.
+    Resolve,
+    /// Runs the validator, which refuses the choice with a message.
+    Validate,
+    /// Asks before replacing an existing file, if [`confirm_overwrite()`](FileDialog::confirm_overwrite) was set.
+    ConfirmOverwrite,
+    /// Warns about entries that can't be used, if [`verify_readable()`](FileDialog::verify_readable) was set.
+    CheckAccess,
+}
+
+/// Every way of accepting goes through these, so that none of them can be bypassed.
+const ACCEPT_STAGES: [AcceptStage; 4] =
+    [AcceptStage::Resolve, AcceptStage::Validate, AcceptStage::ConfirmOverwrite, AcceptStage::CheckAccess];
+
+/// What an [`AcceptStage`] decided about the choice.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum StageOutcome {
+    Continue,
+    /// The choice is dropped, and the dialog stays open.
+    Refuse,
+    /// The user has to answer first. Answering yes resumes with the next stage, answering no drops the choice.
+    Ask(AcceptQuestion),
+}
+
+/// A question stopping a choice from being returned until the user answers it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AcceptQuestion {
+    /// The file exists; replace it?
+    Overwrite(PathBuf),
+    /// The entry can't be used, for this reason; choose it anyway?
+    Access(String),
+}
+
+impl AcceptQuestion {
+    /// Returns the ID of the popup asking the question.
+    fn popup(&self) -> &'static str {
+        match self {
+            Self::Overwrite(_) => OVERWRITE_POPUP,
+            Self::Access(_) => ACCESS_POPUP,
+        }
+    }
+}
+
+/// A choice stopped by a question, waiting for the user to answer it.
+#[derive(Debug, Clone)]
+struct PendingAccept {
+    /// A [`DialogResult::Selected`] or [`DialogResult::SelectedMany`].
+    chosen: DialogResult,
+    /// The index in [`ACCEPT_STAGES`] of the stage to resume with.
+    next_stage: usize,
+    question: AcceptQuestion,
+}
+
+/// How the search box responds to the keyboard.
+#[derive(Debug, Clone, Default)]
+struct SearchBox {
+    /// Hidden with Escape, until Ctrl+F brings it back.
+    hidden: bool,
+    /// Take keyboard focus the next time it is drawn.
+    focus: bool,
+    /// Had keyboard focus when it was drawn last.
+    focused: bool,
+}
+
+/// A key handled by the search box.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SearchKey {
+    /// Ctrl+F, showing and focusing the search box.
+    Find,
+    /// Escape pressed in the search box, clearing it, or hiding it once it is empty.
+    Escape,
+}
+
+/// A keyboard shortcut of the dialog. Pressing keys goes through here, so that the shortcuts described to
+/// the user are always the ones handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Shortcut {
+    /// Opens the focused entry.
+    Activate,
+    Rename,
+    Delete,
+    /// Closes the dialog without choosing anything.
+    Cancel,
+    /// Types a path in the path bar.
+    EditPath,
+    Find,
+    Help,
+}
+
+impl Shortcut {
+    /// Every shortcut, in the order they are described in.
+    const ALL: [Shortcut; 7] = [
+        Shortcut::Activate,
+        Shortcut::EditPath,
+        Shortcut::Find,
+        Shortcut::Rename,
+        Shortcut::Delete,
+        Shortcut::Cancel,
+        Shortcut::Help,
+    ];
+
+    /// Returns the key of the shortcut, and whether Ctrl is held with it.
+    fn key(self) -> (Key, bool) {
+        match self {
+            Shortcut::Activate => (Key::Enter, false),
+            Shortcut::Rename => (Key::F2, false),
+            Shortcut::Delete => (Key::Delete, false),
+            Shortcut::Cancel => (Key::Escape, false),
+            Shortcut::EditPath => (Key::L, true),
+            Shortcut::Find => (Key::F, true),
+            Shortcut::Help => (Key::F1, false),
+        }
+    }
+
+    /// Returns the keys as displayed to the user, eg. `"Ctrl+L"`.
+    fn keys(self) -> String {
+        let (key, ctrl) = self.key();
+        let name = match key {
+            Key::Enter => "Enter",
+            Key::F2 => "F2",
+            Key::Delete => "Delete",
+            Key::Escape => "Escape",
+            Key::L => "L",
+            Key::F => "F",
+            Key::F1 => "F1",
+            _ => unreachable!("no shortcut uses {:?}", key),
+        };
+        if ctrl { format!("Ctrl+{}", name) } else { String::from(name) }
+    }
+
+    /// Returns what the shortcut does.
+    fn description(self) -> &'static str {
+        match self {
+            Shortcut::Activate => "Open the focused entry",
+            Shortcut::Rename => "Rename the focused entry",
+            Shortcut::Delete => "Delete the focused entry",
+            Shortcut::Cancel => "Close the dialog",
+            Shortcut::EditPath => "Type a path",
+            Shortcut::Find => "Search the listing",
+            Shortcut::Help => "Show the keyboard shortcuts",
+        }
+    }
+
+    /// Returns whether the shortcut was pressed this frame.
+    fn pressed(self, ui: &imgui::Ui) -> bool {
+        let (key, ctrl) = self.key();
+        (!ctrl || ui.io().key_ctrl)
+            && (ui.is_key_pressed(key) || (self == Shortcut::Activate && ui.is_key_pressed(Key::KeypadEnter)))
+    }
+}
+
+/// An entry waiting for the user to confirm its deletion.
+#[derive(Debug, Clone)]
+struct Delete {
+    path: PathBuf,
+    /// Whether the entry is moved to the trash rather than deleted permanently.
+    to_trash: bool,
+    /// Whether the entry is a directory with something in it, needing a second confirmation.
+    recursive: bool,
+    error: Option<String>,
+}
+
+/// A file operation that can be undone.
+#[derive(Debug, Clone)]
+enum Undo {
+    /// An entry renamed from `from` to `to`.
+    Rename { from: PathBuf, to: PathBuf },
+    /// An entry moved from `path` to the trash. Permanent deletions can't be undone.
+    Trash { path: PathBuf, trashed: trash::Trashed },
+}
+
+impl Undo {
+    /// Returns what was done, as displayed next to the undo button.
+    fn message(&self) -> String {
+        let name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        match self {
+            Self::Rename { from, to } => format!("Renamed \"{}\" to \"{}\"", name(from), name(to)),
+            Self::Trash { path, .. } => format!("Moved \"{}\" to the trash", name(path)),
+        }
+    }
+
+    /// Does the opposite of what was done, after checking it still can be: the entry is still where it was
+    /// put, and nothing took its old place. Returns the path of the entry, or a message explaining why it
+    /// couldn't be undone.
+    fn undo(&self) -> Result<PathBuf, String> {
+        match self {
+            Self::Rename { from, to } => {
+                if fs::symlink_metadata(to).is_err() {
+                    let name = to.file_name().unwrap_or_default().to_string_lossy();
+                    return Err(format!("\"{}\" no longer exists", name));
+                }
+                rename_entry(to, &from.file_name().unwrap_or_default().to_string_lossy())
+            }
+            Self::Trash { path, trashed } => trash::restore(trashed, path).map(|_| path.clone()),
+        }
+    }
+}
+
+/// The toast offering to undo the last file operation.
+#[derive(Debug, Clone)]
+struct UndoToast {
+    undo: Undo,
+    /// How long the toast stays, counted down with the time between frames.
+    remaining: Duration,
+    /// Why undoing failed, if it did.
+    error: Option<String>,
+}
+
+/// How many entries of the current directory are listed, and how many were left out.
+#[derive(Debug, Clone, Copy, Default)]
+struct ListingCounts {
+    shown: usize,
+    hidden: usize,
+    /// How many entries the filters left out.
+    filtered: usize,
+    /// How many entries would be shown without the search.
+    searched: usize,
+}
+
+/// The message displayed instead of an empty listing, telling why it is empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Placeholder {
+    /// The directory has no entries the dialog could list.
+    EmptyDirectory,
+    /// Every entry of the directory is hidden.
+    AllHidden,
+    /// No file matches the filters.
+    FilteredOut,
+    /// Nothing matches the search.
+    NoMatch,
+}
+
+impl Placeholder {
+    /// Returns the placeholder to display for a listing, or `None` if something is listed.
+    fn for_listing(counts: &ListingCounts) -> Option<Self> {
+        match (counts.shown, counts.hidden) {
+            (0, _) if counts.searched > 0 => Some(Placeholder::NoMatch),
+            (0, _) if counts.filtered > 0 => Some(Placeholder::FilteredOut),
+            (0, 0) => Some(Placeholder::EmptyDirectory),
+            (0, _) => Some(Placeholder::AllHidden),
+            _ => None,
+        }
+    }
+
+    fn message(self) -> &'static str {
+        match self {
+            Placeholder::EmptyDirectory => "This folder is empty",
+            Placeholder::AllHidden      => "Everything in this folder is hidden",
+            Placeholder::FilteredOut    => "No file in this folder matches the filter",
+            Placeholder::NoMatch        => "Nothing in this folder matches the search",
+        }
+    }
+}
+
+/// Picks the directory a dialog opens in: `start_directory` if it is a directory, then the home
+/// directory, then the working directory of the process.
+fn resolve_start_directory(start_directory: Option<&Path>) -> PathBuf {
+    if let Some(start_directory) = start_directory {
+        if start_directory.is_dir() {
+            return start_directory.to_path_buf();
+        }
+        log::error!("Can't start in {}, it is not a directory", start_directory.display());
+        if let Some(home) = home_dir().filter(|home| home.is_dir()) {
+            return home;
+        }
+    }
+    std::env::current_dir().unwrap_or_default()
+}
+
+/// Checks that `name` can be used as the name of a single entry of a directory.
+fn check_entry_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err(String::from("Enter a name"));
+    }
+    if name.contains(std::path::is_separator) || name == "." || name == ".." {
+        return Err(format!("\"{}\" is not a valid name", name));
+    }
+    Ok(())
+}
+
+/// Creates the directory `name` in `dir`, returning its path or a message explaining why it couldn't be created.
+fn create_folder(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    check_entry_name(name)?;
+    let folder = dir.join(name);
+    match fs::create_dir(&folder) {
+        Ok(()) => Ok(folder),
+        Err(err) if err.kind() == std::io::ErrorKind::AlreadyExists => Err(format!("\"{}\" already exists", name)),
+        Err(err) => Err(format!("Cannot create folder: {}", err.kind())),
+    }
+}
+
+/// Renames the entry at `path` to `name`, in the same directory. Returns the new path of the entry
+/// or a message explaining why it couldn't be renamed.
+fn rename_entry(path: &Path, name: &str) -> Result<PathBuf, String> {
+    check_entry_name(name)?;
+    let to = path.with_file_name(name);
+    if to == path {
+        return Ok(to);
+    }
+    /* On case-insensitive filesystems, the new name of a case-only rename exists: it's the entry itself */
+    let from_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let case_only = from_name.to_lowercase() == name.to_lowercase() && same_entry(path, &to);
+    /* fs::rename() replaces existing files on some platforms */
+    if !case_only && fs::symlink_metadata(&to).is_ok() {
+        return Err(format!("\"{}\" already exists", name));
+    }
+    fs::rename(path, &to)
+        .map(|_| to)
+        .map_err(|err| format!("Cannot rename: {}", err.kind()))
+}
+
+/// Returns whether `a` and `b` lead to the same entry, without following symbolic links.
+#[cfg(unix)]
+fn same_entry(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    }
+}
+
+/// Windows doesn't tell entries apart by their case, so names of the same directory equal but for
+/// their case lead to the same entry.
+#[cfg(not(unix))]
+fn same_entry(a: &Path, b: &Path) -> bool {
+    let name = |path: &Path| path.file_name().unwrap_or_default().to_string_lossy().to_lowercase();
+    a.parent() == b.parent() && name(a) == name(b) && fs::symlink_metadata(b).is_ok()
+}
+
+/// Checks that the application will be able to use the entry chosen at `path`: that it can be read after an open
+/// dialog, and written after a save dialog. Returns a message explaining why it can't otherwise.
+///
+/// A file that doesn't exist yet is created and removed again, which tells whether its directory is writable.
+/// Special files like pipes are left alone, as opening them can block until another program opens them too.
+fn check_access(path: &Path, mode: DialogMode) -> Result<(), String> {
+    let name = path.file_name().unwrap_or(path.as_os_str()).to_string_lossy();
+    let result = match (mode, fs::metadata(path)) {
+        (DialogMode::Open, Ok(metadata)) if metadata.is_dir() => fs::read_dir(path).map(|_| ()),
+        (_, Ok(metadata)) if !metadata.is_file() => Ok(()),
+        (DialogMode::Open, _) => fs::File::open(path).map(|_| ()),
+        (DialogMode::Save, Ok(_)) => fs::OpenOptions::new().write(true).open(path).map(|_| ()),
+        (DialogMode::Save, Err(_)) => fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .and_then(|_| fs::remove_file(path)),
+    };
+    let action = if mode == DialogMode::Save { "written" } else { "read" };
+    result.map_err(|err| format!("\"{}\" cannot be {}: {}", name, action, err.kind()))
+}
+
+/// Deletes the entry at `path`. Directories that aren't empty are only deleted if `recursive` is set,
+/// otherwise this returns `Ok(false)`. Returns a message explaining what went wrong on failure.
+fn delete_entry(path: &Path, recursive: bool) -> Result<bool, String> {
+    let metadata = fs::symlink_metadata(path).map_err(|err| format!("Cannot delete: {}", err.kind()))?;
+    let result = if metadata.is_dir() {
+        let empty = fs::read_dir(path).map(|mut entries| entries.next().is_none()).unwrap_or(false);
+        if !empty && !recursive {
+            return Ok(false);
+        }
+        if empty { fs::remove_dir(path) } else { fs::remove_dir_all(path) }
+    } else {
+        fs::remove_file(path)
+    };
+    result
+        .map(|_| true)
+        .map_err(|err| format!("Cannot delete: {}", err.kind()))
+}
+
+/// Adds `path` to the selection, or removes it if it was already selected.
+fn toggle_selection(selection: &mut Vec<PathBuf>, path: &Path) {
+    match selection.iter().position(|selected| selected == path) {
+        Some(i) => {
+            selection.remove(i);
+        }
+        None => selection.push(path.to_path_buf()),
+    }
+}
+
+/// Returns the directory containing `dir`, or `None` at the root of the filesystem, of a drive
+/// (`C:\`) or of a network share (`\\server\share`).
+fn parent_dir(dir: &Path) -> Option<PathBuf> {
+    /* A relative path with a single component has an empty parent, which can't be listed */
+    dir.parent().filter(|parent| !parent.as_os_str().is_empty()).map(Path::to_path_buf)
+}
+
+/// Sets up the columns of the listing in list view and draws their headers.
+/// Returns the sort order picked by clicking a header, if the user just did.
+fn setup_listing_columns(ui: &imgui::Ui, config: &DialogConfig) -> Option<(SortBy, SortOrder)> {
+    let mut sort = None;
+    let sort_flags = |by: SortBy| match (config.sort_by == by, config.sort_order) {
+        (true, SortOrder::Ascending) => TableColumnFlags::DEFAULT_SORT,
+        (true, SortOrder::Descending) => TableColumnFlags::DEFAULT_SORT | TableColumnFlags::PREFER_SORT_DESCENDING,
+        (false, _) => TableColumnFlags::empty(),
+    };
+    ui.table_setup_column_with(TableColumnSetup {
+        flags: TableColumnFlags::WIDTH_STRETCH | TableColumnFlags::NO_HIDE | sort_flags(SortBy::Name),
+        ..TableColumnSetup::new("Name")
+    });
+    ui.table_setup_column_with(TableColumnSetup {
+        flags: TableColumnFlags::WIDTH_FIXED | sort_flags(SortBy::Size),
+        init_width_or_weight: ui.calc_text_size("1023.9 MiB")[0],
+        ..TableColumnSetup::new("Size")
+    });
+    if config.show_modified_column {
+        ui.table_setup_column_with(TableColumnSetup {
+            flags: TableColumnFlags::WIDTH_FIXED | sort_flags(SortBy::Modified),
+            init_width_or_weight: ui.calc_text_size("0000-00-00 00:00")[0],
+            ..TableColumnSetup::new("Modified")
+        });
+    }
+    ui.table_headers_row();
+    if let Some(specs) = ui.table_sort_specs_mut() {
+        specs.conditional_sort(|specs| {
+            if let Some(spec) = specs.iter().next() {
+                let by = match spec.column_idx() {
+                    1 => SortBy::Size,
+                    2 => SortBy::Modified,
+                    _ => SortBy::Name,
+                };
+                let order = match spec.sort_direction() {
+                    Some(TableSortDirection::Descending) => SortOrder::Descending,
+                    _ => SortOrder::Ascending,
+                };
+                sort = Some((by, order));
+            }
+        });
+    }
+    sort
+}
+
+/// Returns the text color of an entry of the listing: the color picked by the application if any,
+/// or the text color of the style, dimmed for hidden entries and tinted blue for directories.
+fn entry_text_color(style: &imgui::Style, entry: &Entry) -> [f32; 4] {
+    let text = style[StyleColor::Text];
+    match entry.color {
+        Some(color) => color,
+        None if entry.hidden => style[StyleColor::TextDisabled],
+        /* Mixed with the text color of the style, so that it stays readable with light and dark themes */
+        None if entry.kind.is_dir() => [0, 1, 2, 3].map(|i| text[i] * 0.6 + DIR_TINT[i] * 0.4),
+        None => text,
+    }
+}
+
+/// Draws a button toggling an option of the search box, in the color of active buttons while it is on.
+/// Returns whether it was clicked.
+fn search_toggle(ui: &imgui::Ui, style: &imgui::Style, label: &str, on: bool) -> bool {
+    let _color = on.then(|| ui.push_style_color(StyleColor::Button, style[StyleColor::ButtonActive]));
+    ui.button(label)
+}
+
+/// Breaks `text` into lines no wider than `width`, for the names of the grid view.
+/// Text beyond [`GRID_NAME_LINES`] lines is cut and replaced with an ellipsis.
+fn wrap_text(ui: &imgui::Ui, text: &str, width: f32) -> String {
+    let (lines, cut) = wrap_lines(ui, text, width);
+    let mut wrapped = lines.into_iter().map(|line| &text[line]).collect::<Vec<_>>().join("\n");
+    if cut {
+        wrapped.push_str("...");
+    }
+    wrapped
+}
+
+/// Returns the byte ranges of `text` on each line [`wrap_text()`] breaks it into, and whether the text was cut.
+fn wrap_lines(ui: &imgui::Ui, text: &str, width: f32) -> (Vec<Range<usize>>, bool) {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        if ui.calc_text_size(&text[start..i + c.len_utf8()])[0] > width && i > start {
+            lines.push(start..i);
+            if lines.len() == GRID_NAME_LINES {
+                return (lines, true);
+            }
+            start = i;
+        }
+    }
+    lines.push(start..text.len());
+    (lines, false)
+}
+
+/// Highlights the parts of a label matched by the search. `lines` are the byte ranges of `text` displayed
+/// on each line of the label, starting at `origin`, and `spans` the matched byte ranges.
+/// The matched parts are drawn again over a background of the text selection color of the style.
+fn highlight_matches(ui: &imgui::Ui, origin: [f32; 2], text: &str, lines: &[Range<usize>], spans: &[Range<usize>], text_color: [f32; 4]) {
+    let background = ui.clone_style()[StyleColor::TextSelectedBg];
+    let draw_list = ui.get_window_draw_list();
+    let line_height = ui.text_line_height();
+    for (row, line) in lines.iter().enumerate() {
+        for span in spans {
+            /* Only the part of the span on this line, if any */
+            let (start, end) = (span.start.max(line.start), span.end.min(line.end));
+            if start >= end {
+                continue;
+            }
+            let x = origin[0] + ui.calc_text_size(&text[line.start..start])[0];
+            let y = origin[1] + row as f32 * line_height;
+            let part = &text[start..end];
+            draw_list.add_rect([x, y], [x + ui.calc_text_size(part)[0], y + line_height], background).filled(true).build();
+            draw_list.add_text([x, y], text_color, part);
+        }
+    }
+}
+
+/// Returns the target of `path` if it is a symbolic link, `path` itself otherwise or if the link is broken.
+fn resolve_symlink(path: PathBuf) -> PathBuf {
+    match fs::symlink_metadata(&path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(&path).unwrap_or(path),
+        _ => path,
+    }
+}
+
+/// Resolves a path typed in the path bar, relative to `dir` unless absolute. `~` stands for the home directory.
+/// Returns the directory to navigate to, and the file to select there if a file was typed.
+fn resolve_typed_path(dir: &Path, typed: &str) -> Result<(PathBuf, Option<PathBuf>), String> {
+    let typed = typed.trim();
+    if typed.is_empty() {
+        return Err(String::from("Type a path"));
+    }
+    let path = match (typed.strip_prefix('~'), home_dir()) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            home.join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => dir.join(typed),
+    };
+    if path.is_dir() {
+        Ok((path, None))
+    } else if let (true, Some(parent)) = (path.is_file(), path.parent()) {
+        Ok((parent.to_path_buf(), Some(path.clone())))
+    } else {
+        Err(format!("{} doesn't exist", path.display()))
+    }
+}
+
+/// Returns the indices of the path segments to collapse so the others fit in `available` pixels.
+/// The root and the deepest segments are kept, the deepest one even if nothing else fits.
+fn hidden_crumbs(widths: &[f32], available: f32, ellipsis_width: f32) -> Range<usize> {
+    if widths.len() <= 2 || widths.iter().sum::<f32>() <= available {
+        return 1..1;
+    }
+    let mut remaining = available - widths[0] - ellipsis_width;
+    let mut first_shown = widths.len() - 1;
+    remaining -= widths[first_shown];
+    while first_shown > 1 && widths[first_shown - 1] <= remaining {
+        first_shown -= 1;
+        remaining -= widths[first_shown];
+    }
+    1..first_shown
+}
+
+/// Moves the cursor so that the next item, `item_width` pixels wide, is horizontally centered.
+fn center_next_item(ui: &imgui::Ui, item_width: f32) {
+    let [width, _] = ui.content_region_avail();
+    let [x, y] = ui.cursor_pos();
+    ui.set_cursor_pos([x + ((width - item_width) / 2.0).max(0.0), y]);
 }
 
 impl FileDialog {
@@ -38,156 +1129,2633 @@ impl FileDialog {
     /// You can also use [`FileDialog::default()`] since it does the same thing.
     #[inline]
     pub fn new() -> Self {
+        Self::from_config(DialogConfig::default())
+    }
+
+    /// Creates a new file dialog from a [`DialogConfig`].
+    ///
+    /// This is equivalent to calling the builder methods matching each field of the configuration.
+    /// Closure-based options can be added with the builder methods afterwards.
+    ///
+    /// The dialog starts in [`DialogConfig::start_directory`], see [`start_directory()`](FileDialog::start_directory).
+    #[inline]
+    pub fn from_config(config: DialogConfig) -> Self {
         Self {
-            accept_text: String::from("Open"),
-            cancel_text: String::from("Cancel"),
-            title: String::from("Open File"),
-            filename: String::new(),
-            is_open: true,
-            dirs_only: false,
-            show_hidden_files: false
+            current_dir: resolve_start_directory(config.start_directory.as_deref()),
+            config,
+            listing: Listing::default(),
+            dirty: true,
+            draw_stats: DrawStats::default(),
+            selection: Vec::new(),
+            pending_choice: None,
+            focus_filename: true,
+            new_folder: None,
+            renaming: None,
+            editing_path: None,
+            pending_delete: None,
+            undo: None,
+            properties: Vec::new(),
+            pending_focus: None,
+            pending_accept: false,
+            search: String::new(),
+            search_matcher: SearchMatcher::new("", false),
+            search_error: None,
+            search_debounce: Debounce::default(),
+            search_box: SearchBox::default(),
+            search_history: SearchHistory::default(),
+            history_open: false,
+            history_highlight: None,
+            highlighted: None,
+            places_providers: Vec::new(),
+            places: None,
+            common_places: CommonPlaces::new(),
+            accept_button_fn: None,
+            cancel_button_fn: None,
+            footer: None,
+            validator: None,
+            entry_color: None,
+            validation_error: None,
+            name_comparator: None,
+            thumbnails: None,
+            preview: None,
+            preview_width: PREVIEW_WIDTH,
+            memory: None,
+            #[cfg(feature = "regex")]
+            regex_filters: Vec::new(),
+            navigation_log: NavigationLog::default(),
+            accessibility: AccessibilityState::default(),
+            last_frame: None,
+            started: false,
+            collapsed: false,
         }
     }
 
+    /// Returns the declarative configuration of the dialog, as set by the builder methods.
+    #[inline]
+    pub fn config(&self) -> &DialogConfig {
+        &self.config
+    }
+
     /// Sets the title of the dialog.
     #[inline]
     pub fn title<S: Into<String>>(mut self, title: S) -> Self {
-        self.title = title.into();
+        self.config.title = title.into();
+        self
+    }
+
+    /// Sets the size of the dialog window the first time it is displayed. Default is `[600.0, 400.0]`.
+    #[inline]
+    pub fn window_size(mut self, size: [f32; 2]) -> Self {
+        self.config.window_size = size;
         self
     }
 
     /// Sets the accept ("Open") text for the dialog.
     #[inline]
     pub fn accept_text<S: Into<String>>(mut self, accept_text: S) -> Self {
-        self.accept_text = accept_text.into();
+        self.config.accept_text = accept_text.into();
         self
     }
 
     /// Sets the reject text for the dialog.
     #[inline]
     pub fn cancel_text<S: Into<String>>(mut self, cancel_text: S) -> Self {
-        self.cancel_text = cancel_text.into();
+        self.config.cancel_text = cancel_text.into();
         self
     }
 
     /// Sets whether the dialog may be used exclusively to open directories.
     #[inline]
     pub fn dir_only(mut self) -> Self {
-        self.dirs_only = true;
+        self.config.dirs_only = true;
+        self
+    }
+
+    /// Allows the dialog to return either a file or the current directory.
+    ///
+    /// Clicking a file still returns it, while the accept button returns the directory
+    /// currently being viewed and is labelled with [`select_folder_text`](FileDialog::select_folder_text).
+    /// Only applies to open dialogs.
+    #[inline]
+    pub fn accept_dirs_too(mut self) -> Self {
+        self.config.accept_dirs_too = true;
+        self
+    }
+
+    /// Only lists files with one of the given extensions, eg. `.filter(&["png", "jpg", "jpeg"])`.
+    ///
+    /// Extensions are compared case-insensitively and may be given with or without the leading dot.
+    /// Calling this again adds to the extensions already allowed. Directories are always listed.
+    pub fn filter(mut self, extensions: &[&str]) -> Self {
+        self.config.extensions.extend(extensions.iter().map(|extension| normalize_extension(extension)));
+        self
+    }
+
+    /// Only lists files whose name matches the glob `pattern`, eg. `.filter_glob("config-*.toml")`.
+    ///
+    /// `*` matches any run of characters, `?` a single character and `[...]` one character of a class,
+    /// like `[abc]`, `[a-z]` or `[!0-9]`. The pattern is matched against the whole file name, ignoring case,
+    /// so `"*.tar.gz"` works as expected. Files matching any glob or any extension given to
+    /// [`filter()`](FileDialog::filter) are listed. Directories are always listed.
+    pub fn filter_glob<S: Into<String>>(mut self, pattern: S) -> Self {
+        self.config.globs.push(pattern.into());
+        self
+    }
+
+    /// Only lists files whose name matches the regular expression `pattern`, eg. `.filter_regex(r"^session-\d{4}\.log$")?`.
+    ///
+    /// The expression is compiled once, here, and searched for in the file name (anchor it to match the whole name).
+    /// Files matching any expression, glob or extension are listed. Directories are always listed.
+    /// Returns an error if `pattern` isn't a valid expression.
+    #[cfg(feature = "regex")]
+    pub fn filter_regex(mut self, pattern: &str) -> Result<Self, regex::Error> {
+        self.regex_filters.push(regex::Regex::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Returns whether the name of the file at `path` passes the extension, glob and regex filters.
+    fn matches_name_filters(&self, path: &Path) -> bool {
+        #[cfg(feature = "regex")]
+        if !self.regex_filters.is_empty() {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            if self.regex_filters.iter().any(|regex| regex.is_match(&name)) {
+                return true;
+            }
+            if self.config.extensions.is_empty() && self.config.globs.is_empty() {
+                return false;
+            }
+        }
+        matches_patterns(path, &self.config.extensions, &self.config.globs)
+    }
+
+    /// Adds a named filter the user can choose from a combo box, eg. `.add_filter("Images", &["png", "jpg"])`.
+    ///
+    /// The first filter added is active when the dialog opens. A filter without extensions lists every file.
+    /// Named filters apply on top of the extensions given to [`filter()`](FileDialog::filter).
+    pub fn add_filter<S: Into<String>>(mut self, name: S, extensions: &[&str]) -> Self {
+        self.config.filters.push(FileFilter::new(name, extensions));
+        self
+    }
+
+    /// Returns the filter chosen in the combo box: its index, in the order the filters were added, and its name,
+    /// as given to [`add_filter()`](FileDialog::add_filter). Returns `None` if the dialog has no named filters.
+    ///
+    /// Read it after [`spawn_borrowed()`](FileDialog::spawn_borrowed) returned a path, eg. to pick the
+    /// format of a saved file. It is the filter the path was chosen with, even if the filter was
+    /// switched in the same frame.
+    #[inline]
+    pub fn active_filter(&self) -> Option<(usize, &str)> {
+        let filter = self.config.filters.get(self.config.active_filter)?;
+        Some((self.config.active_filter, filter.name.as_str()))
+    }
+
+    /// Switches to the filter at `index` of the combo box. In save dialogs, the extension of the file name
+    /// follows the filter when it belongs to one of the filters.
+    fn select_filter(&mut self, index: usize) {
+        if index != self.config.active_filter {
+            self.config.active_filter = index;
+            if let (DialogMode::Save, Some(filter)) = (self.config.mode, self.config.filters.get(index)) {
+                if let Some(filename) = swap_extension(&self.config.filename, &self.config.filters, filter) {
+                    self.config.filename = filename;
+                }
+            }
+            self.apply_filters();
+        }
+    }
+
+    /// Sets the text of the accept button when it selects the current directory. Default is "Select this folder".
+    #[inline]
+    pub fn select_folder_text<S: Into<String>>(mut self, select_folder_text: S) -> Self {
+        self.config.select_folder_text = select_folder_text.into();
         self
     }
 
     /// Sets the dialog for save.
     #[inline]
     pub fn for_save(mut self) -> Self {
-        self.is_open   = false;
-        self.dirs_only = false;
+        self.config.mode      = DialogMode::Save;
+        self.config.dirs_only = false;
         self
     }
 
-    /// Spawns the dialog.
-    ///
-    /// This function spawns the dialog and optionally (Depending on whether the user chose an entry)
-    /// returns a [`PathBuf`] with the path to the chosen file.\
-    /// This is the **owned** version of the `spawn*` family of functions. After calling this function, you won't
-    /// be able to reuse [`self`]. If you wish to continue owning [`self`], then see [`FileDialog::spawn_borrowed()`].
+    /// Sets whether the side panel lists the [`CommonPlaces`] (home, desktop, ...) above the places
+    /// of the other providers. Default is `true`.
+    #[inline]
+    pub fn common_places(mut self, show: bool) -> Self {
+        self.config.common_places = show;
+        self
+    }
+
+    /// Adds a provider of side panel entries.
     ///
-    /// **WARNING**: This dialog expects you to have a [`Ui`](imgui::Ui) ready that the function will immutably borrow.
-    /// See the documentation of [imgui] for details.
-    pub fn spawn(mut self, ui: &imgui::Ui) -> Option<PathBuf> {
+    /// Providers are queried in the order they were added, after the [`common_places()`](FileDialog::common_places).
+    /// The side panel is only shown when there's a place to list. See [`PlacesProvider`] for when they are queried.
+    #[inline]
+    pub fn places_provider(mut self, provider: Box<dyn PlacesProvider>) -> Self {
+        self.places_providers.push(Rc::from(provider));
+        self
+    }
+
+    /// Replaces every registered provider of side panel entries with `providers`.
+    #[inline]
+    pub fn places_providers(mut self, providers: Vec<Box<dyn PlacesProvider>>) -> Self {
+        self.places_providers = providers.into_iter().map(Rc::from).collect();
+        self
+    }
+
+    /// Makes the dialog query the places providers again every `interval` while it is open, eg. to list
+    /// drives plugged in meanwhile, which are highlighted in the side panel for a few seconds. By default they
+    /// are only queried when the dialog opens and after [`refresh_places()`](FileDialog::refresh_places).
+    #[inline]
+    pub fn places_refresh_interval(mut self, interval: Duration) -> Self {
+        self.config.places_refresh_interval = Some(interval);
+        self
+    }
+
+    /// Queries the places providers again the next time the dialog is drawn. Devices that weren't listed
+    /// before are highlighted in the side panel for a few seconds.
+    #[inline]
+    pub fn refresh_places(&mut self) {
+        if let Some(places) = &mut self.places {
+            places.outdated = true;
+        }
+    }
+
+    /// Queries the places providers if the dialog just opened, if [`refresh_places()`](FileDialog::refresh_places)
+    /// was called, or if the refresh interval elapsed since they were last queried.
+    fn update_places(&mut self, now: Instant) {
+        let outdated = match (&self.places, self.config.places_refresh_interval) {
+            (None, _) => true,
+            (Some(places), _) if places.outdated => true,
+            (Some(places), Some(interval)) => now.saturating_duration_since(places.queried) >= interval,
+            (Some(_), None) => false,
+        };
+        if outdated {
+            let common_places = self.config.common_places.then_some(&self.common_places as &dyn PlacesProvider);
+            let providers = common_places.into_iter().chain(self.places_providers.iter().map(|provider| provider.as_ref()));
+            let sections = collect_places(providers);
+            if let Some((memory, _)) = &self.memory {
+                memory.prune_recent();
+            }
+            /* Only the devices seen during this opening count as known, so nothing is new when it opens */
+            let new_devices = match &self.places {
+                Some(previous) => {
+                    let added = added_devices(&previous.sections, &sections).into_iter().map(|path| (path, now));
+                    let still_new = previous.new_devices.iter()
+                        .filter(|(path, appeared)| {
+                            now.saturating_duration_since(*appeared) < NEW_DEVICE_HIGHLIGHT
+                                && sections.iter().any(|(_, places)| places.iter().any(|place| place.path == *path))
+                        })
+                        .cloned();
+                    added.chain(still_new).collect()
+                }
+                None => Vec::new(),
+            };
+            self.places = Some(CachedPlaces {
+                queried: now,
+                outdated: false,
+                sections,
+                new_devices,
+                recent: self.read_recent(),
+            });
+        }
+    }
+
+    /// Returns the recently used paths of the memory of the dialog, with their kind.
+    fn read_recent(&self) -> Vec<(PathBuf, EntryKind)> {
+        match &self.memory {
+            Some((memory, _)) => memory.recent()
+                .into_iter()
+                .filter_map(|path| EntryKind::at(&path).map(|kind| (path, kind)))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns whether a recently used path of the given kind is listed in the side panel. Directories
+    /// can always be entered, files only if the dialog would accept them from the listing.
+    fn accepts_recent(&self, path: &Path, kind: EntryKind) -> bool {
+        let unselectable = kind == EntryKind::BrokenSymlink
+            && self.config.mode == DialogMode::Open
+            && !self.config.select_broken_symlinks;
+        self.passes_filters(path, kind) && !unselectable
+    }
+
+    /// Chooses a recently used file: enters its directory and selects it there, then accepts it the
+    /// next time the dialog is drawn, like activating it in the listing would.
+    fn choose_recent(&mut self, file: &Path) {
+        let Some(parent) = file.parent() else {
+            return;
+        };
+        self.navigate_to(NavTarget::Place(parent.to_path_buf()));
+        if self.current_dir == parent {
+            self.reveal(file.to_path_buf());
+            self.pending_accept = true;
+        }
+    }
+
+    /// Selects a file of the current directory and gives it the keyboard focus, which scrolls the
+    /// listing to it. Files of other directories are ignored, eg. when navigating to them failed.
+    fn reveal(&mut self, file: PathBuf) {
+        if file.parent() != Some(self.current_dir.as_path()) {
+            return;
+        }
+        match self.config.mode {
+            DialogMode::Open if self.config.multi_select => self.selection = vec![file.clone()],
+            DialogMode::Open => self.highlighted = Some(file.clone()),
+            DialogMode::Save => {
+                self.config.filename = file.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                self.highlighted = Some(file.clone());
+            }
+        }
+        self.pending_focus = Some(file);
+    }
+
+    /// Sets how the listing is sorted when the dialog opens, eg. `sort_by(SortBy::Modified, SortOrder::Descending)`
+    /// to list the newest files first. The user can then sort it differently by clicking the headers
+    /// of the listing. Default is by name, ascending.
+    #[inline]
+    pub fn sort_by(mut self, by: SortBy, order: SortOrder) -> Self {
+        self.config.sort_by = by;
+        self.config.sort_order = order;
+        self
+    }
+
+    /// Sets whether directories are listed before files, whatever the listing is sorted by.
+    /// Default is `true`.
+    #[inline]
+    pub fn dirs_first(mut self, dirs_first: bool) -> Self {
+        self.config.dirs_first = dirs_first;
+        self
+    }
+
+    /// Sets whether numbers in names are compared by value, listing `frame_2.png` before `frame_10.png`.
+    /// Disable it to compare names character by character instead. Default is `true`.
+    #[inline]
+    pub fn natural_sort(mut self, natural: bool) -> Self {
+        self.config.natural_sort = natural;
+        self
+    }
+
+    /// Sets whether the search box matches names with a regular expression instead of looking for the query in them.
+    /// The user can toggle it with the ".*" button next to the search box. Default is `false`.
+    #[cfg(feature = "regex")]
+    #[inline]
+    pub fn regex_search(mut self, regex: bool) -> Self {
+        self.config.regex_search = regex;
+        self
+    }
+
+    /// Sets whether the search box matches names case-sensitively, so that "Makefile" doesn't find "makefile".
+    /// The user can toggle it with the "Aa" button next to the search box. Default is `false`.
+    #[inline]
+    pub fn case_sensitive_search(mut self, case_sensitive: bool) -> Self {
+        self.config.case_sensitive_search = case_sensitive;
+        self
+    }
+
+    /// Compares file names with a closure instead of the built-in rules, eg. to sort them the way
+    /// the operating system does with a collation library.
+    ///
+    /// The closure is used wherever the listing compares names, so directories are still listed
+    /// first unless [`dirs_first(false)`](FileDialog::dirs_first) is set, and names still break ties
+    /// when sorting by size or date. Names it considers equal are ordered by the built-in rules.
+    /// It takes precedence over [`natural_sort()`](FileDialog::natural_sort) and the collation locale.
+    /// ```no_run
+    /// use imfile::FileDialog;
+    ///
+    /// let dialog = FileDialog::new()
+    ///     .sort_with(|a, b| a.len().cmp(&b.len()));
+    /// ```
+    pub fn sort_with<F: Fn(&str, &str) -> std::cmp::Ordering + 'static>(mut self, compare: F) -> Self {
+        self.name_comparator = Some(Rc::new(compare));
+        self
+    }
+
+    /// Sets where dotfiles are placed in the listing when hidden files are shown.
+    /// Default is [`DotfilesPosition::First`].
+    #[inline]
+    pub fn dotfiles_position(mut self, position: DotfilesPosition) -> Self {
+        self.config.dotfiles_position = position;
+        self
+    }
+
+    /// Sorts names according to the alphabet of `locale` (eg. `"sv"`, `"de-DE"`).
+    ///
+    /// By default names are sorted by code point, ignoring case, which puts accented letters after `z`.
+    /// With a locale set, accented letters sort next to their base letter (`ä` next to `a` in German),
+    /// unless the language considers them separate letters: Swedish and Finnish sort `å`, `ä` and `ö`
+    /// after `z`, Danish and Norwegian `æ`, `ø` and `å`. Other languages get the German behaviour.
+    #[cfg(feature = "collation")]
+    #[inline]
+    pub fn collation_locale<S: Into<String>>(mut self, locale: S) -> Self {
+        self.config.collation_locale = Some(locale.into());
+        self
+    }
+
+    /// Stars a file, listing it in the "Starred" section of the side panel.
+    ///
+    /// Users can star and unstar files themselves from the context menu of an entry.
+    /// Use [`FileDialog::starred_files()`] to read the list back and persist it.
+    #[inline]
+    pub fn star<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        let path = path.into();
+        if !self.config.starred.contains(&path) {
+            self.config.starred.push(path);
+        }
+        self
+    }
+
+    /// Returns the files currently starred, in the order they were starred.
+    #[inline]
+    pub fn starred_files(&self) -> &[PathBuf] {
+        &self.config.starred
+    }
+
+    /// Adds a checkbox to the controls row of the dialog, such as "Open as read-only".
+    ///
+    /// `id` identifies the option when reading its value back with [`FileDialog::option()`],
+    /// while `label` is the text displayed next to the checkbox. Options are displayed in the
+    /// order they were added; adding an option with an existing `id` replaces it.
+    pub fn option_checkbox<I: Into<String>, L: Into<String>>(mut self, id: I, label: L, default: bool) -> Self {
+        let option = OptionCheckbox {
+            id: id.into(),
+            label: label.into(),
+            value: default,
+        };
+        match self.config.options.iter_mut().find(|existing| existing.id == option.id) {
+            Some(existing) => *existing = option,
+            None => self.config.options.push(option),
+        }
+        self
+    }
+
+    /// Adds a checkbox to the controls row of the dialog, identified by its label.
+    /// Shorthand for [`option_checkbox(label, label, default)`](FileDialog::option_checkbox).
+    #[inline]
+    pub fn extra_toggle<L: Into<String>>(self, label: L, default: bool) -> Self {
+        let label = label.into();
+        self.option_checkbox(label.clone(), label, default)
+    }
+
+    /// Returns the current value of the option added with `id`, or `None` if there's no such option.
+    #[inline]
+    pub fn option(&self, id: &str) -> Option<bool> {
+        self.config.options.iter().find(|option| option.id == id).map(|option| option.value)
+    }
+
+    /// Returns every option with its current value, in the order they were added.
+    ///
+    /// Options keep their value when the user changes directory, so reading them once the dialog
+    /// returned a [`DialogResult::Selected`] gives the values the user chose the file with:
+    /// ```no_run
+    /// use imfile::{DialogResult, FileDialog};
+    ///
+    /// # fn draw(ui: &imgui::Ui, dialog: &mut FileDialog) {
+    /// if let DialogResult::Selected(path) = dialog.spawn_borrowed(ui) {
+    ///     let values: Vec<bool> = dialog.options().iter().map(|option| option.value).collect();
+    ///     println!("{} {:?}", path.display(), values);
+    /// }
+    /// # }
+    /// ```
+    #[inline]
+    pub fn options(&self) -> &[OptionCheckbox] {
+        &self.config.options
+    }
+
+    /// Pre-fills the filename field of a save dialog, eg. `.for_save().default_filename("untitled.toml")`.
+    ///
+    /// The field has keyboard focus with its text selected when the dialog opens, so typing replaces it.
+    /// The name is kept when the user changes directory. Open dialogs have no filename field and ignore this.
+    #[inline]
+    pub fn default_filename<S: Into<String>>(mut self, filename: S) -> Self {
+        self.config.filename = filename.into();
+        self
+    }
+
+    /// Sets the extension appended to file names typed without one in save dialogs, eg. `.default_extension("png")`.
+    ///
+    /// If the active [named filter](FileDialog::add_filter) has extensions, its first one is appended instead.
+    /// Names that already have an extension are returned as typed. The returned path, extension included,
+    /// is what [`confirm_overwrite()`](FileDialog::confirm_overwrite) checks.
+    pub fn default_extension<S: AsRef<str>>(mut self, extension: S) -> Self {
+        self.config.default_extension = Some(normalize_extension(extension.as_ref()));
+        self
+    }
+
+    /// Returns the path a save dialog returns for the typed file name, with an extension appended if it has none.
+    fn save_path(&self) -> PathBuf {
+        let mut name = self.config.filename.clone();
+        if Path::new(&name).extension().is_none() {
+            let extension = self.config.filters.get(self.config.active_filter)
+                .and_then(|filter| filter.extensions.first())
+                .or(self.config.default_extension.as_ref());
+            if let Some(extension) = extension {
+                name.push('.');
+                name.push_str(extension);
+            }
+        }
+        self.current_dir.join(name)
+    }
+
+    /// Lets the user rename and delete entries of the listing, from their context menu or with F2 and Delete.
+    ///
+    /// Deleted entries are moved to the trash, following the FreeDesktop.org specification. Where there is
+    /// no such trash (Windows, macOS), deleting fails unless [`FileDialog::permanent_delete_fallback()`] is set.
+    /// Deleting always asks for confirmation.
+    /// Plain pickers don't need to modify the filesystem, so this is disabled by default.
+    #[inline]
+    pub fn allow_file_operations(mut self) -> Self {
+        self.config.allow_file_operations = true;
+        self
+    }
+
+    /// Deletes entries permanently when they can't be moved to the trash, because the platform has none
+    /// or it can't be written to. The confirmation then says so, and asks twice for directories that
+    /// aren't empty.
+    #[inline]
+    pub fn permanent_delete_fallback(mut self) -> Self {
+        self.config.permanent_delete_fallback = true;
+        self
+    }
+
+    /// Sets whether the controls row has a "New Folder" button, creating a directory in the current one.
+    /// Default is `true`; disable it for read-only locations.
+    #[inline]
+    pub fn allow_new_folder(mut self, allow: bool) -> Self {
+        self.config.allow_new_folder = allow;
+        self
+    }
+
+    /// Sets whether save dialogs ask before returning a file that already exists. Default is `true`.
+    ///
+    /// When enabled, choosing an existing file opens a popup asking to replace it. The path is only
+    /// returned after the user confirms; declining goes back to the dialog as it was.
+    #[inline]
+    pub fn confirm_overwrite(mut self, confirm: bool) -> Self {
+        self.config.confirm_overwrite = confirm;
+        self
+    }
+
+    /// Checks that the chosen files can be used before returning them: that they can be opened for reading, or
+    /// for writing in save dialogs. Off by default, as it opens every chosen file once more.
+    ///
+    /// A file that fails the check, like one another program holds exclusively, opens a popup naming the
+    /// error. The user can choose it anyway or go back to the dialog as it was.
+    #[inline]
+    pub fn verify_readable(mut self) -> Self {
+        self.config.verify_readable = true;
+        self
+    }
+
+    /// Opens directories and files with a single click instead of a double click, eg. for touch screens.
+    ///
+    /// By default a click highlights an entry and a double click opens it.
+    /// Save dialogs still need a double click to save over a file.
+    #[inline]
+    pub fn single_click_activates(mut self) -> Self {
+        self.config.single_click_activates = true;
+        self
+    }
+
+    /// Lets the user select several entries, returned together as [`DialogResult::SelectedMany`].
+    ///
+    /// Clicking a file toggles it in the selection instead of returning it, and the accept button returns
+    /// everything selected. With [`dir_only()`](FileDialog::dir_only), directories are selected with the
+    /// checkbox next to them, so that clicking them still navigates. The selection is cleared when the
+    /// dialog changes directory.
+    #[inline]
+    pub fn multi_select(mut self) -> Self {
+        self.config.multi_select = true;
+        self
+    }
+
+    /// Sets whether selecting a symbolic link returns the path of its target instead of the path
+    /// of the link. The [`validator()`](FileDialog::validator) is given the target too. Default is `false`.
+    #[inline]
+    pub fn resolve_symlinks(mut self, resolve: bool) -> Self {
+        self.config.resolve_symlinks = resolve;
+        self
+    }
+
+    /// Lets open dialogs select symbolic links whose target doesn't exist. They are listed
+    /// greyed out otherwise. Save dialogs can always select them, saving creates the target.
+    #[inline]
+    pub fn select_broken_symlinks(mut self) -> Self {
+        self.config.select_broken_symlinks = true;
+        self
+    }
+
+    /// Forces the user to make a decision through the buttons of the dialog.
+    ///
+    /// The window can't be collapsed, so the dialog can't be tucked away and forgotten,
+    /// it has no close button and Escape doesn't cancel it.
+    #[inline]
+    pub fn force_decision(mut self) -> Self {
+        self.config.force_decision = true;
+        self
+    }
+
+    /// Gives the window a close button in its title bar, which cancels the dialog like the cancel button.
+    /// The window has none by default, as the application may not expect it to be closed that way.
+    /// Ignored with [`force_decision()`](FileDialog::force_decision).
+    #[inline]
+    pub fn with_close_button(mut self) -> Self {
+        self.config.close_button = true;
+        self
+    }
+
+    /// Checks that the options of the dialog make sense together.
+    ///
+    /// Debug builds run this every time the dialog is spawned and panic on an invalid configuration.
+    /// See [`ConfigError`] for the rules.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        self.config.validate()?;
+        #[cfg(feature = "regex")]
+        if self.config.dirs_only && !self.regex_filters.is_empty() {
+            return Err(ConfigError::DirOnlyWithFilters);
+        }
+        Ok(())
+    }
+
+    /// Returns the most recent navigations performed by the dialog, including failed ones.
+    #[inline]
+    pub fn navigation_log(&self) -> &NavigationLog {
+        &self.navigation_log
+    }
+
+    /// Returns what the dialog did to draw the last frame: how many entries it drew, and whether it had to
+    /// rebuild the listing or could reuse the one of the previous frames. When nothing changes, the directory
+    /// is neither read, sorted nor filtered again, which the host can check with this, eg. in a debug overlay.
+    #[inline]
+    pub fn draw_stats(&self) -> DrawStats {
+        self.draw_stats
+    }
+
+    /// Returns the keyboard shortcuts of the dialog as pairs of keys and what they do, eg.
+    /// `("Ctrl+L", "Type a path")`, for hosts listing them in their own help screens. The dialog lists
+    /// them itself in a popup opened with F1.
+    ///
+    /// Only the shortcuts the configuration of the dialog enables are returned: F2 and Delete need
+    /// [`allow_file_operations()`](FileDialog::allow_file_operations), for instance.
+    pub fn shortcut_descriptions(&self) -> Vec<(String, String)> {
+        Shortcut::ALL.into_iter()
+            .filter(|&shortcut| match shortcut {
+                Shortcut::Rename | Shortcut::Delete => self.config.allow_file_operations,
+                Shortcut::Cancel => self.cancels_on_escape(),
+                Shortcut::EditPath => self.config.show_path_bar,
+                Shortcut::Activate | Shortcut::Find | Shortcut::Help => true,
+            })
+            .map(|shortcut| (shortcut.keys(), String::from(shortcut.description())))
+            .collect()
+    }
+
+    /// Describes the state of the dialog in a sentence or two, for screen readers and other
+    /// accessibility tools.
+    ///
+    /// The summary names the current directory, how many entries were listed during the
+    /// last frame, the entry with keyboard focus and the error of the last navigation if it failed,
+    /// eg. `"Folder /home/me/photos: 12 items, 3 hidden. Focused: beach.png."`. Announce it
+    /// whenever it changes.
+    pub fn accessibility_summary(&self) -> String {
+        let AccessibilityState { counts, focused } = &self.accessibility;
+        let mut summary = format!("Folder {}: {} items", self.current_dir.display(), counts.shown);
+        if counts.hidden > 0 {
+            summary.push_str(&format!(", {} hidden", counts.hidden));
+        }
+        summary.push('.');
+        if let Some(focused) = focused {
+            summary.push_str(&format!(" Focused: {}.", focused));
+        }
+        if let Some(NavEvent { to, error: Some(error), .. }) = self.navigation_log.last() {
+            summary.push_str(&format!(" Can't open {}: {}.", to.display(), error));
+        }
+        summary
+    }
+
+    /// Sets the directory the dialog opens in.
+    ///
+    /// If `path` isn't an existing directory, the dialog opens in the home directory instead,
+    /// or in the working directory of the process as a last resort. A directory remembered with
+    /// [`memory()`](FileDialog::memory) takes precedence, whichever is called first.
+    pub fn start_directory<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config.start_directory = Some(path.into());
+        self.current_dir = self.remembered_directory()
+            .unwrap_or_else(|| resolve_start_directory(self.config.start_directory.as_deref()));
+        self.dirty = true;
+        self
+    }
+
+    /// Makes the dialog remember its directory in `memory`, under the given purpose.
+    ///
+    /// If a dialog with the same purpose was used before, this one opens where it was left, taking
+    /// precedence over [`start_directory()`](FileDialog::start_directory). The memory is read again when
+    /// the dialog is first drawn, so a dialog built before another one with the same purpose is closed
+    /// still opens where that one was left. Every directory the dialog navigates to is then recorded
+    /// for the next one. See [`DialogMemory`].
+    pub fn memory<S: Into<String>>(mut self, memory: &DialogMemory, purpose: S) -> Self {
+        self.memory = Some((memory.clone(), purpose.into()));
+        if let Some(dir) = self.remembered_directory() {
+            self.current_dir = dir;
+            self.dirty = true;
+        }
+        self
+    }
+
+    /// Called when the dialog is drawn. The first time, unless it already navigated, it moves to the directory
+    /// remembered by its memory, which may have changed since the dialog was built.
+    fn start(&mut self) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        if let Some(dir) = self.remembered_directory().filter(|dir| *dir != self.current_dir) {
+            self.current_dir = dir;
+            self.dirty = true;
+        }
+    }
+
+    /// Returns the directory the last dialog with the same purpose was left in, if it still exists.
+    fn remembered_directory(&self) -> Option<PathBuf> {
+        let (memory, purpose) = self.memory.as_ref()?;
+        memory.last_directory(purpose).filter(|dir| dir.is_dir())
+    }
+
+    /// Returns the directory currently displayed by the dialog.
+    #[inline]
+    pub fn current_dir(&self) -> &Path {
+        &self.current_dir
+    }
+
+    /// Returns the entries listed by the dialog, in the order they are displayed: sorted, without the hidden
+    /// files, and only those passing the filters and the search.
+    ///
+    /// Read it after [`spawn_borrowed()`](FileDialog::spawn_borrowed) returned, eg. to draw decorations over
+    /// the rows. It holds until the dialog is drawn again or one of its methods changes the listing.
+    pub fn entries(&self) -> impl ExactSizeIterator<Item = &Entry> + '_ {
+        self.listing.visible.iter().map(|&i| &self.listing.entries[i])
+    }
+
+    /// Returns the positions in [`entries()`](FileDialog::entries) of the entries displayed as selected: every
+    /// selected entry when selecting several, the highlighted one otherwise. Like `entries()`, the positions
+    /// hold until the listing changes.
+    pub fn selection_indices(&self) -> Vec<usize> {
+        self.entries()
+            .enumerate()
+            .filter(|(_, entry)| self.selection.contains(&entry.path) || self.highlighted.as_ref() == Some(&entry.path))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Returns what the user is doing in the dialog: the directory displayed, the selection, the search and its
+    /// history, and the width of the preview pane. Give it back to [`apply_state()`](FileDialog::apply_state),
+    /// eg. after the application restarts. The configuration is in [`config()`](FileDialog::config).
+    pub fn save_state_full(&self) -> DialogState {
+        DialogState {
+            current_dir: self.current_dir.clone(),
+            selection: self.selection.clone(),
+            highlighted: self.highlighted.clone(),
+            search: self.search.clone(),
+            search_history: self.search_history.queries().to_vec(),
+            preview_width: self.preview_width,
+        }
+    }
+
+    /// Puts the dialog back in the state returned by [`save_state_full()`](FileDialog::save_state_full).
+    ///
+    /// The filesystem may have changed since: when the directory can't be listed anymore, the dialog opens in
+    /// its closest parent that can, and stays where it is if there is none. Entries of the selection that are
+    /// gone or aren't in that directory are dropped. The directory replaces the one remembered by the
+    /// [`memory()`](FileDialog::memory) of the dialog, if any, but isn't recorded in it or the
+    /// [`NavigationLog`].
+    pub fn apply_state(&mut self, state: DialogState) {
+        let readable = |dir: &Path| fs::read_dir(dir).is_ok();
+        let mut dir = Some(state.current_dir);
+        while let Some(candidate) = dir.take() {
+            if readable(&candidate) {
+                self.current_dir = candidate;
+                break;
+            }
+            dir = parent_dir(&candidate);
+        }
+        let current_dir = self.current_dir.clone();
+        let listed = move |path: &PathBuf| path.parent() == Some(current_dir.as_path()) && fs::symlink_metadata(path).is_ok();
+        self.selection = state.selection.into_iter().filter(&listed).collect();
+        self.highlighted = state.highlighted.filter(&listed);
+        self.dirty = true;
+        self.started = true;
+        self.pending_focus = None;
+        self.search_history.clear();
+        for query in state.search_history.iter().rev() {
+            self.search_history.push(query);
+        }
+        self.search = state.search;
+        self.search_debounce.cancel();
+        self.update_search();
+        self.preview_width = match state.preview_width {
+            width if width.is_finite() && width >= PREVIEW_WIDTH / 2.0 => width,
+            _ => PREVIEW_WIDTH,
+        };
+    }
+
+    /// Changes the directory displayed by the dialog.
+    ///
+    /// Every part of the dialog that navigates goes through here. The target must be
+    /// a readable directory, otherwise the dialog stays where it is. The attempt is
+    /// recorded in the [`NavigationLog`] either way.
+    fn navigate_to(&mut self, target: NavTarget) {
+        let from = self.current_dir.clone();
+        let to = match &target {
+            NavTarget::Entry(path) | NavTarget::PathBar(path) | NavTarget::Typed(path) | NavTarget::Place(path) => path.clone(),
+            NavTarget::Parent => parent_dir(&from).unwrap_or_else(|| from.clone()),
+        };
+        let result = fs::read_dir(&to).map(|_| ());
+        match &result {
+            Ok(()) => {
+                self.current_dir = to.clone();
+                self.dirty = true;
+                self.started = true;
+                self.selection.clear();
+                self.renaming = None;
+                self.editing_path = None;
+                self.validation_error = None;
+                if let Some(thumbnails) = &mut self.thumbnails {
+                    thumbnails.cache.clear();
+                }
+                self.pending_focus = None;
+                self.search_history.push(&self.search);
+                self.search.clear();
+                self.search_debounce.cancel();
+                self.update_search();
+                self.highlighted = None;
+                if let Some((memory, purpose)) = &self.memory {
+                    memory.set_last_directory(purpose.as_str(), &to);
+                    memory.push_recent(&to);
+                }
+                if self.places.is_some() {
+                    let recent = self.read_recent();
+                    if let Some(places) = &mut self.places {
+                        places.recent = recent;
+                    }
+                }
+            }
+            Err(err) => {
+                log::error!("Can't change directory to {}: {}", to.display(), err.to_string());
+                /* The drive or volume may have been removed since the places were queried */
+                if matches!(target, NavTarget::Place(_)) {
+                    self.refresh_places();
+                }
+            }
+        }
+        self.navigation_log.push(NavEvent {
+            target,
+            from,
+            to,
+            error: result.err().map(|err| err.to_string()),
+        });
+    }
+
+    /// Marks the listing of the current directory as outdated, so that it is read again
+    /// the next time the dialog is spawned. The places of the side panel are queried again too.
+    ///
+    /// The dialog reads a directory once when it enters it, and after changing anything that affects
+    /// the listing. Call this when the directory may have changed behind the dialog's back.
+    #[inline]
+    pub fn invalidate(&mut self) {
+        self.dirty = true;
+        self.refresh_places();
+    }
+
+    /// Reads and sorts the entries of the current directory, then filters them.
+    fn refresh_listing(&mut self) {
+        let read_dir = match fs::read_dir(&self.current_dir) {
+            Ok(read_dir) => read_dir,
+            Err(err) => {
+                log::error!("Can't read directory {}: {}", self.current_dir.display(), err.to_string());
+                self.listing = Listing {
+                    error: Some(format!("Cannot read directory: {}", err.kind())),
+                    ..Listing::default()
+                };
+                self.dirty = false;
+                return;
+            }
+        };
+        /* Entries that can't be read are skipped, like entries that vanished since read_dir() */
+        let mut entries: Vec<_> = read_dir
+            .filter_map(|entry| entry.ok().map(|entry| Entry::new(&entry)))
+            .collect();
+        if let Some(entry_color) = &self.entry_color {
+            for entry in &mut entries {
+                entry.color = entry_color(&entry.path, entry.kind.is_dir());
+            }
+        }
+        self.listing = Listing { entries, ..Listing::default() };
+        self.dirty = false;
+        self.sort_listing();
+    }
+
+    /// Sorts the entries of the listing again, without reading the directory again.
+    fn sort_listing(&mut self) {
+        let sort = SortOptions::new(&self.config, self.name_comparator.as_ref());
+        self.listing.entries.sort_by(|a, b| sort.compare_entries(a, b));
+        self.apply_filters();
+    }
+
+    /// Picks the entries of the listing to display, without reading the directory again.
+    fn apply_filters(&mut self) {
+        self.draw_stats.rebuilds += 1;
+        let matcher = &self.search_matcher;
+        let mut counts = ListingCounts::default();
+        let mut visible = Vec::new();
+        let mut spans = Vec::new();
+        for (i, entry) in self.listing.entries.iter().enumerate() {
+            if !self.config.show_hidden_files && entry.hidden {
+                counts.hidden += 1;
+                continue;
+            }
+            if !self.passes_filters(&entry.path, entry.kind) {
+                counts.filtered += 1;
+                continue;
+            }
+            counts.searched += 1;
+            if let Some(found) = matcher.find(&entry.display_name) {
+                visible.push(i);
+                spans.push(found);
+            }
+        }
+        counts.shown = visible.len();
+        self.listing.visible = visible;
+        self.listing.spans = spans;
+        self.listing.counts = counts;
+    }
+
+    /// Matches names against the query of the search box from now on. An invalid regular expression is reported
+    /// and the previous query stays in use, so that the listing doesn't change while the user is typing it.
+    fn update_search(&mut self) {
+        #[cfg(feature = "regex")]
+        if self.config.regex_search {
+            match SearchMatcher::regex(&self.search, self.config.case_sensitive_search) {
+                Ok(matcher) => {
+                    self.search_matcher = matcher;
+                    self.search_error = None;
+                }
+                Err(error) => self.search_error = Some(error.to_string()),
+            }
+            return;
+        }
+        self.search_matcher = SearchMatcher::new(&self.search, self.config.case_sensitive_search);
+        self.search_error = None;
+    }
+
+    /// Handles a key of the search box, `query` being what it holds this frame.
+    ///
+    /// Escape never closes the dialog from the search box: it clears the query first, and hides the search box
+    /// once it is empty, giving the keyboard focus back to the listing.
+    fn search_key(&mut self, key: SearchKey, query: &mut String) {
+        match key {
+            SearchKey::Find => {
+                self.search_box.hidden = false;
+                self.search_box.focus = true;
+            }
+            SearchKey::Escape if !query.is_empty() => {
+                query.clear();
+                /* Escape took the focus away from the search box */
+                self.search_box.focus = true;
+            }
+            SearchKey::Escape => {
+                self.search_box.hidden = true;
+                self.search_box.focused = false;
+                let entries = &self.listing.entries;
+                self.pending_focus = self.highlighted.clone()
+                    .or_else(|| self.listing.visible.first().map(|&i| entries[i].path.clone()));
+            }
+        }
+    }
+
+    /// Returns whether an entry of the given kind passes the filters of the dialog. Directories always do.
+    fn passes_filters(&self, path: &Path, kind: EntryKind) -> bool {
+        let active_filter = self.config.filters.get(self.config.active_filter);
+        kind.is_dir()
+            || ((kind.is_file() || kind == EntryKind::BrokenSymlink)
+                && !self.config.dirs_only
+                && self.matches_name_filters(path)
+                && active_filter.map_or(true, |filter| filter.matches(path)))
+    }
+
+    /// Follows an entry renamed from `from` to `to`: it stays selected, and so does its name in the filename field.
+    fn entry_renamed(&mut self, from: &Path, to: &Path) {
+        for selected in self.selection.iter_mut().chain(&mut self.highlighted).filter(|selected| **selected == from) {
+            *selected = to.to_path_buf();
+        }
+        if from.file_name().is_some_and(|name| *name.to_string_lossy() == self.config.filename) {
+            self.config.filename = to.file_name().unwrap_or_default().to_string_lossy().into_owned();
+        }
+        self.dirty = true;
+    }
+
+    /// Offers to undo `undo` for [`UNDO_TIMEOUT`], replacing what could be undone before.
+    fn offer_undo(&mut self, undo: Undo) {
+        self.undo = Some(UndoToast { undo, remaining: UNDO_TIMEOUT, error: None });
+    }
+
+    /// Counts down the time left to undo the last operation, `elapsed` being the time since the last frame.
+    fn tick_undo(&mut self, elapsed: Duration) {
+        if let Some(toast) = &mut self.undo {
+            toast.remaining = toast.remaining.saturating_sub(elapsed);
+            if toast.remaining.is_zero() {
+                self.undo = None;
+            }
+        }
+    }
+
+    /// Undoes the last operation, refreshing the listing. The toast stays with the reason if it fails.
+    fn undo_last(&mut self) {
+        let Some(toast) = &mut self.undo else { return };
+        match toast.undo.undo() {
+            Ok(path) => {
+                if let Some(UndoToast { undo: Undo::Rename { from, to }, .. }) = self.undo.take() {
+                    self.entry_renamed(&to, &from);
+                }
+                self.dirty = true;
+                self.pending_focus = Some(path.clone());
+                self.highlighted = Some(path);
+            }
+            Err(error) => toast.error = Some(error),
+        }
+    }
+
+    /// Forgets about an entry deleted from the current directory, moving the keyboard focus to the entry after it.
+    fn entry_deleted(&mut self, path: &Path) {
+        let entries = &self.listing.entries;
+        if let Some(position) = self.listing.visible.iter().position(|&i| entries[i].path == path) {
+            let next = self.listing.visible.get(position + 1)
+                .or_else(|| position.checked_sub(1).and_then(|previous| self.listing.visible.get(previous)));
+            self.pending_focus = next.map(|&i| entries[i].path.clone());
+        }
+        if self.highlighted.as_deref() == Some(path) {
+            self.highlighted = self.pending_focus.clone();
+        }
+        self.selection.retain(|selected| selected != path);
+        self.pending_delete = None;
+        self.dirty = true;
+    }
+
+    /// Returns what the accept button does in the current state of the dialog, or `None` if it is disabled.
+    fn accept_action(&self) -> Option<AcceptAction> {
+        let selects_folders = self.config.dirs_only || self.config.accept_dirs_too;
+        match self.config.mode {
+            DialogMode::Save if self.config.filename.is_empty() => None,
+            DialogMode::Save => Some(AcceptAction::Return(self.save_path())),
+            DialogMode::Open if self.config.multi_select => {
+                if !self.selection.is_empty() {
+                    Some(AcceptAction::ReturnMany(self.selection.clone()))
+                } else if selects_folders {
+                    Some(AcceptAction::Return(self.current_dir.clone()))
+                } else {
+                    None
+                }
+            }
+            DialogMode::Open => match &self.highlighted {
+                /* Like in native dialogs, opening a directory enters it unless directories can be chosen */
+                Some(highlighted) if !selects_folders && highlighted.is_dir() => Some(AcceptAction::Enter(highlighted.clone())),
+                Some(highlighted) => Some(AcceptAction::Return(highlighted.clone())),
+                None if selects_folders => Some(AcceptAction::Return(self.current_dir.clone())),
+                None => None,
+            },
+        }
+    }
+
+    /// Returns whether accepting `action` returns a folder of an open dialog, which changes the label of the button.
+    fn selects_folder(&self, action: Option<&AcceptAction>) -> bool {
+        matches!(action, Some(AcceptAction::Return(path)) if self.config.mode == DialogMode::Open && path.is_dir())
+    }
+
+    /// Returns the state of the accept button, when accepting does `action`.
+    /// The label counts the selected entries of multi-select dialogs, like "Open (3)".
+    fn accept_state(&self, action: Option<&AcceptAction>) -> AcceptState {
+        AcceptState {
+            label: match action {
+                _ if self.selects_folder(action) => self.config.select_folder_text.clone(),
+                Some(AcceptAction::ReturnMany(paths)) => format!("{} ({})", self.config.accept_text, paths.len()),
+                _ => self.config.accept_text.clone(),
+            },
+            enabled: action.is_some(),
+            tooltip: match (action, self.config.mode) {
+                (Some(_), _) => None,
+                (None, DialogMode::Open) => Some(String::from("Select a file first")),
+                (None, DialogMode::Save) => Some(String::from("Type a file name first")),
+            },
+        }
+    }
+
+    /// Runs the stages of accepting `chosen` from `ACCEPT_STAGES[stage]` on, returning what the dialog returns
+    /// once all of them passed. A stage asking the user stops the choice in
+    /// [`pending_choice`](FileDialog::pending_choice) until answered, and a refusing stage drops it.
+    ///
+    /// The stages run one after the other on the frame the user accepted, and the validator isn't `Send`,
+    /// so they can't move to a thread; the questions are where the user gets to stop them.
+    fn run_accept(&mut self, mut chosen: DialogResult, stage: usize) -> Option<DialogResult> {
+        for (index, &stage) in ACCEPT_STAGES.iter().enumerate().skip(stage) {
+            match self.accept_stage(stage, &mut chosen) {
+                StageOutcome::Continue => {}
+                StageOutcome::Refuse => return None,
+                StageOutcome::Ask(question) => {
+                    self.pending_choice = Some(PendingAccept { chosen, next_stage: index + 1, question });
+                    return None;
+                }
+            }
+        }
+        Some(chosen)
+    }
+
+    /// Runs one stage of accepting `chosen`, which it may change.
+    fn accept_stage(&mut self, stage: AcceptStage, chosen: &mut DialogResult) -> StageOutcome {
+        match stage {
+            AcceptStage::Resolve if self.config.resolve_symlinks => {
+                match chosen {
+                    DialogResult::Selected(path) => *path = resolve_symlink(std::mem::take(path)),
+                    DialogResult::SelectedMany(paths) => {
+                        *paths = std::mem::take(paths).into_iter().map(resolve_symlink).collect();
+                    }
+                    _ => {}
+                }
+                StageOutcome::Continue
+            }
+            AcceptStage::Validate => {
+                let Some(validator) = &self.validator else {
+                    return StageOutcome::Continue;
+                };
+                match chosen.clone().paths().iter().map(|path| validator(path)).find_map(Result::err) {
+                    Some(error) => {
+                        self.validation_error = Some(error);
+                        StageOutcome::Refuse
+                    }
+                    None => {
+                        self.validation_error = None;
+                        StageOutcome::Continue
+                    }
+                }
+            }
+            AcceptStage::ConfirmOverwrite if self.config.mode == DialogMode::Save && self.config.confirm_overwrite => {
+                match chosen {
+                    DialogResult::Selected(path) if path.exists() => StageOutcome::Ask(AcceptQuestion::Overwrite(path.clone())),
+                    _ => StageOutcome::Continue,
+                }
+            }
+            AcceptStage::CheckAccess if self.config.verify_readable => {
+                match chosen.clone().paths().iter().find_map(|path| check_access(path, self.config.mode).err()) {
+                    Some(error) => StageOutcome::Ask(AcceptQuestion::Access(error)),
+                    None => StageOutcome::Continue,
+                }
+            }
+            _ => StageOutcome::Continue,
+        }
+    }
+
+    /// Answers the question stopping the pending choice: yes resumes it with the next stage, no drops it.
+    fn answer_accept(&mut self, yes: bool) -> Option<DialogResult> {
+        let pending = self.pending_choice.take()?;
+        if yes { self.run_accept(pending.chosen, pending.next_stage) } else { None }
+    }
+
+    /// Records that the dialog is drawn in `frame`, returning whether it wasn't already.
+    fn first_draw_in(&mut self, frame: i32) -> bool {
+        self.last_frame.replace(frame) != Some(frame)
+    }
+
+    /// Returns whether pressing Escape cancels the dialog, which only its buttons can do with
+    /// [`force_decision()`](FileDialog::force_decision).
+    #[inline]
+    fn cancels_on_escape(&self) -> bool {
+        !self.config.force_decision
+    }
+
+    /// Returns whether the window has a close button, see [`with_close_button()`](FileDialog::with_close_button).
+    #[inline]
+    fn has_close_button(&self) -> bool {
+        self.config.close_button && !self.config.force_decision
+    }
+
+    /// Records what happened to the window during the frame. Returns whether it was closed, which cancels
+    /// the dialog.
+    fn window_event(&mut self, event: WindowEvent) -> bool {
+        self.collapsed = event == WindowEvent::Collapsed;
+        event == WindowEvent::Closed
+    }
+
+    /// Activates an entry of the listing, eg. by double clicking it or pressing Enter on it, and returns
+    /// what the dialog does.
+    ///
+    /// The listing is only read when entering a directory, so the entry may have been replaced since by
+    /// an entry of another kind, like a file by a directory. Its kind is read again first, and its row
+    /// updated if it changed, so that directories are always entered and files always chosen.
+    fn activate(&mut self, path: &Path) -> Option<AcceptAction> {
+        let Some(kind) = EntryKind::at(path) else {
+            /* Removed since the listing was read */
+            self.dirty = true;
+            return None;
+        };
+        if let Some(entry) = self.listing.entries.iter_mut().find(|entry| entry.path == path && entry.kind != kind) {
+            entry.kind = kind;
+            self.sort_listing();
+        }
+        if kind.is_dir() {
+            return Some(AcceptAction::Enter(path.to_path_buf()));
+        }
+        match self.config.mode {
+            DialogMode::Open if kind == EntryKind::BrokenSymlink && !self.config.select_broken_symlinks => None,
+            DialogMode::Open if self.config.multi_select => self.accept_action(),
+            DialogMode::Open => Some(AcceptAction::Return(path.to_path_buf())),
+            DialogMode::Save => {
+                self.config.filename = path.file_name().unwrap_or_default().to_string_lossy().into_owned();
+                self.accept_action()
+            }
+        }
+    }
+
+    /// Drops the selected entries that were removed, or replaced by an entry of a kind the dialog
+    /// doesn't select, since they were selected.
+    fn recheck_selection(&mut self) {
+        let selected = self.selection.len();
+        let dirs_only = self.config.dirs_only;
+        self.selection.retain(|path| EntryKind::at(path).is_some_and(|kind| kind.is_dir() == dirs_only));
+        if self.selection.len() != selected {
+            self.dirty = true;
+        }
+    }
+
+    /// Returns the flags the dialog window is created with.
+    fn window_flags(&self) -> WindowFlags {
+        let mut flags = WindowFlags::empty();
+        if self.config.force_decision {
+            flags |= WindowFlags::NO_COLLAPSE;
+        }
+        flags
+    }
+
+    /// Sets whether the path bar is displayed at the top of the dialog. Default is `true`.
+    #[inline]
+    pub fn show_path_bar(mut self, show: bool) -> Self {
+        self.config.show_path_bar = show;
+        self
+    }
+
+    /// Sets whether the side panel is displayed. Default is `true`.
+    ///
+    /// Even when enabled, the side panel is only displayed if there's something to list in it.
+    #[inline]
+    pub fn show_sidebar(mut self, show: bool) -> Self {
+        self.config.show_sidebar = show;
+        self
+    }
+
+    /// Draws glyphs in front of the entries of the listing, instead of the `[dir]` and `[file]` prefixes.
+    /// The application has to load the font of the glyphs, see [`IconSet`](crate::IconSet).
+    #[cfg(feature = "icons")]
+    #[inline]
+    pub fn icons(mut self, icons: crate::IconSet) -> Self {
+        self.config.icons = Some(icons);
+        self
+    }
+
+    /// Sets how the listing lays entries out when the dialog opens. The user can switch views
+    /// with the button of the controls row, and the dialog keeps the view when changing directory.
+    /// Default is [`ViewMode::List`].
+    #[inline]
+    pub fn view_mode(mut self, mode: ViewMode) -> Self {
+        self.config.view_mode = mode;
+        self
+    }
+
+    /// Sets whether the listing displays when every entry was last modified. Default is `true`.
+    #[inline]
+    pub fn show_modified_column(mut self, show: bool) -> Self {
+        self.config.show_modified_column = show;
+        self
+    }
+
+    /// Sets whether the controls row (buttons, checkboxes) is displayed at the bottom of the dialog.
+    /// Default is `true`.
+    #[inline]
+    pub fn show_controls(mut self, show: bool) -> Self {
+        self.config.show_controls = show;
+        self
+    }
+
+    /// Sets whether the status bar, counting the entries listed and hidden, is displayed at the bottom
+    /// of the dialog. Default is `true`.
+    #[inline]
+    pub fn show_status_bar(mut self, show: bool) -> Self {
+        self.config.show_status_bar = show;
+        self
+    }
+
+    /// Displays the beginning of the selected file in a pane on the right of the listing, which the user can
+    /// resize with the splitter on its left. Only the first 16 KiB of the file are read, and binary files
+    /// are not displayed.
+    #[inline]
+    pub fn with_preview(mut self) -> Self {
+        self.config.show_preview = true;
+        self
+    }
+
+    /// Displays the details of the selected entry (full path, size, times, permissions on Unix, link target)
+    /// in a pane on the right of the listing, shared with [`with_preview()`](FileDialog::with_preview).
+    /// They are read once, when the entry gets selected.
+    #[inline]
+    pub fn with_details(mut self) -> Self {
+        self.config.show_details = true;
+        self
+    }
+
+    /// Draws thumbnails above the files of the grid view, `size` pixels large.
+    ///
+    /// The closure receives the path of a file and returns the texture of its thumbnail, or `None`
+    /// if the file has none, in which case only its name is drawn. Decoding images and uploading
+    /// textures is up to the application. The closure is called the first time a file is drawn,
+    /// and its result is kept until the dialog changes directory, so the application may free the
+    /// textures of a directory once the dialog left it.
+    /// ```no_run
+    /// use imfile::{FileDialog, ViewMode};
+    ///
+    /// # fn load_thumbnail(path: &std::path::Path) -> Option<imgui::TextureId> { None }
+    /// let dialog = FileDialog::new()
+    ///     .view_mode(ViewMode::Grid)
+    ///     .thumbnail_provider([96.0, 96.0], |path| load_thumbnail(path));
+    /// ```
+    pub fn thumbnail_provider<F: Fn(&Path) -> Option<imgui::TextureId> + 'static>(mut self, size: [f32; 2], provider: F) -> Self {
+        self.thumbnails = Some(Thumbnails { provider: Rc::new(provider), size, cache: HashMap::new() });
+        self
+    }
+
+    /// Draws the accept button with a closure instead of the built-in button.
+    ///
+    /// The closure receives the [`Ui`](imgui::Ui) and the current [`AcceptState`], and must return `true`
+    /// on the frame the button was activated, exactly like [`Ui::button()`](imgui::Ui::button) does.
+    /// The dialog treats that return value the same way as a click on the built-in button.
+    /// ```no_run
+    /// use imfile::FileDialog;
+    ///
+    /// let dialog = FileDialog::new()
+    ///     .accept_button_fn(|ui, state| {
+    ///         let _color = ui.push_style_color(imgui::StyleColor::Button, [0.2, 0.5, 0.9, 1.0]);
+    ///         ui.button(&state.label)
+    ///     });
+    /// ```
+    pub fn accept_button_fn<F: FnMut(&imgui::Ui, &AcceptState) -> bool + 'static>(mut self, accept_button_fn: F) -> Self {
+        self.accept_button_fn = Some(Rc::new(RefCell::new(accept_button_fn)));
+        self
+    }
+
+    /// Draws the cancel button with a closure instead of the built-in button, like
+    /// [`accept_button_fn()`](FileDialog::accept_button_fn) does for the accept button.
+    ///
+    /// The closure receives the [`Ui`](imgui::Ui) and the text the button should display, and must return `true`
+    /// on the frame the button was activated. The dialog is then cancelled.
+    /// ```no_run
+    /// use imfile::FileDialog;
+    ///
+    /// let dialog = FileDialog::new()
+    ///     .cancel_button_fn(|ui, label| {
+    ///         let _color = ui.push_style_color(imgui::StyleColor::Button, [0.6, 0.2, 0.2, 1.0]);
+    ///         ui.button(label)
+    ///     });
+    /// ```
+    pub fn cancel_button_fn<F: FnMut(&imgui::Ui, &str) -> bool + 'static>(mut self, cancel_button_fn: F) -> Self {
+        self.cancel_button_fn = Some(Rc::new(RefCell::new(cancel_button_fn)));
+        self
+    }
+
+    /// Loads the entry to display in the preview pane, if the selection changed.
+    fn update_preview(&mut self) {
+        let previewed = self.highlighted.as_ref().or(self.selection.last());
+        if previewed != self.preview.as_ref().map(|preview| &preview.path) {
+            let (details, text) = (self.config.show_details, self.config.show_preview);
+            self.preview = previewed.map(|path| Preview::load(path.clone(), details, text));
+        }
+    }
+
+    /// Picks the text color of the entries of the listing, eg. to tint images or source files.
+    ///
+    /// The closure receives the path of an entry and whether it is a directory. It runs once per
+    /// entry when the directory is read, not every frame. Entries it returns `None` for get the
+    /// default colors: the text color of the imgui style, dimmed for hidden entries and tinted blue
+    /// for directories. Pick colors that are readable with the style of the application.
+    /// ```no_run
+    /// use imfile::FileDialog;
+    ///
+    /// let dialog = FileDialog::new()
+    ///     .entry_color(|path, is_dir| match path.extension().and_then(|extension| extension.to_str()) {
+    ///         Some("rs") if !is_dir => Some([0.9, 0.6, 0.3, 1.0]),
+    ///         _ => None,
+    ///     });
+    /// ```
+    pub fn entry_color<F: Fn(&Path, bool) -> Option<[f32; 4]> + 'static>(mut self, entry_color: F) -> Self {
+        self.entry_color = Some(Rc::new(entry_color));
+        self.dirty = true;
+        self
+    }
+
+    /// Checks the paths the user chooses before the dialog returns them, eg. to refuse reserved names
+    /// or files too large to open.
+    ///
+    /// The closure runs whenever the user accepts, whether with the accept button, the Enter key
+    /// or a double click. When it returns an error, the dialog stays open and displays the message
+    /// next to the accept button. With [`multi_select()`](FileDialog::multi_select), every selected
+    /// path must pass. Save dialogs check the path before asking whether to replace an existing file.
+    /// ```no_run
+    /// use imfile::FileDialog;
+    ///
+    /// let dialog = FileDialog::new()
+    ///     .for_save()
+    ///     .validator(|path| match path.file_stem().and_then(|stem| stem.to_str()) {
+    ///         Some("project") => Err(String::from("\"project\" is reserved")),
+    ///         _ => Ok(()),
+    ///     });
+    /// ```
+    pub fn validator<F: Fn(&Path) -> Result<(), String> + 'static>(mut self, validator: F) -> Self {
+        self.validator = Some(Rc::new(validator));
+        self
+    }
+
+    /// Draws widgets of the application, such as an encoding selector, on their own line above the
+    /// buttons of the controls row.
+    ///
+    /// The closure is called every frame the controls row is drawn. It has the whole width of the
+    /// dialog, as returned by [`Ui::content_region_avail()`](imgui::Ui::content_region_avail), but room
+    /// for a single line of widgets only. The controls row must be shown for the closure to be called.
+    /// ```no_run
+    /// use imfile::FileDialog;
+    ///
+    /// let mut encoding = 0;
+    /// let dialog = FileDialog::new()
+    ///     .footer(move |ui| {
+    ///         ui.combo_simple_string("Encoding", &mut encoding, &["UTF-8", "Latin-1"]);
+    ///     });
+    /// ```
+    pub fn footer<F: FnMut(&imgui::Ui) + 'static>(mut self, footer: F) -> Self {
+        self.footer = Some(Rc::new(RefCell::new(footer)));
+        self
+    }
+
+    /// Returns the height passed to the child windows between the path bar and the controls row.
+    ///
+    /// Negative heights are relative to the bottom of the window in ImGui, and 0 fills the
+    /// remaining space, so the listing takes over the controls row and the status bar when they are hidden.
+    #[inline]
+    fn content_height(&self) -> f32 {
+        -(self.controls_height() + self.status_bar_height())
+    }
+
+    /// Returns the height reserved for the controls row, 0 when it is hidden.
+    fn controls_height(&self) -> f32 {
+        match (self.config.show_controls, &self.footer) {
+            (true, Some(_)) => CONTROLS_HEIGHT + FOOTER_HEIGHT,
+            (true, None) => CONTROLS_HEIGHT,
+            (false, _) => 0.0,
+        }
+    }
+
+    /// Returns the height reserved for the status bar, 0 when it is hidden.
+    fn status_bar_height(&self) -> f32 {
+        if self.config.show_status_bar { STATUS_BAR_HEIGHT } else { 0.0 }
+    }
+
+    /// Spawns the dialog.
+    ///
+    /// This function spawns the dialog and returns what the user did with it during this frame,
+    /// see [`DialogResult`]. Use [`DialogResult::path()`] if you only care about the chosen file.\
+    /// This is the **owned** version of the `spawn*` family of functions. After calling this function, you won't
+    /// be able to reuse [`self`]. If you wish to continue owning [`self`], then see [`FileDialog::spawn_borrowed()`].
+    ///
+    /// **WARNING**: This dialog expects you to have a [`Ui`](imgui::Ui) ready that the function will immutably borrow.
+    /// See the documentation of [imgui] for details.
+    #[inline]
+    pub fn spawn(mut self, ui: &imgui::Ui) -> DialogResult {
+        self.spawn_borrowed(ui)
+    }
+
+    /// Spawns the dialog, without giving up ownership of it.
+    ///
+    /// This is the **borrowed** version of the `spawn*` family of functions. The dialog keeps its state
+    /// (current directory, hidden files toggle, file name, ...) between calls, so keep the [`FileDialog`]
+    /// around (eg. in your GUI struct) and call this every frame for as long as the dialog should be displayed.
+    /// Returns [`DialogResult::StillOpen`] until the user chooses an entry or cancels the dialog.
+    ///
+    /// The dialog is drawn at most once per frame. Calling this again in the same frame, eg. from two panels,
+    /// draws nothing and returns [`DialogResult::StillOpen`], so that a click can't be handled twice.
+    ///
+    /// **WARNING**: This dialog expects you to have a [`Ui`](imgui::Ui) ready that the function will immutably borrow.
+    /// See the documentation of [imgui] for details.
+    pub fn spawn_borrowed(&mut self, ui: &imgui::Ui) -> DialogResult {
+        #[cfg(debug_assertions)]
+        if let Err(err) = self.validate() {
+            panic!("Invalid file dialog configuration: {}", err);
+        }
+        if !self.first_draw_in(ui.frame_count()) {
+            log::warn!("File dialog \"{}\" drawn twice in the same frame, only the first one is displayed", self.config.title);
+            return DialogResult::StillOpen;
+        }
+        self.draw_stats = DrawStats::default();
+        self.start();
         let mut path = None;
-        ui.window(self.title.clone())
-            .size([600.0, 400.0], Condition::FirstUseEver)
-            .build(|| {
+        /* Nothing is read from the filesystem for a collapsed window: what was read is drawn the frame it expands */
+        let collapsed = self.collapsed;
+        if self.config.show_sidebar && !collapsed {
+            self.update_places(Instant::now());
+        }
+        let places = match &self.places {
+            Some(places) if self.config.show_sidebar => places.sections.clone(),
+            _ => Vec::new(),
+        };
+        let new_devices: Vec<_> = match &self.places {
+            Some(places) if self.config.show_sidebar => places.new_devices.iter()
+                .filter(|(_, appeared)| appeared.elapsed() < NEW_DEVICE_HIGHLIGHT)
+                .map(|(path, _)| path.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        if self.dirty && !collapsed {
+            self.refresh_listing();
+        }
+        if (self.config.show_preview || self.config.show_details) && !collapsed {
+            self.update_preview();
+        }
+        let bookmarks = match &self.memory {
+            Some((memory, _)) if self.config.show_sidebar => memory.bookmarks(),
+            _ => Vec::new(),
+        };
+        let recent: Vec<_> = match &self.places {
+            Some(places) if self.config.show_sidebar => places.recent.iter()
+                .filter(|(path, kind)| self.accepts_recent(path, *kind))
+                .cloned()
+                .collect(),
+            _ => Vec::new(),
+        };
+        let content_height = self.content_height();
+        let counts = self.listing.counts;
+        let mut show_hidden_files = self.config.show_hidden_files;
+        /* Set when the case sensitivity or regex toggles of the search box are clicked */
+        let mut search_options_changed = false;
+        let mut search_now = false;
+        let mut search = self.search.clone();
+        let filter_labels: Vec<_> = self.config.filters.iter().map(FileFilter::label).collect();
+        let mut navigation = None;
+        let mut sort = None;
+        /* Selected once the navigation to its directory succeeded */
+        let mut revealed_file: Option<PathBuf> = None;
+        let mut close_path_input = false;
+        let mut focused = None;
+        let mut selected_many = None;
+        let mut open_delete_popup = false;
+        let mut open_properties_popup = false;
+        let mut accept_requested = std::mem::take(&mut self.pending_accept);
+        let mut recent_file = None;
+        let mut deleted = None;
+        let mut cancelled = false;
+        let mut opened = true;
+        let mut window = ui.window(self.config.title.clone())
+            .size(self.config.window_size, Condition::FirstUseEver)
+            .flags(self.window_flags());
+        if self.has_close_button() {
+            window = window.opened(&mut opened);
+        }
+        let drawn = window.build(|| {
+            /* Checked before drawing anything, so that a field being typed in last frame gets the key first */
+            if ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS)
+                && !ui.is_any_item_active()
+                && self.renaming.is_none()
+                && self.editing_path.is_none()
+                && !self.search_box.focused
+                && Shortcut::Cancel.pressed(ui)
+            {
+                if self.new_folder.is_some() {
+                    self.new_folder = None;
+                } else if self.cancels_on_escape() {
+                    cancelled = true;
+                }
+            }
+            if ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS) && Shortcut::Find.pressed(ui) {
+                self.search_key(SearchKey::Find, &mut search);
+            }
+            if self.config.show_path_bar
+                && ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS)
+                && Shortcut::EditPath.pressed(ui)
+            {
+                self.editing_path = Some(PathInput::new(&self.current_dir));
+            }
+            if ui.is_window_focused_with_flags(WindowFocusedFlags::ROOT_AND_CHILD_WINDOWS)
+                && !ui.is_any_item_active()
+                && Shortcut::Help.pressed(ui)
+            {
+                ui.open_popup(HELP_POPUP);
+            }
+            if self.config.show_path_bar {
                 ui.child_window("Path Selection")
                     .horizontal_scrollbar(false)
                     .border(true)
-                    .size([0.0, 32.0])
+                    .size([0.0, PATH_BAR_HEIGHT])
+                    .build(||{
+                        if ui.button("Path: ") && self.editing_path.is_none() {
+                            self.editing_path = Some(PathInput::new(&self.current_dir));
+                        }
+                        ui.same_line();
+                        if let Some(editing) = self.editing_path.as_mut() {
+                            if editing.focus {
+                                ui.set_keyboard_focus_here();
+                                editing.focus = false;
+                            }
+                            let style = ui.clone_style();
+                            let error_width = editing.input.error.as_ref()
+                                .map_or(0.0, |error| ui.calc_text_size(error)[0] + style.item_spacing[0]);
+                            ui.set_next_item_width(ui.content_region_avail()[0] - error_width);
+                            let submitted = ui.input_text("##Typed path", &mut editing.input.name)
+                                .enter_returns_true(true)
+                                .auto_select_all(true)
+                                .build();
+                            if submitted {
+                                match resolve_typed_path(&self.current_dir, &editing.input.name) {
+                                    Ok((dir, file)) => {
+                                        navigation = Some(NavTarget::Typed(dir));
+                                        revealed_file = file;
+                                    }
+                                    Err(error) => editing.input.error = Some(error),
+                                }
+                            } else if ui.is_key_pressed(Key::Escape) {
+                                close_path_input = true;
+                            }
+                            if let Some(error) = &editing.input.error {
+                                ui.same_line();
+                                ui.text_colored(ERROR_COLOR, error);
+                            }
+                            return;
+                        }
+                        let mut crumb = PathBuf::new();
+                        let crumbs: Vec<(String, PathBuf)> = self.current_dir.iter().map(|dir| {
+                            crumb.push(dir);
+                            (dir.to_string_lossy().into_owned(), crumb.clone())
+                        }).collect();
+                        let bookmarkable = self.memory.as_ref()
+                            .is_some_and(|(memory, _)| !memory.bookmarks().contains(&self.current_dir));
+
+                        /* Collapse the middle segments when the path doesn't fit, the deepest ones are clicked the most. */
+                        let style = ui.clone_style();
+                        let button_width = |label: &str| ui.calc_text_size(label)[0] + style.frame_padding[0] * 2.0 + style.item_spacing[0];
+                        let widths: Vec<f32> = crumbs.iter().map(|(name, _)| button_width(name)).collect();
+                        let mut available = ui.content_region_avail()[0];
+                        if bookmarkable {
+                            available -= button_width("+");
+                        }
+                        let hidden = hidden_crumbs(&widths, available, button_width(ELLIPSIS));
+
+                        for (i, (name, path)) in crumbs.iter().enumerate() {
+                            if hidden.contains(&i) {
+                                if i == hidden.start {
+                                    if ui.button(ELLIPSIS) {
+                                        ui.open_popup(CRUMBS_POPUP);
+                                    }
+                                    if let Some(_popup) = ui.begin_popup(CRUMBS_POPUP) {
+                                        for (name, path) in &crumbs[hidden.clone()] {
+                                            if ui.selectable(name) {
+                                                navigation = Some(NavTarget::PathBar(path.clone()));
+                                            }
+                                        }
+                                    }
+                                    ui.same_line();
+                                }
+                                continue;
+                            }
+                            if ui.button(format!("{}##crumb{}", name, i)) {
+                                navigation = Some(NavTarget::PathBar(path.clone()));
+                            }
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text(format!("Directory: {}", path.display()));
+                            }
+                            ui.same_line();
+                        }
+                        if let (true, Some((memory, _))) = (bookmarkable, &self.memory) {
+                            if ui.small_button("+##Bookmark") {
+                                memory.add_bookmark(&self.current_dir);
+                            }
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Bookmark this folder");
+                            }
+                        }
+                    });
+            }
+            let sidebar_empty = places.is_empty() && bookmarks.is_empty() && recent.is_empty() && self.config.starred.is_empty();
+            if self.config.show_sidebar && !sidebar_empty {
+                ui.child_window("Places")
+                    .border(true)
+                    .size([SIDEBAR_WIDTH, content_height])
+                    .build(|| {
+                        for (section, places) in &places {
+                            ui.text_disabled(section.title());
+                            for place in places {
+                                let new = new_devices.contains(&place.path);
+                                let clicked = {
+                                    let _color = new.then(|| ui.push_style_color(StyleColor::Text, NEW_DEVICE_COLOR));
+                                    ui.selectable(&place.label)
+                                };
+                                if clicked {
+                                    navigation = Some(NavTarget::Place(place.path.clone()));
+                                }
+                                if ui.is_item_hovered() {
+                                    let kind = if new { "New device" } else { "Directory" };
+                                    ui.tooltip_text(format!("{}: {}", kind, place.path.display()));
+                                }
+                            }
+                        }
+                        if !bookmarks.is_empty() {
+                            ui.text_disabled(PlaceSection::Bookmarks.title());
+                        }
+                        for (i, bookmark) in bookmarks.iter().enumerate() {
+                            let name = bookmark.file_name().unwrap_or(bookmark.as_os_str()).to_string_lossy();
+                            let mut removed = false;
+                            if bookmark.is_dir() {
+                                if ui.selectable(format!("{}##bookmark{}", name, i)) {
+                                    navigation = Some(NavTarget::Place(bookmark.clone()));
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text(format!("Directory: {}", bookmark.display()));
+                                }
+                                if let Some(_menu) = ui.begin_popup_context_item() {
+                                    removed = ui.selectable("Remove bookmark");
+                                }
+                            } else {
+                                ui.text_disabled(&name);
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text(format!("Missing: {}", bookmark.display()));
+                                }
+                                ui.same_line();
+                                removed = ui.small_button(format!("x##Remove bookmark {}", bookmark.display()));
+                            }
+                            if let (true, Some((memory, _))) = (removed, &self.memory) {
+                                memory.remove_bookmark(bookmark);
+                            }
+                        }
+                        if !recent.is_empty() {
+                            ui.text_disabled(PlaceSection::Recent.title());
+                        }
+                        for (i, (recent, kind)) in recent.iter().enumerate() {
+                            let name = recent.file_name().unwrap_or(recent.as_os_str()).to_string_lossy();
+                            let clicked = ui.selectable(format!("{}##recent{}", name, i));
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text(recent.display().to_string());
+                            }
+                            if !clicked {
+                                continue;
+                            }
+                            if kind.is_dir() {
+                                navigation = Some(NavTarget::Place(recent.clone()));
+                            } else if self.config.mode == DialogMode::Open {
+                                /* Goes through the listing, so that the file is accepted like any other */
+                                recent_file = Some(recent.clone());
+                            } else if let Some(parent) = recent.parent() {
+                                /* Saving over a recent file goes through the filename field, in its directory */
+                                navigation = Some(NavTarget::Place(parent.to_path_buf()));
+                                self.config.filename = name.into_owned();
+                            }
+                        }
+                        if !self.config.starred.is_empty() {
+                            ui.text_disabled("Starred");
+                        }
+                        let mut unstarred = None;
+                        for (i, file) in self.config.starred.iter().enumerate() {
+                            let name = file.file_name().unwrap_or(file.as_os_str()).to_string_lossy();
+                            if file.is_file() {
+                                /* Starred files are selected in their directory instead of being opened */
+                                if ui.selectable(format!("{}##starred{}", name, i)) {
+                                    if let Some(parent) = file.parent() {
+                                        navigation = Some(NavTarget::Place(parent.to_path_buf()));
+                                        revealed_file = Some(file.clone());
+                                    }
+                                }
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text(format!("File: {}", file.display()));
+                                }
+                            } else {
+                                ui.text_disabled(&name);
+                                if ui.is_item_hovered() {
+                                    ui.tooltip_text(format!("Missing: {}", file.display()));
+                                }
+                                ui.same_line();
+                                if ui.small_button(format!("x##Unstar {}", file.display())) {
+                                    unstarred = Some(i);
+                                }
+                            }
+                        }
+                        if let Some(i) = unstarred {
+                            self.config.starred.remove(i);
+                        }
+                    });
+                ui.same_line();
+            }
+            let show_pane = self.config.show_preview || self.config.show_details;
+            let listing_width = match show_pane {
+                true => -(self.preview_width + SPLITTER_WIDTH + ui.clone_style().item_spacing[0] * 2.0),
+                false => 0.0,
+            };
+            ui.child_window("Select file / directory")
+                .border(true)
+                .size([listing_width, content_height])
+                .build(|| {
+                    let style = ui.clone_style();
+                    if !self.search_box.hidden {
+                        let toggles: &[&str] = if cfg!(feature = "regex") { &["Aa", ".*"] } else { &["Aa"] };
+                        let toggles_width: f32 = toggles.iter()
+                            .map(|label| ui.calc_text_size(label)[0] + style.frame_padding[0] * 2.0 + style.item_spacing[0])
+                            .sum();
+                        if self.search_box.focus {
+                            ui.set_keyboard_focus_here();
+                            self.search_box.focus = false;
+                        }
+                        ui.set_next_item_width(-toggles_width);
+                        let submitted = ui.input_text("##Search", &mut search)
+                            .hint("Search")
+                            .enter_returns_true(true)
+                            .auto_select_all(true)
+                            .build();
+                        let search_active = ui.is_item_active();
+                        /* The search box already lost the focus to Escape, reverting what was typed since it had it */
+                        if self.search_box.focused && ui.is_key_pressed(Key::Escape) {
+                            self.search_key(SearchKey::Escape, &mut search);
+                            search_now = true;
+                        } else {
+                            self.search_box.focused = search_active;
+                        }
+                        ui.same_line();
+                        if search_toggle(ui, &style, "Aa", self.config.case_sensitive_search) {
+                            self.config.case_sensitive_search = !self.config.case_sensitive_search;
+                            search_options_changed = true;
+                        }
+                        if ui.is_item_hovered() {
+                            ui.tooltip_text("Match case");
+                        }
+                        #[cfg(feature = "regex")]
+                        {
+                            ui.same_line();
+                            if search_toggle(ui, &style, ".*", self.config.regex_search) {
+                                self.config.regex_search = !self.config.regex_search;
+                                search_options_changed = true;
+                            }
+                            if ui.is_item_hovered() {
+                                ui.tooltip_text("Use a regular expression");
+                            }
+                        }
+                        if let Some(error) = &self.search_error {
+                            ui.text_colored(ERROR_COLOR, error);
+                        }
+                        /* The history is offered while the search box is empty, or on demand with the down arrow */
+                        let history_len = self.search_history.queries().len();
+                        if search_active && history_len > 0 && (search.is_empty() || ui.is_key_pressed(Key::DownArrow)) {
+                            self.history_open = true;
+                        } else if search != self.search && !search.is_empty() {
+                            /* Typing a new query hides it */
+                            self.history_open = false;
+                            self.history_highlight = None;
+                        }
+                        if self.history_open && search_active {
+                            if ui.is_key_pressed(Key::DownArrow) {
+                                self.history_highlight = Some(self.history_highlight.map_or(0, |i| (i + 1).min(history_len - 1)));
+                            } else if ui.is_key_pressed(Key::UpArrow) {
+                                self.history_highlight = self.history_highlight.and_then(|i| i.checked_sub(1));
+                            }
+                        }
+                        let mut chosen_query = None;
+                        if submitted {
+                            chosen_query = self.history_highlight.filter(|_| self.history_open)
+                                .and_then(|i| self.search_history.queries().get(i).cloned())
+                                .or_else(|| Some(search.clone()));
+                        }
+                        if self.history_open && history_len > 0 {
+                            /* Stays open while the mouse is over it, the search box losing focus as soon as it is clicked */
+                            let mut keep_open = search_active;
+                            let mut clear_history = false;
+                            let height = (history_len + 1) as f32 * ui.text_line_height_with_spacing()
+                                + style.item_spacing[1] + style.window_padding[1] * 2.0;
+                            ui.child_window("##Search history")
+                                .border(true)
+                                .size([0.0, height])
+                                .build(|| {
+                                    keep_open |= ui.is_window_hovered();
+                                    for (i, query) in self.search_history.queries().iter().enumerate() {
+                                        let highlighted = self.history_highlight == Some(i);
+                                        if ui.selectable_config(format!("{query}##History {i}")).selected(highlighted).build() {
+                                            chosen_query = Some(query.clone());
+                                        }
+                                    }
+                                    ui.separator();
+                                    if ui.selectable("Clear history") {
+                                        clear_history = true;
+                                    }
+                                });
+                            if clear_history {
+                                self.search_history.clear();
+                            }
+                            if !keep_open || clear_history {
+                                self.history_open = false;
+                                self.history_highlight = None;
+                            }
+                        }
+                        if let Some(query) = chosen_query {
+                            search = query;
+                            search_now = true;
+                            self.search_history.push(&search);
+                            self.history_open = false;
+                            self.history_highlight = None;
+                        }
+                    }
+                    let mut undo_requested = false;
+                    if let Some(toast) = &self.undo {
+                        ui.text(toast.undo.message());
+                        ui.same_line();
+                        undo_requested = ui.small_button("Undo");
+                        if let Some(error) = &toast.error {
+                            ui.same_line();
+                            ui.text_colored(ERROR_COLOR, error);
+                        }
+                    }
+                    if undo_requested {
+                        self.undo_last();
+                    }
+                    let multi_select = self.config.multi_select;
+                    let file_operations = self.config.allow_file_operations;
+                    let single_click = self.config.single_click_activates;
+                    let mut start_rename = None;
+                    let mut start_delete = None;
+                    let mut show_properties = None;
+                    let mut renamed = None;
+                    let mut cancel_rename = false;
+                    /* Activated after drawing the listing, once its kind was read again */
+                    let mut activated_entry = None;
+                    let grid = self.config.view_mode == ViewMode::Grid;
+                    let (table_id, columns, table_flags) = match (grid, self.config.show_modified_column) {
+                        (true, _) => {
+                            let cell_width = self.thumbnails.as_ref().map_or(GRID_CELL_WIDTH, |thumbnails| thumbnails.size[0].max(GRID_CELL_WIDTH));
+                            let columns = (ui.content_region_avail()[0] / cell_width).max(1.0) as usize;
+                            ("Grid", columns, TableFlags::SIZING_STRETCH_SAME)
+                        }
+                        (false, show_modified) => {
+                            let columns = if show_modified { 3 } else { 2 };
+                            ("Listing", columns, TableFlags::SORTABLE | TableFlags::RESIZABLE | TableFlags::SIZING_STRETCH_PROP)
+                        }
+                    };
+                    /* Names are wrapped under the kind of the entry in grid view, and follow it in list view */
+                    #[cfg(feature = "icons")]
+                    let icons = self.config.icons.clone();
+                    let entry_kind = |kind: &'static str, _entry: &Entry| {
+                        #[cfg(feature = "icons")]
+                        let kind = icons.as_ref().map_or(kind, |icons| icons.for_entry(_entry));
+                        kind
+                    };
+                    let entry_name = |entry: &Entry| format!("{}{}", entry.display_name, if entry.kind.is_symlink() { " ->" } else { "" });
+                    let entry_label = |kind: &'static str, entry: &Entry| {
+                        let kind = entry_kind(kind, entry);
+                        match grid {
+                            true => format!("{}\n{}##{}", kind, wrap_text(ui, &entry_name(entry), GRID_CELL_WIDTH), entry.display_name),
+                            false => format!("{}\t{}", kind, entry_name(entry)),
+                        }
+                    };
+                    let style = ui.clone_style();
+                    /* Called right after drawing the label of an entry starting at `origin` */
+                    let highlight_label = |origin: [f32; 2], kind: &'static str, entry: &Entry, spans: &[Range<usize>]| {
+                        if spans.is_empty() {
+                            return;
+                        }
+                        let name = entry_name(entry);
+                        let (origin, lines) = match grid {
+                            /* Under the line of the kind */
+                            true => ([origin[0], origin[1] + ui.text_line_height()], wrap_lines(ui, &name, GRID_CELL_WIDTH).0),
+                            false => {
+                                let indent = ui.calc_text_size(format!("{}\t", entry_kind(kind, entry)))[0];
+                                ([origin[0] + indent, origin[1]], std::iter::once(0..name.len()).collect())
+                            }
+                        };
+                        highlight_matches(ui, origin, &name, &lines, spans, entry_text_color(&style, entry));
+                    };
+                    let span = if grid { SelectableFlags::empty() } else { SelectableFlags::SPAN_ALL_COLUMNS };
+                    let mut entries_drawn = 0;
+                    if let Some(_table) = ui.begin_table_with_flags(table_id, columns, table_flags) {
+                        if !grid {
+                            sort = setup_listing_columns(ui, &self.config);
+                        }
+                        for (entry, spans) in self.listing.visible.iter().map(|&i| &self.listing.entries[i]).zip(&self.listing.spans) {
+                            entries_drawn += 1;
+                            if !grid {
+                                ui.table_next_row();
+                            }
+                            ui.table_next_column();
+                            let _color = ui.push_style_color(StyleColor::Text, entry_text_color(&style, entry));
+                            if let (true, true, Some(thumbnails)) = (grid, entry.kind.is_file(), &mut self.thumbnails) {
+                                let provider = &thumbnails.provider;
+                                let texture = *thumbnails.cache.entry(entry.path.clone()).or_insert_with(|| provider(&entry.path));
+                                if let Some(texture) = texture {
+                                    imgui::Image::new(texture, thumbnails.size).build(ui);
+                                }
+                            }
+                            if let Some(rename) = self.renaming.as_mut().filter(|rename| rename.path == entry.path) {
+                                if rename.focus {
+                                    ui.set_keyboard_focus_here();
+                                    rename.focus = false;
+                                }
+                                let submitted = ui.input_text(format!("##Rename {}", entry.path.display()), &mut rename.input.name)
+                                    .enter_returns_true(true)
+                                    .auto_select_all(true)
+                                    .build();
+                                if let Some(error) = &rename.input.error {
+                                    ui.text_colored(ERROR_COLOR, error);
+                                }
+                                if submitted {
+                                    match rename_entry(&entry.path, &rename.input.name) {
+                                        Ok(to) => renamed = Some((entry.path.clone(), to)),
+                                        Err(error) => rename.input.error = Some(error),
+                                    }
+                                } else if ui.is_key_pressed(Key::Escape) {
+                                    cancel_rename = true;
+                                }
+                                continue;
+                            }
+                            if self.pending_focus.as_ref() == Some(&entry.path) {
+                                ui.set_keyboard_focus_here();
+                                self.pending_focus = None;
+                            }
+                            let unselectable = entry.kind == EntryKind::BrokenSymlink
+                                && self.config.mode == DialogMode::Open
+                                && !self.config.select_broken_symlinks;
+                            if !entry.kind.is_dir() {
+                                let label = entry_label("[file]", entry);
+                                let origin = ui.cursor_screen_pos();
+                                let disabled = ui.begin_disabled(unselectable);
+                                if multi_select {
+                                    let selected = self.selection.contains(&entry.path);
+                                    if ui.selectable_config(label).selected(selected).flags(span).build() {
+                                        toggle_selection(&mut self.selection, &entry.path);
+                                    }
+                                } else {
+                                    let highlighted = self.highlighted.as_ref() == Some(&entry.path);
+                                    if ui.selectable_config(label)
+                                        .selected(highlighted)
+                                        .allow_double_click(true)
+                                        .flags(span)
+                                        .build() {
+                                        let double_clicked = ui.is_mouse_double_clicked(MouseButton::Left);
+                                        self.highlighted = Some(entry.path.clone());
+                                        match self.config.mode {
+                                            DialogMode::Open => if double_clicked || single_click {
+                                                activated_entry = Some(entry.path.clone());
+                                            },
+                                            DialogMode::Save => {
+                                                /* Saving over a file goes through the filename field, like a typed name */
+                                                self.config.filename = entry.name.to_string_lossy().into_owned();
+                                                if double_clicked {
+                                                    activated_entry = Some(entry.path.clone());
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+                                highlight_label(origin, "[file]", entry, spans);
+                                disabled.end();
+                            } else {
+                                if multi_select && self.config.dirs_only {
+                                    let mut selected = self.selection.contains(&entry.path);
+                                    if ui.checkbox(format!("##Select {}", entry.path.display()), &mut selected) {
+                                        toggle_selection(&mut self.selection, &entry.path);
+                                    }
+                                    ui.same_line();
+                                }
+                                let highlighted = self.highlighted.as_ref() == Some(&entry.path);
+                                let origin = ui.cursor_screen_pos();
+                                if ui.selectable_config(entry_label("[dir] ", entry))
+                                    .selected(highlighted)
+                                    .allow_double_click(true)
+                                    .flags(span)
+                                    .build()
+                                {
+                                    if single_click || ui.is_mouse_double_clicked(MouseButton::Left) {
+                                        activated_entry = Some(entry.path.clone());
+                                    } else {
+                                        self.highlighted = Some(entry.path.clone());
+                                    }
+                                }
+                                highlight_label(origin, "[dir] ", entry, spans);
+                            }
+                            if let Some(target) = &entry.link_target {
+                                if ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+                                    match entry.kind {
+                                        EntryKind::BrokenSymlink => ui.tooltip_text(format!("Broken link to {}", target.display())),
+                                        _ => ui.tooltip_text(format!("Link to {}", target.display())),
+                                    }
+                                }
+                            }
+                            let mut activated = false;
+                            if ui.is_item_focused() {
+                                focused = Some(entry.display_name.clone());
+                                activated = Shortcut::Activate.pressed(ui);
+                                if file_operations && Shortcut::Rename.pressed(ui) {
+                                    start_rename = Some(entry);
+                                }
+                                if file_operations && Shortcut::Delete.pressed(ui) {
+                                    start_delete = Some(entry);
+                                }
+                            }
+                            if ui.is_item_clicked_with_button(MouseButton::Right) {
+                                /* The menu acts on the entry it was opened on, which replaces the selection */
+                                if multi_select && (entry.kind.is_file() || self.config.dirs_only) {
+                                    if !self.selection.contains(&entry.path) {
+                                        self.selection = vec![entry.path.clone()];
+                                    }
+                                } else {
+                                    self.highlighted = Some(entry.path.clone());
+                                }
+                            }
+                            if let Some(_menu) = ui.begin_popup_context_item() {
+                                activated |= ui.selectable_config(if entry.kind.is_dir() { "Enter" } else { "Open" })
+                                    .disabled(unselectable)
+                                    .build();
+                                if ui.selectable("Copy path") {
+                                    ui.set_clipboard_text(entry.path.display().to_string());
+                                }
+                                if entry.kind.is_file() {
+                                    match self.config.starred.iter().position(|starred| *starred == entry.path) {
+                                        Some(i) => if ui.selectable("Unstar") {
+                                            self.config.starred.remove(i);
+                                        },
+                                        None => if ui.selectable("Star") {
+                                            self.config.starred.push(entry.path.clone());
+                                        },
+                                    }
+                                }
+                                if file_operations && ui.selectable("Rename") {
+                                    start_rename = Some(entry);
+                                }
+                                if file_operations && ui.selectable("Delete") {
+                                    start_delete = Some(entry);
+                                }
+                                if ui.selectable("Properties") {
+                                    show_properties = Some(entry);
+                                }
+                            }
+                            if activated {
+                                activated_entry = Some(entry.path.clone());
+                            }
+                            if grid {
+                                continue;
+                            }
+                            /* Drawn last, so that the checks above still apply to the entry itself */
+                            ui.table_next_column();
+                            let size = match (entry.kind.is_dir(), entry.size) {
+                                (true, _) => String::from("-"),
+                                (false, Some(size)) => format_size(size),
+                                (false, None) => String::from("?"),
+                            };
+                            let [x, y] = ui.cursor_pos();
+                            ui.set_cursor_pos([x + (ui.content_region_avail()[0] - ui.calc_text_size(&size)[0]).max(0.0), y]);
+                            ui.text_disabled(&size);
+                            if self.config.show_modified_column {
+                                ui.table_next_column();
+                                ui.text_disabled(entry.modified.and_then(format_time).unwrap_or_else(|| String::from("—")));
+                            }
+                        }
+                    }
+                    self.draw_stats.entries_drawn = entries_drawn;
+                    if self.draw_stats.rebuilds == 0 {
+                        self.draw_stats.cache_hits = entries_drawn;
+                    }
+                    if let Some(entry) = start_rename {
+                        self.renaming = Some(Rename {
+                            path: entry.path.clone(),
+                            input: NameInput { name: entry.name.to_string_lossy().into_owned(), error: None },
+                            focus: true,
+                        });
+                    }
+                    if cancel_rename {
+                        self.renaming = None;
+                    }
+                    if let Some(entry) = start_delete {
+                        let to_trash = trash::is_available() || !self.config.permanent_delete_fallback;
+                        self.pending_delete = Some(Delete { path: entry.path.clone(), to_trash, recursive: false, error: None });
+                        open_delete_popup = true;
+                    }
+                    if let Some(entry) = show_properties {
+                        self.properties = entry_details(&entry.path);
+                        open_properties_popup = true;
+                    }
+                    if let Some((from, to)) = renamed {
+                        self.entry_renamed(&from, &to);
+                        self.renaming = None;
+                        if from != to {
+                            self.offer_undo(Undo::Rename { from, to });
+                        }
+                    }
+                    if let Some(activated) = activated_entry {
+                        match self.activate(&activated) {
+                            Some(AcceptAction::Return(chosen)) => path = Some(chosen),
+                            Some(AcceptAction::ReturnMany(chosen)) => selected_many = Some(chosen),
+                            Some(AcceptAction::Enter(dir)) => navigation = Some(NavTarget::Entry(dir)),
+                            None => {}
+                        }
+                    }
+                    if let Some(error) = &self.listing.error {
+                        ui.text_disabled(error);
+                    } else if let Some(placeholder) = Placeholder::for_listing(&counts) {
+                        /* Placed a third of the way down the listing */
+                        let [x, y] = ui.cursor_pos();
+                        ui.set_cursor_pos([x, y + ui.content_region_avail()[1] / 3.0]);
+                        center_next_item(ui, ui.calc_text_size(placeholder.message())[0]);
+                        ui.text_disabled(placeholder.message());
+                        if placeholder == Placeholder::AllHidden {
+                            let label = "Show hidden files";
+                            center_next_item(ui, ui.calc_text_size(label)[0] + ui.clone_style().frame_padding[0] * 2.0);
+                            if ui.button(label) {
+                                show_hidden_files = true;
+                            }
+                        }
+                    }
+                    /* Entries have their own menu, this one opens between them */
+                    if ui.is_window_hovered() && !ui.is_any_item_hovered() && ui.is_mouse_released(MouseButton::Right) {
+                        ui.open_popup(LISTING_POPUP);
+                    }
+                    if let Some(_menu) = ui.begin_popup(LISTING_POPUP) {
+                        /* The name of the folder is typed in the controls row */
+                        if self.config.allow_new_folder && self.config.show_controls && ui.selectable("New Folder") {
+                            self.new_folder = Some(NameInput { name: String::from("New Folder"), error: None });
+                        }
+                        if ui.selectable("Refresh") {
+                            self.invalidate();
+                        }
+                    }
+                });
+            if show_pane {
+                ui.same_line();
+                ui.invisible_button("##Preview splitter", [SPLITTER_WIDTH, content_height]);
+                if ui.is_item_hovered() || ui.is_item_active() {
+                    ui.set_mouse_cursor(Some(MouseCursor::ResizeEW));
+                }
+                if ui.is_item_active() {
+                    let max_width = ui.window_content_region_max()[0] / 2.0;
+                    self.preview_width = (self.preview_width - ui.io().mouse_delta[0]).clamp(PREVIEW_WIDTH / 2.0, max_width.max(PREVIEW_WIDTH / 2.0));
+                }
+                ui.same_line();
+                ui.child_window("Preview")
+                    .border(true)
+                    .horizontal_scrollbar(true)
+                    .size([0.0, content_height])
+                    .build(|| {
+                        let Some(preview) = &self.preview else {
+                            ui.text_disabled("Nothing selected");
+                            return;
+                        };
+                        for (label, value) in &preview.details {
+                            ui.text_disabled(label);
+                            ui.text_wrapped(value);
+                        }
+                        if !preview.details.is_empty() && preview.text.is_some() {
+                            ui.separator();
+                        }
+                        match &preview.text {
+                            Some(Ok(text)) => ui.text(text),
+                            Some(Err(notice)) => ui.text_disabled(notice),
+                            None => {}
+                        }
+                    });
+            }
+            if self.config.show_controls {
+                ui.child_window("controls")
+                    .border(false)
+                    .size([0.0, -self.status_bar_height()])
                     .build(||{
-                        ui.button("Path: ");
+                        if let Some(footer) = &self.footer {
+                            (footer.borrow_mut())(ui);
+                        }
+                        if self.config.mode == DialogMode::Save {
+                            ui.text("Filename:");
+                            ui.same_line();
+                            ui.set_next_item_width(FILENAME_WIDTH);
+                            if self.focus_filename {
+                                ui.set_keyboard_focus_here();
+                                self.focus_filename = false;
+                            }
+                            accept_requested |= ui.input_text("##Filename", &mut self.config.filename)
+                                .auto_select_all(true)
+                                .enter_returns_true(true)
+                                .build();
+                        }
+                        if !filter_labels.is_empty() {
+                            ui.same_line();
+                            /* Applied right away, so that an accept in the same frame uses the new filter */
+                            let mut active_filter = self.config.active_filter;
+                            if ui.combo_simple_string("##Filter", &mut active_filter, &filter_labels) {
+                                self.select_filter(active_filter);
+                            }
+                        }
+                        ui.same_line();
+                        let disabled = ui.begin_disabled(parent_dir(&self.current_dir).is_none());
+                        if ui.button("Back") {
+                            navigation = Some(NavTarget::Parent);
+                        }
+                        disabled.end();
                         ui.same_line();
-                        std::env::current_dir().unwrap().iter().for_each(|dir|{
-                            if ui.button(dir.to_string_lossy()) {
-                                std::env::set_current_dir(dir)
-                                    .map_err(|err| log::error!("Can't change directory to {}: {}", dir.to_string_lossy(), err.to_string()))
-                                    .ok();
+                        if self.config.allow_new_folder {
+                            match &mut self.new_folder {
+                                Some(input) => {
+                                    ui.set_next_item_width(FILENAME_WIDTH);
+                                    let mut create = ui.input_text("##New folder", &mut input.name)
+                                        .enter_returns_true(true)
+                                        .build();
+                                    ui.same_line();
+                                    create |= ui.button("Create");
+                                    ui.same_line();
+                                    let dismissed = ui.button("x##Dismiss new folder");
+                                    if let Some(error) = &input.error {
+                                        ui.same_line();
+                                        ui.text_colored(ERROR_COLOR, error);
+                                    }
+                                    if dismissed {
+                                        self.new_folder = None;
+                                    } else if create {
+                                        match create_folder(&self.current_dir, &input.name) {
+                                            Ok(folder) => {
+                                                navigation = Some(NavTarget::Entry(folder));
+                                                self.new_folder = None;
+                                            }
+                                            Err(error) => input.error = Some(error),
+                                        }
+                                    }
+                                }
+                                None => if ui.button("New Folder") {
+                                    self.new_folder = Some(NameInput { name: String::from("New Folder"), error: None });
+                                },
                             }
-                            if ui.is_item_hovered() {
-                                ui.tooltip_text(format!("Directory: {}", dir.to_string_lossy()));
+                            ui.same_line();
+                        }
+                        let action = self.accept_action();
+                        let selects_folder = self.selects_folder(action.as_ref());
+                        let accept_state = self.accept_state(action.as_ref());
+                        let clicked = match &self.accept_button_fn {
+                            Some(accept_button_fn) => (accept_button_fn.borrow_mut())(ui, &accept_state),
+                            None => {
+                                let disabled = ui.begin_disabled(!accept_state.enabled);
+                                let clicked = ui.button(&accept_state.label);
+                                disabled.end();
+                                if let Some(tooltip) = &accept_state.tooltip {
+                                    if ui.is_item_hovered_with_flags(ItemHoveredFlags::ALLOW_WHEN_DISABLED) {
+                                        ui.tooltip_text(tooltip);
+                                    }
+                                }
+                                clicked
                             }
+                        };
+                        /* Tells which folder gets selected, the current one or the highlighted one */
+                        if let (true, Some(AcceptAction::Return(folder))) = (selects_folder, &action) {
                             ui.same_line();
-                        })
+                            ui.text_disabled(folder.file_name().unwrap_or(folder.as_os_str()).to_string_lossy());
+                        }
+                        if let Some(error) = &self.validation_error {
+                            ui.same_line();
+                            ui.text_colored(ERROR_COLOR, error);
+                        }
+                        accept_requested |= clicked;
+                        ui.same_line();
+                        cancelled |= match &self.cancel_button_fn {
+                            Some(cancel_button_fn) => (cancel_button_fn.borrow_mut())(ui, &self.config.cancel_text),
+                            None => ui.button(&self.config.cancel_text),
+                        };
+                        ui.same_line();
+                        ui.checkbox("Hidden Files", &mut show_hidden_files);
+                        ui.same_line();
+                        let (view_label, other_view) = match self.config.view_mode {
+                            ViewMode::List => ("Grid View", ViewMode::Grid),
+                            ViewMode::Grid => ("List View", ViewMode::List),
+                        };
+                        if ui.button(view_label) {
+                            self.config.view_mode = other_view;
+                        }
+                        for option in &mut self.config.options {
+                            ui.same_line();
+                            ui.checkbox(format!("{}##{}", option.label, option.id), &mut option.value);
+                        }
                     });
-                ui.child_window("Select file / directory")
-                    .border(true)
-                    .size([0.0, -32.0])
-                    .build(|| {
-                        let mut entries: Vec<_> = fs::read_dir(std::env::current_dir().unwrap())
-                            .unwrap()
-                            .filter_map(|entry| {
-                                let entry = entry.expect("Filesystem entry error");
-                                if self.show_hidden_files {
-                                   Some(entry) 
-                                } else {
-                                    if !entry.path().starts_with(".") {
-                                        Some(entry)
-                                    } else {
-                                        None
+            }
+            if self.config.show_status_bar {
+                if self.search.is_empty() {
+                    ui.text_disabled(format!("{} shown", counts.shown));
+                } else {
+                    ui.text_disabled(format!("{} of {} shown", counts.shown, counts.searched));
+                }
+                if counts.hidden > 0 {
+                    ui.same_line();
+                    ui.text_disabled("·");
+                    ui.same_line();
+                    if ui.small_button(format!("{} hidden", counts.hidden)) {
+                        show_hidden_files = true;
+                    }
+                    if ui.is_item_hovered() {
+                        ui.tooltip_text("Show hidden files");
+                    }
+                }
+            }
+            /* Outside the controls row, so that double clicks and Enter accept without it */
+            if accept_requested {
+                self.recheck_selection();
+                match self.accept_action() {
+                    Some(AcceptAction::Return(chosen)) => path = Some(chosen),
+                    Some(AcceptAction::ReturnMany(chosen)) => selected_many = Some(chosen),
+                    Some(AcceptAction::Enter(dir)) => navigation = Some(NavTarget::Entry(dir)),
+                    None => {}
+                }
+            }
+            let chosen = match (path.take(), selected_many.take()) {
+                (Some(chosen), _) => Some(DialogResult::Selected(chosen)),
+                (None, Some(chosen)) => Some(DialogResult::SelectedMany(chosen)),
+                (None, None) => None,
+            };
+            let mut accepted = chosen.and_then(|chosen| self.run_accept(chosen, 0));
+            if open_delete_popup {
+                ui.open_popup(DELETE_POPUP);
+            }
+            if open_properties_popup {
+                ui.open_popup(PROPERTIES_POPUP);
+            }
+            if let Some(_popup) = ui.begin_popup(HELP_POPUP) {
+                for (keys, description) in self.shortcut_descriptions() {
+                    ui.text(keys);
+                    ui.same_line_with_pos(HELP_KEYS_WIDTH);
+                    ui.text_disabled(description);
+                }
+            }
+            if let Some(_popup) = ui.begin_popup(PROPERTIES_POPUP) {
+                for (label, value) in &self.properties {
+                    ui.text_disabled(label);
+                    ui.text(value);
+                }
+            }
+            if let Some(_popup) = ui.begin_modal_popup(DELETE_POPUP) {
+                let permanent_fallback = self.config.permanent_delete_fallback;
+                match &mut self.pending_delete {
+                    Some(delete) => {
+                        if delete.to_trash {
+                            ui.text(format!("Move {} to the trash?", delete.path.display()));
+                        } else if delete.recursive {
+                            ui.text(format!("{} is not empty.", delete.path.display()));
+                            ui.text("Delete it and everything in it permanently?");
+                        } else {
+                            ui.text(format!("Delete {} permanently?", delete.path.display()));
+                        }
+                        if let Some(error) = &delete.error {
+                            ui.text_colored(ERROR_COLOR, error);
+                        }
+                        let label = match (delete.to_trash, delete.recursive) {
+                            (true, _) => "Move to Trash",
+                            (false, true) => "Delete everything",
+                            (false, false) => "Delete",
+                        };
+                        if ui.button(label) {
+                            let mut trashed = None;
+                            let result = if delete.to_trash {
+                                match trash::move_to_trash(&delete.path) {
+                                    Ok(moved) => {
+                                        trashed = Some(moved);
+                                        Ok(true)
                                     }
+                                    Err(error) => Err(error),
                                 }
-                            })
-                            .collect();
-                        /* Sorting directories first to make it easier to navigate */
-                        entries.sort_by(|a, b| {
-                            if a.path().is_dir() && !b.path().is_dir() {
-                                Ordering::Less
-                            } else if !a.path().is_dir() && b.path().is_dir() {
-                                Ordering::Greater
                             } else {
-                                a.path().cmp(&b.path())
-                            }
-                        });
-                        for entry in entries {
-                            if entry.path().is_file() && !self.dirs_only {
-                                if ui.button(format!("[file]\t{}", PathBuf::from(entry.path().iter().last().unwrap()).display())) {
-                                    path = Some(entry.path());
+                                delete_entry(&delete.path, delete.recursive)
+                            };
+                            match result {
+                                Ok(true) => {
+                                    deleted = Some((delete.path.clone(), trashed));
+                                    ui.close_current_popup();
                                 }
-                            } else if entry.path().is_dir() {
-                                if ui.button(format!("[dir] \t{}", PathBuf::from(entry.path().iter().last().unwrap()).display())) {
-                                    std::env::set_current_dir(entry.path())
-                                        .map_err(|e|{
-                                            log::error!("Can't access '{}': {}", entry.path().display(), e.to_string());
-                                            path = None;
-                                        })
-                                        .ok();
+                                Ok(false) => delete.recursive = true,
+                                Err(error) => {
+                                    /* The reason stays visible while asking for a permanent deletion */
+                                    delete.to_trash &= !permanent_fallback;
+                                    delete.error = Some(error);
                                 }
                             }
                         }
-                    });
-                    ui.child_window("controls")
-                        .border(false)
-                        .build(||{
-                            if !self.is_open {
-                                ui.text(format!("Filename: {}", self.filename));
-                            }
-                            ui.same_line();
-                            if ui.button("Back") {
-                                let dir = {
-                                    let mut tmp = std::env::current_dir().unwrap();
-                                    tmp.pop();
-                                    tmp
-                                };
-                                std::env::set_current_dir(dir).ok();
-                            }
-                            ui.same_line();
-                            ui.button("Open");
-                            ui.same_line();
-                            if ui.checkbox("Hidden Files", &mut self.show_hidden_files) {
-                                self.show_hidden_files = !self.show_hidden_files;
-                            }
-                        })
-            });
-            path
+                        ui.same_line();
+                        if ui.button("Cancel##Delete") {
+                            self.pending_delete = None;
+                            ui.close_current_popup();
+                        }
+                    }
+                    None => ui.close_current_popup(),
+                }
+            }
+            /* The answer resumes the choice once the popup is closed, so that a later question opens its own */
+            let mut answer = None;
+            let question = self.pending_choice.as_ref().map(|pending| &pending.question);
+            if let Some(_popup) = ui.begin_modal_popup(OVERWRITE_POPUP) {
+                if let Some(AcceptQuestion::Overwrite(file)) = question {
+                    let name = file.file_name().unwrap_or(file.as_os_str()).to_string_lossy();
+                    ui.text(format!("\"{}\" already exists. Do you want to replace it?", name));
+                }
+                if ui.button("Yes") {
+                    answer = Some(true);
+                    ui.close_current_popup();
+                }
+                ui.same_line();
+                if ui.button("No") {
+                    answer = Some(false);
+                    ui.close_current_popup();
+                }
+            }
+            if let Some(_popup) = ui.begin_modal_popup(ACCESS_POPUP) {
+                if let Some(AcceptQuestion::Access(error)) = question {
+                    ui.text_colored(ERROR_COLOR, error);
+                }
+                if ui.button("Choose anyway") {
+                    answer = Some(true);
+                    ui.close_current_popup();
+                }
+                ui.same_line();
+                if ui.button("Go back") {
+                    answer = Some(false);
+                    ui.close_current_popup();
+                }
+            }
+            if let Some(yes) = answer {
+                accepted = self.answer_accept(yes);
+            }
+            if let Some(pending) = &self.pending_choice {
+                if !ui.is_popup_open(pending.question.popup()) {
+                    ui.open_popup(pending.question.popup());
+                }
+            }
+            match accepted {
+                Some(DialogResult::Selected(chosen)) => path = Some(chosen),
+                Some(DialogResult::SelectedMany(chosen)) => selected_many = Some(chosen),
+                _ => {}
+            }
+        }).is_some();
+        let closed = self.window_event(WindowEvent::of(drawn, opened));
+        /* Filtering a large directory on every keystroke would stall the typing, so the listing waits
+         * for a pause unless the query was submitted or the options changed */
+        if search != self.search {
+            self.search = search;
+            self.search_debounce.changed(SEARCH_DEBOUNCE);
+        }
+        let elapsed = Duration::from_secs_f32(ui.io().delta_time.max(0.0));
+        self.tick_undo(elapsed);
+        if self.search_debounce.tick(elapsed) || search_now || search_options_changed {
+            self.search_debounce.cancel();
+            self.update_search();
+            self.apply_filters();
+        }
+        if show_hidden_files != self.config.show_hidden_files {
+            self.config.show_hidden_files = show_hidden_files;
+            self.apply_filters();
+        }
+        if let Some((by, order)) = sort.filter(|&sort| sort != (self.config.sort_by, self.config.sort_order)) {
+            self.config.sort_by = by;
+            self.config.sort_order = order;
+            self.sort_listing();
+        }
+        if let Some((path, trashed)) = deleted {
+            self.entry_deleted(&path);
+            if let Some(trashed) = trashed {
+                self.offer_undo(Undo::Trash { path, trashed });
+            }
+        }
+        self.accessibility = AccessibilityState { counts, focused };
+        if close_path_input {
+            self.editing_path = None;
+        }
+        if let Some(target) = navigation {
+            self.navigate_to(target);
+        }
+        if let Some(file) = recent_file {
+            self.choose_recent(&file);
+        }
+        if let Some(file) = revealed_file {
+            self.reveal(file);
+        }
+        let result = match (selected_many, path) {
+            (Some(paths), _) => DialogResult::SelectedMany(paths),
+            (None, Some(path)) => DialogResult::Selected(path),
+            (None, None) if cancelled || closed => DialogResult::Cancelled,
+            (None, None) => DialogResult::StillOpen,
+        };
+        if !result.is_open() {
+            self.closed(&result);
+        }
+        result
+    }
+
+    /// Called when the dialog returns a result. If it is drawn again, that's a new opening.
+    fn closed(&mut self, result: &DialogResult) {
+        self.search_history.push(&self.search);
+        if let (DialogResult::Selected(_) | DialogResult::SelectedMany(_), Some((memory, purpose))) = (result, &self.memory) {
+            memory.set_last_directory(purpose.as_str(), &self.current_dir);
+            for path in result.clone().paths() {
+                memory.push_recent(path);
+            }
+        }
+        self.places = None;
     }
 }
 
@@ -196,3 +3764,1188 @@ impl Default for FileDialog {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::navigation::NAVIGATION_LOG_CAPACITY;
+
+    /// Creates an ImGui context to draw dialogs in, one frame at a time. ImGui has a single global context,
+    /// so the tests using one take turns.
+    fn with_context(test: impl FnOnce(&mut imgui::Context)) {
+        static CONTEXT: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _lock = CONTEXT.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        let mut context = imgui::Context::create();
+        context.set_ini_filename(None);
+        context.io_mut().display_size = [1280.0, 720.0];
+        context.fonts().build_rgba32_texture();
+        test(&mut context);
+    }
+
+    /// Draws `dialog` in a new frame and returns the result.
+    fn draw(context: &mut imgui::Context, dialog: &mut FileDialog) -> DialogResult {
+        let result = dialog.spawn_borrowed(context.new_frame());
+        context.render();
+        result
+    }
+
+    #[test]
+    fn parent_of_unix_paths() {
+        assert_eq!(parent_dir(Path::new("/home/user")), Some(PathBuf::from("/home")));
+        assert_eq!(parent_dir(Path::new("/home")), Some(PathBuf::from("/")));
+        assert_eq!(parent_dir(Path::new("/")), None);
+        assert_eq!(parent_dir(Path::new("docs")), None);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn parent_of_drive_paths() {
+        assert_eq!(parent_dir(Path::new(r"C:\Users\me")), Some(PathBuf::from(r"C:\Users")));
+        assert_eq!(parent_dir(Path::new(r"C:\Users")), Some(PathBuf::from(r"C:\")));
+        assert_eq!(parent_dir(Path::new(r"C:\")), None);
+        assert_eq!(parent_dir(Path::new("C:")), None);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn parent_of_network_paths() {
+        assert_eq!(parent_dir(Path::new(r"\\server\share\dir")), Some(PathBuf::from(r"\\server\share\")));
+        assert_eq!(parent_dir(Path::new(r"\\server\share\")), None);
+        assert_eq!(parent_dir(Path::new(r"\\server\share")), None);
+    }
+
+    #[test]
+    fn crumbs_that_fit_are_all_shown() {
+        assert_eq!(hidden_crumbs(&[10.0, 20.0, 20.0, 20.0], 70.0, 5.0), 1..1);
+        assert_eq!(hidden_crumbs(&[10.0, 20.0, 20.0, 20.0], 500.0, 5.0), 1..1);
+        assert_eq!(hidden_crumbs(&[], 0.0, 5.0), 1..1);
+        /* The root and a single segment are never collapsed, even when they overflow */
+        assert_eq!(hidden_crumbs(&[10.0, 200.0], 50.0, 5.0), 1..1);
+    }
+
+    #[test]
+    fn middle_crumbs_collapse_first() {
+        /* One pixel short: the shallowest segment after the root goes first */
+        assert_eq!(hidden_crumbs(&[10.0, 20.0, 20.0, 20.0], 69.0, 5.0), 1..2);
+        /* Exactly enough room for the root, the ellipsis and the two deepest segments */
+        assert_eq!(hidden_crumbs(&[10.0, 20.0, 20.0, 20.0], 55.0, 5.0), 1..2);
+        assert_eq!(hidden_crumbs(&[10.0, 20.0, 20.0, 20.0], 54.0, 5.0), 1..3);
+        assert_eq!(hidden_crumbs(&[10.0, 20.0, 20.0, 20.0, 20.0], 70.0, 5.0), 1..3);
+    }
+
+    #[test]
+    fn deepest_crumb_is_always_shown() {
+        assert_eq!(hidden_crumbs(&[10.0, 20.0, 100.0], 50.0, 5.0), 1..2);
+        assert_eq!(hidden_crumbs(&[10.0, 20.0, 20.0, 100.0], 0.0, 5.0), 1..3);
+    }
+
+    #[test]
+    fn crumbs_keep_fitting_while_navigating_deeper() {
+        let (available, ellipsis) = (120.0, 8.0);
+        let mut widths = vec![12.0];
+        for depth in 1..40 {
+            widths.push(10.0 + (depth * 7 % 30) as f32);
+            let hidden = hidden_crumbs(&widths, available, ellipsis);
+            let shown: f32 = widths.iter().enumerate()
+                .filter(|(i, _)| !hidden.contains(i))
+                .map(|(_, width)| width)
+                .sum();
+            let ellipsis = if hidden.is_empty() { 0.0 } else { ellipsis };
+            assert!(shown + ellipsis <= available, "depth {}: {:?} doesn't fit", depth, hidden);
+            /* Only a run of segments right after the root is hidden, never the deepest ones */
+            assert_eq!(hidden.start, 1);
+            assert!(hidden.end < widths.len());
+        }
+    }
+
+    fn counts(shown: usize, hidden: usize, filtered: usize, searched: usize) -> ListingCounts {
+        ListingCounts { shown, hidden, filtered, searched }
+    }
+
+    #[test]
+    fn nothing_is_displayed_over_a_listing() {
+        assert_eq!(Placeholder::for_listing(&counts(1, 0, 0, 1)), None);
+        assert_eq!(Placeholder::for_listing(&counts(3, 5, 7, 4)), None);
+    }
+
+    #[test]
+    fn empty_listings_tell_why() {
+        assert_eq!(Placeholder::for_listing(&counts(0, 0, 0, 0)), Some(Placeholder::EmptyDirectory));
+        assert_eq!(Placeholder::for_listing(&counts(0, 2, 0, 0)), Some(Placeholder::AllHidden));
+        assert_eq!(Placeholder::for_listing(&counts(0, 0, 2, 0)), Some(Placeholder::FilteredOut));
+        assert_eq!(Placeholder::for_listing(&counts(0, 0, 0, 2)), Some(Placeholder::NoMatch));
+    }
+
+    #[test]
+    fn placeholders_name_the_last_exclusion() {
+        /* The search applies to what the filters let through, which excludes hidden files */
+        assert_eq!(Placeholder::for_listing(&counts(0, 2, 2, 2)), Some(Placeholder::NoMatch));
+        assert_eq!(Placeholder::for_listing(&counts(0, 2, 2, 0)), Some(Placeholder::FilteredOut));
+    }
+
+    #[test]
+    fn builder_and_config_agree() {
+        let dir = std::env::temp_dir();
+        let built = FileDialog::new()
+            .for_save()
+            .title("Export")
+            .window_size([800.0, 500.0])
+            .accept_text("Export")
+            .cancel_text("Back")
+            .start_directory(&dir)
+            .default_filename("scene")
+            .default_extension(".PNG")
+            .filter(&["png"])
+            .filter_glob("scene-*")
+            .add_filter("Images", &["png", ".jpg"])
+            .confirm_overwrite(false)
+            .sort_by(SortBy::Modified, SortOrder::Descending)
+            .natural_sort(false)
+            .dotfiles_position(DotfilesPosition::Last)
+            .star("/tmp/starred.txt")
+            .option_checkbox("layers", "Keep layers", true)
+            .view_mode(ViewMode::Grid)
+            .show_sidebar(false)
+            .show_status_bar(false);
+        let configured = FileDialog::from_config(DialogConfig {
+            mode: DialogMode::Save,
+            title: String::from("Export"),
+            window_size: [800.0, 500.0],
+            accept_text: String::from("Export"),
+            cancel_text: String::from("Back"),
+            start_directory: Some(dir),
+            filename: String::from("scene"),
+            default_extension: Some(String::from("png")),
+            extensions: vec![String::from("png")],
+            globs: vec![String::from("scene-*")],
+            filters: vec![FileFilter::new("Images", &["png", "jpg"])],
+            confirm_overwrite: false,
+            sort_by: SortBy::Modified,
+            sort_order: SortOrder::Descending,
+            natural_sort: false,
+            dotfiles_position: DotfilesPosition::Last,
+            starred: vec![PathBuf::from("/tmp/starred.txt")],
+            options: vec![OptionCheckbox { id: String::from("layers"), label: String::from("Keep layers"), value: true }],
+            view_mode: ViewMode::Grid,
+            show_sidebar: false,
+            show_status_bar: false,
+            ..DialogConfig::default()
+        });
+        assert_eq!(built.config(), configured.config());
+        assert_eq!(built.current_dir(), configured.current_dir());
+        assert_eq!(FileDialog::new().config(), FileDialog::default().config());
+    }
+
+    /// Returns the names the dialog lists when searching for `query` among entries named `names`.
+    fn search(names: &[&str], query: &str) -> Vec<String> {
+        let mut dialog = FileDialog::new();
+        dialog.listing.entries = names.iter().map(|name| Entry::fake(name, EntryKind::File)).collect();
+        dialog.search = String::from(query);
+        dialog.update_search();
+        dialog.apply_filters();
+        dialog.listing.visible.iter().map(|&i| dialog.listing.entries[i].display_name.clone()).collect()
+    }
+
+    #[test]
+    fn decomposed_names_match_composed_queries() {
+        assert_eq!(search(&["cafe\u{301}.png", "cafe.png"], "caf\u{e9}"), ["caf\u{e9}.png"]);
+        assert_eq!(search(&["Cafe\u{301}.png"], "CAF\u{c9}"), ["Caf\u{e9}.png"]);
+    }
+
+    #[test]
+    fn composed_names_match_decomposed_queries() {
+        assert_eq!(search(&["caf\u{e9}.png", "cafe.png"], "cafe\u{301}"), ["caf\u{e9}.png"]);
+        assert!(search(&["caf\u{e9}.png"], "cafe").is_empty());
+    }
+
+    #[test]
+    fn toggling_hidden_files_filters_the_listing_again() {
+        let mut dialog = FileDialog::new();
+        dialog.listing.entries = vec![
+            Entry::fake(".git", EntryKind::Dir),
+            Entry::fake("src", EntryKind::Dir),
+            Entry::fake(".gitignore", EntryKind::File),
+            Entry::fake("Cargo.toml", EntryKind::File),
+        ];
+        dialog.apply_filters();
+        assert_eq!(dialog.listing.visible, [1, 3]);
+        assert_eq!(dialog.listing.counts.hidden, 2);
+        dialog.config.show_hidden_files = true;
+        dialog.apply_filters();
+        assert_eq!(dialog.listing.visible, [0, 1, 2, 3]);
+        assert_eq!(dialog.listing.counts.hidden, 0);
+    }
+
+    #[test]
+    fn options_start_with_their_default() {
+        let dialog = FileDialog::new()
+            .option_checkbox("read-only", "Open as read-only", false)
+            .option_checkbox("recent", "Add to recent files", true);
+        assert_eq!(dialog.option("read-only"), Some(false));
+        assert_eq!(dialog.option("recent"), Some(true));
+        assert_eq!(dialog.option("Open as read-only"), None);
+        assert_eq!(dialog.option("missing"), None);
+        assert_eq!(FileDialog::new().options(), []);
+    }
+
+    #[test]
+    fn adding_an_option_again_replaces_it() {
+        let dialog = FileDialog::new()
+            .option_checkbox("read-only", "Read-only", false)
+            .option_checkbox("recent", "Add to recent files", true)
+            .option_checkbox("read-only", "Open as read-only", true);
+        let ids: Vec<_> = dialog.options().iter().map(|option| (option.id.as_str(), option.label.as_str())).collect();
+        assert_eq!(ids, [("read-only", "Open as read-only"), ("recent", "Add to recent files")]);
+        assert_eq!(dialog.option("read-only"), Some(true));
+    }
+
+    #[test]
+    fn extra_toggles_are_identified_by_their_label() {
+        let dialog = FileDialog::new().extra_toggle("Keep layers", true);
+        assert_eq!(dialog.option("Keep layers"), Some(true));
+    }
+
+    #[test]
+    fn options_report_what_the_user_checked() {
+        let mut dialog = FileDialog::from_config(DialogConfig {
+            options: vec![OptionCheckbox { id: String::from("read-only"), label: String::from("Read-only"), value: false }],
+            ..DialogConfig::default()
+        });
+        assert_eq!(dialog.option("read-only"), Some(false));
+        /* What clicking the checkbox does */
+        dialog.config.options[0].value = true;
+        assert_eq!(dialog.option("read-only"), Some(true));
+        assert_eq!(dialog.clone().option("read-only"), Some(true));
+    }
+
+    /// A provider counting how many times it was queried.
+    fn counting_provider(queries: &Rc<std::cell::Cell<usize>>) -> Box<dyn PlacesProvider> {
+        let queries = Rc::clone(queries);
+        Box::new(move || {
+            queries.set(queries.get() + 1);
+            vec![Place::new("Work", "/work")]
+        })
+    }
+
+    #[test]
+    fn places_are_queried_when_the_dialog_opens() {
+        let queries = Rc::default();
+        let mut dialog = FileDialog::new().common_places(false).places_provider(counting_provider(&queries));
+        let now = Instant::now();
+        assert_eq!(queries.get(), 0);
+        dialog.update_places(now);
+        assert_eq!(queries.get(), 1);
+        dialog.update_places(now + Duration::from_secs(3600));
+        assert_eq!(queries.get(), 1);
+        assert_eq!(dialog.places.as_ref().unwrap().sections, [(PlaceSection::Places, vec![Place::new("Work", "/work")])]);
+
+        /* Opening the dialog again after it returned */
+        dialog.closed(&DialogResult::Cancelled);
+        dialog.update_places(now + Duration::from_secs(3601));
+        assert_eq!(queries.get(), 2);
+        dialog.update_places(now + Duration::from_secs(3602));
+        assert_eq!(queries.get(), 2);
+    }
+
+    #[test]
+    fn places_are_queried_when_asked_to() {
+        let queries = Rc::default();
+        let mut dialog = FileDialog::new().common_places(false).places_provider(counting_provider(&queries));
+        let now = Instant::now();
+        dialog.update_places(now);
+        dialog.refresh_places();
+        assert_eq!(queries.get(), 1);
+        dialog.update_places(now);
+        assert_eq!(queries.get(), 2);
+    }
+
+    #[test]
+    fn places_are_queried_at_the_refresh_interval() {
+        let queries = Rc::default();
+        let mut dialog = FileDialog::new()
+            .common_places(false)
+            .places_provider(counting_provider(&queries))
+            .places_refresh_interval(Duration::from_secs(5));
+        let now = Instant::now();
+        for (elapsed, expected) in [(0, 1), (4, 1), (5, 2), (9, 2), (10, 3), (30, 4), (31, 4)] {
+            dialog.update_places(now + Duration::from_secs(elapsed));
+            assert_eq!(queries.get(), expected, "after {} seconds", elapsed);
+        }
+    }
+
+    #[test]
+    fn places_that_cant_be_opened_are_queried_again() {
+        let queries = Rc::default();
+        let mut dialog = FileDialog::new().common_places(false).places_provider(counting_provider(&queries));
+        let now = Instant::now();
+        dialog.update_places(now);
+        dialog.navigate_to(NavTarget::Place(std::env::temp_dir()));
+        dialog.update_places(now);
+        assert_eq!(queries.get(), 1);
+        /* Like a drive that was unplugged */
+        dialog.navigate_to(NavTarget::Place(std::env::temp_dir().join("imfile-unplugged-drive")));
+        dialog.update_places(now);
+        assert_eq!(queries.get(), 2);
+        assert_eq!(dialog.current_dir(), std::env::temp_dir());
+    }
+
+    #[test]
+    fn devices_plugged_in_while_open_are_highlighted() {
+        let devices = Rc::new(RefCell::new(vec![PathBuf::from("/media/a")]));
+        let plugged = devices.clone();
+        let mut dialog = FileDialog::new()
+            .common_places(false)
+            .places_provider(Box::new(move || {
+                plugged.borrow().iter().map(|path| Place::new("", path).section(PlaceSection::Devices)).collect()
+            }))
+            .places_refresh_interval(Duration::from_secs(5));
+        let new_devices = |dialog: &FileDialog| -> Vec<PathBuf> {
+            dialog.places.as_ref().unwrap().new_devices.iter().map(|(path, _)| path.clone()).collect()
+        };
+        let now = Instant::now();
+        dialog.update_places(now);
+        assert_eq!(new_devices(&dialog), Vec::<PathBuf>::new());
+
+        devices.borrow_mut().push(PathBuf::from("/media/b"));
+        dialog.update_places(now + Duration::from_secs(5));
+        assert_eq!(new_devices(&dialog), [PathBuf::from("/media/b")]);
+        /* Refreshing by hand compares them too, and keeps the highlight of the devices still there */
+        devices.borrow_mut().push(PathBuf::from("/media/c"));
+        dialog.refresh_places();
+        dialog.update_places(now + Duration::from_secs(6));
+        assert_eq!(new_devices(&dialog), [PathBuf::from("/media/c"), PathBuf::from("/media/b")]);
+        devices.borrow_mut().retain(|path| *path != Path::new("/media/c"));
+        dialog.update_places(now + Duration::from_secs(15));
+        assert_eq!(new_devices(&dialog), Vec::<PathBuf>::new());
+
+        /* Devices found when the dialog opens again are not new */
+        devices.borrow_mut().push(PathBuf::from("/media/d"));
+        dialog.closed(&DialogResult::Cancelled);
+        dialog.update_places(now + Duration::from_secs(16));
+        assert_eq!(new_devices(&dialog), Vec::<PathBuf>::new());
+    }
+
+    #[test]
+    fn refreshing_the_dialog_queries_the_places_again() {
+        let queries = Rc::default();
+        let mut dialog = FileDialog::new().common_places(false).places_provider(counting_provider(&queries));
+        let now = Instant::now();
+        dialog.update_places(now);
+        dialog.invalidate();
+        assert!(dialog.dirty);
+        dialog.update_places(now);
+        assert_eq!(queries.get(), 2);
+    }
+
+    #[test]
+    fn layout_regions_never_overlap() {
+        let height = DialogConfig::default().window_size[1];
+        for flags in 0..32 {
+            let [path_bar, sidebar, controls, status_bar, footer] = [1, 2, 4, 8, 16].map(|bit| flags & bit != 0);
+            let mut dialog = FileDialog::new()
+                .show_path_bar(path_bar)
+                .show_sidebar(sidebar)
+                .show_controls(controls)
+                .show_status_bar(status_bar);
+            if footer {
+                dialog = dialog.footer(|_| {});
+            }
+            let top = if path_bar { PATH_BAR_HEIGHT } else { 0.0 };
+            /* The content height is relative to the bottom of the window, what it leaves is split between the controls row and the status bar */
+            assert!(dialog.content_height() <= 0.0);
+            assert!(dialog.controls_height() >= 0.0 && dialog.status_bar_height() >= 0.0);
+            let bottom = dialog.controls_height() + dialog.status_bar_height();
+            assert_eq!(-dialog.content_height(), bottom, "regions overlap with flags {:#b}", flags);
+            assert!(height - top - bottom > 0.0, "no room for the listing with flags {:#b}", flags);
+            assert_eq!(dialog.controls_height() > 0.0, controls);
+            assert_eq!(dialog.status_bar_height() > 0.0, status_bar);
+        }
+    }
+
+    /// Creates an empty directory for a test, holding empty files with the given names.
+    fn scratch_dir(test: &str, files: &[&str]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("imfile-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        for file in files {
+            fs::write(dir.join(file), "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn recent_files_are_accepted_like_listed_files() {
+        let dir = scratch_dir("recent-accept", &["notes.txt"]);
+        let file = dir.join("notes.txt");
+        let mut dialog = FileDialog::new();
+        dialog.choose_recent(&file);
+        assert_eq!(dialog.current_dir(), dir);
+        assert_eq!(dialog.highlighted.as_ref(), Some(&file));
+        assert!(dialog.pending_accept);
+        assert!(matches!(dialog.accept_action(), Some(AcceptAction::Return(chosen)) if chosen == file));
+
+        let mut dialog = FileDialog::new().multi_select();
+        dialog.choose_recent(&file);
+        assert!(matches!(dialog.accept_action(), Some(AcceptAction::ReturnMany(chosen)) if chosen == [file.clone()]));
+
+        /* The file is chosen in its directory, which has to be readable */
+        let mut dialog = FileDialog::new().start_directory(&dir);
+        dialog.choose_recent(&dir.join("missing").join("notes.txt"));
+        assert!(!dialog.pending_accept);
+        assert_eq!(dialog.highlighted, None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn recent_files_the_dialog_cant_accept_are_hidden() {
+        let (file, image, dir) = (Path::new("/notes.txt"), Path::new("/shot.png"), Path::new("/photos"));
+        let dialog = FileDialog::new();
+        assert!(dialog.accepts_recent(file, EntryKind::File));
+        assert!(!dialog.accepts_recent(file, EntryKind::BrokenSymlink));
+        assert!(!dialog.accepts_recent(file, EntryKind::Other));
+        assert!(FileDialog::new().select_broken_symlinks().accepts_recent(file, EntryKind::BrokenSymlink));
+        assert!(FileDialog::new().for_save().accepts_recent(file, EntryKind::BrokenSymlink));
+
+        let dialog = FileDialog::new().dir_only();
+        assert!(!dialog.accepts_recent(file, EntryKind::File));
+        assert!(dialog.accepts_recent(dir, EntryKind::Dir));
+        assert!(dialog.accepts_recent(dir, EntryKind::Symlink { to_dir: true }));
+
+        let dialog = FileDialog::new().filter(&["png"]);
+        assert!(!dialog.accepts_recent(file, EntryKind::File));
+        assert!(dialog.accepts_recent(image, EntryKind::File));
+
+        let mut dialog = FileDialog::new().add_filter("Images", &["png"]).add_filter("Text", &["txt"]);
+        assert!(!dialog.accepts_recent(file, EntryKind::File));
+        dialog.config.active_filter = 1;
+        assert!(dialog.accepts_recent(file, EntryKind::File));
+        assert!(!dialog.accepts_recent(image, EntryKind::File));
+    }
+
+    #[test]
+    fn recent_paths_are_read_when_the_dialog_opens() {
+        let dir = scratch_dir("recent-read", &["kept.txt", "removed.txt"]);
+        let memory = DialogMemory::new();
+        memory.push_recent(dir.join("kept.txt"));
+        memory.push_recent(dir.join("removed.txt"));
+        let mut dialog = FileDialog::new().common_places(false).memory(&memory, "test");
+        fs::remove_file(dir.join("removed.txt")).unwrap();
+        dialog.update_places(Instant::now());
+        let recent = &dialog.places.as_ref().unwrap().recent;
+        assert_eq!(recent, &[(dir.join("kept.txt"), EntryKind::File)]);
+        assert_eq!(memory.recent(), [dir.join("kept.txt")]);
+
+        /* Entering a directory records it */
+        dialog.navigate_to(NavTarget::Place(dir.clone()));
+        let recent = &dialog.places.as_ref().unwrap().recent;
+        assert_eq!(recent, &[(dir.clone(), EntryKind::Dir), (dir.join("kept.txt"), EntryKind::File)]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn revealed_files_are_selected_and_focused() {
+        let dir = scratch_dir("reveal", &["notes.txt"]);
+        let file = dir.join("notes.txt");
+        let mut dialog = FileDialog::new();
+        dialog.navigate_to(NavTarget::Place(dir.clone()));
+        dialog.reveal(file.clone());
+        assert_eq!(dialog.highlighted.as_ref(), Some(&file));
+        assert_eq!(dialog.pending_focus.as_ref(), Some(&file));
+
+        let mut dialog = FileDialog::new().multi_select().start_directory(&dir);
+        dialog.reveal(file.clone());
+        assert_eq!(dialog.selection, [file.as_path()]);
+
+        let mut dialog = FileDialog::new().for_save().start_directory(&dir);
+        dialog.reveal(file.clone());
+        assert_eq!(dialog.config().filename, "notes.txt");
+
+        /* Only files of the current directory can be selected */
+        let mut dialog = FileDialog::new().start_directory(std::env::temp_dir());
+        dialog.reveal(file);
+        assert_eq!(dialog.highlighted, None);
+        assert_eq!(dialog.pending_focus, None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn forced_decisions_are_made_through_the_buttons() {
+        let dialog = FileDialog::new();
+        assert!(dialog.cancels_on_escape());
+        assert!(!dialog.window_flags().contains(WindowFlags::NO_COLLAPSE));
+        let dialog = FileDialog::new().force_decision();
+        assert!(!dialog.cancels_on_escape());
+        assert!(dialog.window_flags().contains(WindowFlags::NO_COLLAPSE));
+    }
+
+    #[test]
+    fn close_buttons_are_opt_in() {
+        assert!(!FileDialog::new().has_close_button());
+        assert!(FileDialog::new().with_close_button().has_close_button());
+        assert!(!FileDialog::new().with_close_button().force_decision().has_close_button());
+    }
+
+    #[test]
+    fn collapsed_windows_stay_open_without_reading() {
+        let dir = scratch_dir("collapse", &["a.txt"]);
+        let mut dialog = FileDialog::new().start_directory(&dir).with_close_button();
+        assert!(!dialog.window_event(WindowEvent::of(false, true)));
+        assert!(dialog.collapsed);
+        with_context(|context| {
+            /* Collapsed: still open, and the directory isn't read */
+            assert_eq!(draw(context, &mut dialog), DialogResult::StillOpen);
+            assert_eq!(dialog.entries().len(), 0);
+            assert!(dialog.dirty);
+            /* Expanded again */
+            assert!(!dialog.collapsed);
+            assert_eq!(draw(context, &mut dialog), DialogResult::StillOpen);
+            assert_eq!(dialog.entries().len(), 1);
+        });
+        assert!(!dialog.window_event(WindowEvent::of(true, true)));
+        assert!(!dialog.collapsed);
+        /* Closing a collapsed window closes it too */
+        assert!(dialog.window_event(WindowEvent::of(false, false)));
+        assert!(dialog.window_event(WindowEvent::of(true, false)));
+        assert!(!dialog.collapsed);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn clones_keep_the_configuration_only() {
+        let dir = scratch_dir("clone", &["notes.txt"]);
+        let mut dialog = FileDialog::new().title("Import").start_directory(&dir).validator(|_| Ok(()));
+        dialog.navigate_to(NavTarget::Place(dir.clone()));
+        dialog.refresh_listing();
+        dialog.reveal(dir.join("notes.txt"));
+        dialog.search = String::from("notes");
+        dialog.selection.push(dir.join("notes.txt"));
+
+        let clone = dialog.clone();
+        assert_eq!(clone.config(), dialog.config());
+        assert_eq!(clone.current_dir(), dir);
+        assert!(clone.validator.is_some());
+        assert!(clone.dirty && clone.listing.entries.is_empty());
+        assert!(clone.selection.is_empty() && clone.search.is_empty());
+        assert_eq!(clone.highlighted, None);
+        assert_eq!(clone.pending_focus, None);
+        assert_eq!(clone.navigation_log().last(), None);
+        assert!(dialog.navigation_log().last().is_some());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn clones_navigate_independently() {
+        let dir = scratch_dir("clone-navigation", &[]);
+        fs::create_dir(dir.join("inner")).unwrap();
+        let dialog = FileDialog::new().start_directory(&dir);
+        let mut clone = dialog.clone();
+        clone.navigate_to(NavTarget::Entry(dir.join("inner")));
+        assert_eq!(clone.current_dir(), dir.join("inner"));
+        assert_eq!(dialog.current_dir(), dir);
+        assert_eq!(dialog.navigation_log().last(), None);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn validators_check_the_resolved_paths() {
+        let dir = scratch_dir("resolve-validate", &["target.txt"]);
+        let (target, link) = (dir.join("target.txt"), dir.join("link.txt"));
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        let checked = Rc::new(RefCell::new(Vec::new()));
+        let seen = checked.clone();
+        let mut dialog = FileDialog::new().resolve_symlinks(true).validator(move |path| {
+            seen.borrow_mut().push(path.to_path_buf());
+            Err(String::from("refused"))
+        });
+        assert_eq!(dialog.run_accept(DialogResult::Selected(link.clone()), 0), None);
+        assert_eq!(dialog.validation_error.as_deref(), Some("refused"));
+
+        let mut dialog = dialog.validator(|_| Ok(()));
+        let accepted = dialog.run_accept(DialogResult::SelectedMany(vec![link.clone(), target.clone()]), 0);
+        let target = fs::canonicalize(&target).unwrap();
+        assert_eq!(accepted, Some(DialogResult::SelectedMany(vec![target.clone(), dir.join("target.txt")])));
+        assert_eq!(dialog.validation_error, None);
+        assert_eq!(*checked.borrow(), [target]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn activated_entries_are_read_again() {
+        let dir = scratch_dir("activate", &["swapped"]);
+        let swapped = dir.join("swapped");
+        let mut dialog = FileDialog::new().start_directory(&dir);
+        dialog.refresh_listing();
+        assert_eq!(dialog.listing.entries[0].kind, EntryKind::File);
+
+        /* A file replaced by a directory is entered, not returned */
+        fs::remove_file(&swapped).unwrap();
+        fs::create_dir(&swapped).unwrap();
+        assert!(matches!(dialog.activate(&swapped), Some(AcceptAction::Enter(entered)) if entered == swapped));
+        assert_eq!(dialog.listing.entries[0].kind, EntryKind::Dir);
+
+        /* And the other way around */
+        fs::remove_dir(&swapped).unwrap();
+        fs::write(&swapped, "").unwrap();
+        assert!(matches!(dialog.activate(&swapped), Some(AcceptAction::Return(chosen)) if chosen == swapped));
+        assert_eq!(dialog.listing.entries[0].kind, EntryKind::File);
+
+        /* Removed entries do nothing, and the listing is read again */
+        fs::remove_file(&swapped).unwrap();
+        dialog.dirty = false;
+        assert!(dialog.activate(&swapped).is_none());
+        assert!(dialog.dirty);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn selections_are_checked_again_when_accepting() {
+        let dir = scratch_dir("recheck", &["kept.txt", "swapped", "removed.txt"]);
+        let mut dialog = FileDialog::new().multi_select().start_directory(&dir);
+        dialog.selection = ["kept.txt", "swapped", "removed.txt"].map(|name| dir.join(name)).to_vec();
+        fs::remove_file(dir.join("swapped")).unwrap();
+        fs::create_dir(dir.join("swapped")).unwrap();
+        fs::remove_file(dir.join("removed.txt")).unwrap();
+        dialog.dirty = false;
+        dialog.recheck_selection();
+        assert_eq!(dialog.selection, [dir.join("kept.txt")]);
+        assert!(dialog.dirty);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn dialogs_are_drawn_once_per_frame() {
+        let mut dialog = FileDialog::new();
+        assert!(dialog.first_draw_in(1));
+        assert!(!dialog.first_draw_in(1));
+        assert!(!dialog.first_draw_in(1));
+        assert!(dialog.first_draw_in(2));
+        /* Clones are drawn on their own */
+        assert!(dialog.clone().first_draw_in(2));
+    }
+
+    #[test]
+    fn entries_are_renamed() {
+        let dir = scratch_dir("rename", &["report.txt", "notes.txt"]);
+        assert_eq!(rename_entry(&dir.join("report.txt"), "Report.txt"), Ok(dir.join("Report.txt")));
+        assert!(dir.join("Report.txt").exists());
+        assert_eq!(rename_entry(&dir.join("Report.txt"), "Report.txt"), Ok(dir.join("Report.txt")));
+        assert_eq!(rename_entry(&dir.join("Report.txt"), "notes.txt"), Err(String::from("\"notes.txt\" already exists")));
+        assert!(rename_entry(&dir.join("Report.txt"), "").is_err());
+        assert!(rename_entry(&dir.join("Report.txt"), "..").is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn case_only_renames_keep_other_entries() {
+        /* Only entries that are the rename itself are overwritten, not other entries whose names differ by case */
+        let dir = scratch_dir("rename-case", &["notes.txt"]);
+        assert!(same_entry(&dir.join("notes.txt"), &dir.join(".").join("notes.txt")));
+        if dir.join("NOTES.txt").exists() {
+            /* Case-insensitive filesystem: both names are the same entry */
+            assert!(same_entry(&dir.join("notes.txt"), &dir.join("NOTES.txt")));
+            assert_eq!(rename_entry(&dir.join("notes.txt"), "NOTES.txt"), Ok(dir.join("NOTES.txt")));
+        } else {
+            fs::write(dir.join("NOTES.txt"), "").unwrap();
+            assert!(!same_entry(&dir.join("notes.txt"), &dir.join("NOTES.txt")));
+            assert!(rename_entry(&dir.join("notes.txt"), "NOTES.txt").is_err());
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn button_closures_are_shared_between_clones() {
+        let dialog = FileDialog::new()
+            .accept_button_fn(|ui, state| ui.button(&state.label))
+            .cancel_button_fn(|ui, label| ui.button(label));
+        let clone = dialog.clone();
+        assert!(Rc::ptr_eq(dialog.accept_button_fn.as_ref().unwrap(), clone.accept_button_fn.as_ref().unwrap()));
+        assert!(Rc::ptr_eq(dialog.cancel_button_fn.as_ref().unwrap(), clone.cancel_button_fn.as_ref().unwrap()));
+        assert!(FileDialog::new().cancel_button_fn.is_none());
+    }
+
+    #[test]
+    fn active_filters_are_reported_after_accepting() {
+        let mut dialog = FileDialog::new()
+            .for_save()
+            .default_filename("scene")
+            .add_filter("Images", &["png", "jpg"])
+            .add_filter("Documents", &["pdf"]);
+        assert_eq!(dialog.active_filter(), Some((0, "Images")));
+        assert_eq!(dialog.save_path().file_name().unwrap(), "scene.png");
+
+        /* What choosing another filter in the combo box does, before the accept button of the same frame */
+        dialog.select_filter(1);
+        assert!(matches!(dialog.accept_action(), Some(AcceptAction::Return(path)) if path.file_name().unwrap() == "scene.pdf"));
+        assert_eq!(dialog.active_filter(), Some((1, "Documents")));
+
+        let dialog = FileDialog::new();
+        assert_eq!(dialog.active_filter(), None);
+    }
+
+    #[test]
+    fn navigations_are_logged() {
+        let dir = scratch_dir("navigation-log", &["notes.txt"]);
+        fs::create_dir_all(dir.join("inner").join("deeper")).unwrap();
+        let mut dialog = FileDialog::new().start_directory(&dir);
+        let steps = [
+            (NavTarget::Entry(dir.join("inner")), dir.clone(), dir.join("inner")),
+            (NavTarget::PathBar(dir.join("inner").join("deeper")), dir.join("inner"), dir.join("inner").join("deeper")),
+            (NavTarget::Parent, dir.join("inner").join("deeper"), dir.join("inner")),
+            (NavTarget::Typed(dir.clone()), dir.join("inner"), dir.clone()),
+            (NavTarget::Place(dir.join("inner")), dir.clone(), dir.join("inner")),
+        ];
+        for (target, from, to) in steps {
+            dialog.navigate_to(target.clone());
+            let event = dialog.navigation_log().last().unwrap();
+            assert_eq!(event, &NavEvent { target, from, to: to.clone(), error: None });
+            assert_eq!(dialog.current_dir(), to);
+        }
+
+        /* Failures are logged too, and leave the dialog where it was */
+        for target in [NavTarget::Entry(dir.join("missing")), NavTarget::Typed(dir.join("notes.txt"))] {
+            dialog.navigate_to(target.clone());
+            let event = dialog.navigation_log().last().unwrap();
+            assert_eq!((&event.target, event.from.as_path()), (&target, dir.join("inner").as_path()));
+            assert!(event.error.is_some());
+            assert_eq!(dialog.current_dir(), dir.join("inner"));
+        }
+        assert_eq!(dialog.navigation_log().events().len(), 7);
+
+        /* Only the most recent navigations are kept */
+        for _ in 0..NAVIGATION_LOG_CAPACITY {
+            dialog.navigate_to(NavTarget::Parent);
+        }
+        assert_eq!(dialog.navigation_log().events().len(), NAVIGATION_LOG_CAPACITY);
+        assert!(dialog.navigation_log().events().all(|event| event.target == NavTarget::Parent));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn remembered_directories_take_precedence() {
+        let dir = scratch_dir("remembered-start", &["notes.txt"]);
+        fs::create_dir(dir.join("inner")).unwrap();
+        let memory = DialogMemory::new();
+
+        /* Open, navigate, accept */
+        let mut dialog = FileDialog::new().memory(&memory, "test").start_directory(&dir);
+        dialog.start();
+        assert_eq!(dialog.current_dir(), dir);
+        dialog.navigate_to(NavTarget::Entry(dir.join("inner")));
+        dialog.closed(&DialogResult::Selected(dir.join("inner").join("file.txt")));
+
+        /* Reopening lands in the same directory, whichever builder is called first */
+        let dialog = FileDialog::new().memory(&memory, "test").start_directory(&dir);
+        assert_eq!(dialog.current_dir(), dir.join("inner"));
+        let dialog = FileDialog::new().start_directory(&dir).memory(&memory, "test");
+        assert_eq!(dialog.current_dir(), dir.join("inner"));
+        let dialog = FileDialog::new().start_directory(&dir).memory(&memory, "other");
+        assert_eq!(dialog.current_dir(), dir);
+
+        /* The memory is read again when the dialog is first drawn */
+        let mut early = FileDialog::new().memory(&memory, "early").start_directory(&dir);
+        let mut other = FileDialog::new().memory(&memory, "early").start_directory(&dir);
+        other.navigate_to(NavTarget::Entry(dir.join("inner")));
+        other.closed(&DialogResult::Cancelled);
+        early.start();
+        assert_eq!(early.current_dir(), dir.join("inner"));
+
+        /* But not once the dialog navigated */
+        let mut navigated = FileDialog::new().memory(&memory, "test");
+        navigated.navigate_to(NavTarget::Place(dir.clone()));
+        navigated.start();
+        assert_eq!(navigated.current_dir(), dir);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn custom_buttons_accept_and_cancel_like_the_built_in_ones() {
+        let dir = scratch_dir("custom-buttons", &[]);
+        let states = Rc::new(RefCell::new(Vec::new()));
+        let script = Rc::new(RefCell::new(vec![false, true]));
+        let mut dialog = FileDialog::new().for_save().default_filename("scene.txt").start_directory(&dir).accept_button_fn({
+            let (states, script) = (states.clone(), script.clone());
+            move |_, state| {
+                states.borrow_mut().push(state.clone());
+                script.borrow_mut().remove(0)
+            }
+        });
+        with_context(|context| {
+            assert_eq!(draw(context, &mut dialog), DialogResult::StillOpen);
+            assert_eq!(draw(context, &mut dialog), DialogResult::Selected(dir.join("scene.txt")));
+        });
+        let expected = AcceptState { label: String::from("Open"), enabled: true, tooltip: None };
+        assert_eq!(*states.borrow(), [expected.clone(), expected]);
+
+        /* The validator still applies */
+        script.borrow_mut().push(true);
+        let mut dialog = dialog.validator(|_| Err(String::from("No")));
+        with_context(|context| assert_eq!(draw(context, &mut dialog), DialogResult::StillOpen));
+        assert_eq!(dialog.validation_error.as_deref(), Some("No"));
+
+        let mut dialog = FileDialog::new().start_directory(&dir).cancel_button_fn(|_, label| label == "Cancel");
+        with_context(|context| assert_eq!(draw(context, &mut dialog), DialogResult::Cancelled));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn accept_labels_count_the_selection() {
+        let dir = scratch_dir("accept-label", &["a.txt", "b.txt", "c.txt"]);
+        fs::create_dir(dir.join("inner")).unwrap();
+        let mut dialog = FileDialog::new().multi_select().start_directory(&dir);
+        let state = dialog.accept_state(dialog.accept_action().as_ref());
+        assert_eq!(state, AcceptState { label: String::from("Open"), enabled: false, tooltip: Some(String::from("Select a file first")) });
+        dialog.selection = vec![dir.join("a.txt"), dir.join("b.txt"), dir.join("c.txt")];
+        assert_eq!(dialog.accept_state(dialog.accept_action().as_ref()).label, "Open (3)");
+
+        let mut dialog = FileDialog::new().accept_dirs_too().start_directory(&dir);
+        dialog.highlighted = Some(dir.join("inner"));
+        assert_eq!(dialog.accept_state(dialog.accept_action().as_ref()).label, "Select this folder");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn invalid_regex_searches_keep_the_previous_filtering() {
+        let mut dialog = FileDialog::new().regex_search(true);
+        dialog.listing.entries = ["shot1.png", "shot22.png", "notes.txt"].map(|name| Entry::fake(name, EntryKind::File)).into();
+        let search = |dialog: &mut FileDialog, query: &str| {
+            dialog.search = String::from(query);
+            dialog.update_search();
+            dialog.apply_filters();
+            dialog.listing.visible.clone()
+        };
+        assert_eq!(search(&mut dialog, r"shot\d\."), [0]);
+        assert_eq!(dialog.listing.spans, [vec![Range { start: 0, end: 6 }]]);
+        assert_eq!(search(&mut dialog, r"shot("), [0]);
+        assert!(dialog.search_error.is_some());
+        assert_eq!(search(&mut dialog, r"\.png$"), [0, 1]);
+        assert_eq!(dialog.search_error, None);
+
+        /* Without the regex toggle, the query is looked for as is */
+        dialog.config.regex_search = false;
+        assert_eq!(search(&mut dialog, r"shot("), []);
+        assert_eq!(dialog.search_error, None);
+    }
+
+    #[test]
+    fn searches_left_behind_are_remembered() {
+        let dir = scratch_dir("search-history", &[]);
+        fs::create_dir(dir.join("a")).unwrap();
+        let mut dialog = FileDialog::new().start_directory(&dir);
+        dialog.search = String::from("notes");
+        dialog.navigate_to(NavTarget::Entry(dir.join("a")));
+        assert_eq!(dialog.search, "");
+        assert_eq!(dialog.search_history.queries(), ["notes"]);
+
+        /* Leaving an empty search box records nothing */
+        dialog.navigate_to(NavTarget::Parent);
+        assert_eq!(dialog.search_history.queries(), ["notes"]);
+
+        dialog.search = String::from("Notes");
+        dialog.closed(&DialogResult::Cancelled);
+        assert_eq!(dialog.search_history.queries(), ["Notes"]);
+        assert!(dialog.clone().search_history.queries().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn escape_clears_then_hides_the_search_box() {
+        let mut dialog = FileDialog::new();
+        dialog.listing.entries = ["notes.txt", "report.txt"].iter().map(|name| Entry::fake(name, EntryKind::File)).collect();
+        dialog.apply_filters();
+        let mut query = String::from("notes");
+        dialog.search_box.focused = true;
+        dialog.search_key(SearchKey::Escape, &mut query);
+        assert_eq!(query, "");
+        assert!(!dialog.search_box.hidden);
+        assert!(dialog.search_box.focus);
+
+        dialog.search_box.focus = false;
+        dialog.search_key(SearchKey::Escape, &mut query);
+        assert!(dialog.search_box.hidden);
+        assert!(!dialog.search_box.focused);
+        assert_eq!(dialog.pending_focus, Some(dialog.listing.entries[0].path.clone()));
+
+        dialog.search_key(SearchKey::Find, &mut query);
+        assert!(!dialog.search_box.hidden);
+        assert!(dialog.search_box.focus);
+    }
+
+    #[test]
+    fn escape_in_the_search_box_never_closes_the_dialog() {
+        let dir = scratch_dir("search-keys", &["notes.txt", "report.txt"]);
+        let mut dialog = FileDialog::new().start_directory(&dir);
+        let press = |context: &mut imgui::Context, dialog: &mut FileDialog, keys: &[Key]| {
+            for &key in keys {
+                context.io_mut().add_key_event(key, true);
+            }
+            let result = draw(context, dialog);
+            for &key in keys {
+                context.io_mut().add_key_event(key, false);
+            }
+            /* A frame without the dialog sees the keys released, so they can be pressed again */
+            context.new_frame();
+            context.render();
+            result
+        };
+        with_context(|context| {
+            assert_eq!(draw(context, &mut dialog), DialogResult::StillOpen);
+            dialog.search_box.hidden = true;
+            assert_eq!(press(context, &mut dialog, &[Key::ModCtrl, Key::F]), DialogResult::StillOpen);
+            assert!(!dialog.search_box.hidden);
+
+            dialog.search = String::from("notes");
+            dialog.update_search();
+            dialog.apply_filters();
+            assert_eq!(dialog.listing.visible.len(), 1);
+            /* Without a running ImGui, nothing ever has the focus, so the search box is told it had it */
+            dialog.search_box.focused = true;
+            assert_eq!(press(context, &mut dialog, &[Key::Escape]), DialogResult::StillOpen);
+            assert_eq!(dialog.search, "");
+            assert_eq!(dialog.listing.visible.len(), 2);
+
+            dialog.search_box.focused = true;
+            assert_eq!(press(context, &mut dialog, &[Key::Escape]), DialogResult::StillOpen);
+            assert!(dialog.search_box.hidden);
+
+            /* Back in the listing, Escape cancels */
+            assert_eq!(press(context, &mut dialog, &[Key::Escape]), DialogResult::Cancelled);
+        });
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn filenames_follow_the_selected_filter() {
+        let mut dialog = FileDialog::new()
+            .for_save()
+            .default_filename("render.png")
+            .add_filter("PNG", &["png"])
+            .add_filter("JPEG", &["jpg"]);
+        dialog.select_filter(1);
+        assert_eq!(dialog.config.filename, "render.jpg");
+        dialog.select_filter(0);
+        assert_eq!(dialog.config.filename, "render.png");
+
+        let mut dialog = FileDialog::new()
+            .default_filename("render.png")
+            .add_filter("PNG", &["png"])
+            .add_filter("JPEG", &["jpg"]);
+        dialog.select_filter(1);
+        assert_eq!(dialog.config.filename, "render.png");
+    }
+
+    #[test]
+    fn renames_can_be_undone_for_a_while() {
+        let dir = scratch_dir("undo-rename", &["draft.txt"]);
+        let mut dialog = FileDialog::new().start_directory(&dir);
+        let (from, to) = (dir.join("draft.txt"), dir.join("final.txt"));
+        assert_eq!(rename_entry(&from, "final.txt").unwrap(), to);
+        dialog.offer_undo(Undo::Rename { from: from.clone(), to: to.clone() });
+        dialog.selection = vec![to.clone()];
+        dialog.tick_undo(Duration::from_secs(4));
+        dialog.undo_last();
+        assert!(from.exists() && !to.exists());
+        assert_eq!(dialog.selection, [from.as_path()]);
+        assert_eq!(dialog.highlighted.as_ref(), Some(&from));
+        assert!(dialog.undo.is_none());
+
+        /* The renamed entry went away meanwhile */
+        dialog.offer_undo(Undo::Rename { from: dir.join("gone.txt"), to: dir.join("moved.txt") });
+        dialog.undo_last();
+        assert_eq!(dialog.undo.as_ref().and_then(|toast| toast.error.as_deref()), Some("\"moved.txt\" no longer exists"));
+
+        /* Something took its old place meanwhile */
+        fs::write(dir.join("other.txt"), "").unwrap();
+        dialog.offer_undo(Undo::Rename { from: dir.join("other.txt"), to: from.clone() });
+        dialog.undo_last();
+        assert!(dialog.undo.as_ref().is_some_and(|toast| toast.error.is_some()));
+        assert!(from.exists());
+
+        /* The toast goes away after a while, whatever the frame rate */
+        dialog.offer_undo(Undo::Rename { from: to.clone(), to: from.clone() });
+        for _ in 0..299 {
+            dialog.tick_undo(Duration::from_secs_f32(1.0 / 60.0));
+        }
+        assert!(dialog.undo.is_some());
+        dialog.tick_undo(Duration::from_millis(100));
+        assert!(dialog.undo.is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn shortcuts_are_described_when_enabled() {
+        for shortcut in Shortcut::ALL {
+            assert!(!shortcut.keys().is_empty() && !shortcut.description().is_empty(), "{:?}", shortcut);
+        }
+        let keys = |dialog: &FileDialog| -> Vec<String> {
+            dialog.shortcut_descriptions().into_iter().map(|(keys, _)| keys).collect()
+        };
+        assert_eq!(keys(&FileDialog::new()), ["Enter", "Ctrl+L", "Ctrl+F", "Escape", "F1"]);
+        assert_eq!(
+            keys(&FileDialog::new().allow_file_operations()),
+            ["Enter", "Ctrl+L", "Ctrl+F", "F2", "Delete", "Escape", "F1"]
+        );
+        assert_eq!(keys(&FileDialog::new().force_decision().show_path_bar(false)), ["Enter", "Ctrl+F", "F1"]);
+        assert!(FileDialog::new().shortcut_descriptions().contains(&(String::from("Ctrl+L"), String::from("Type a path"))));
+    }
+
+    #[test]
+    fn idle_frames_reuse_the_listing() {
+        let dir = scratch_dir("draw-stats", &["a.txt", "b.txt", "c.txt"]);
+        fs::create_dir(dir.join("inner")).unwrap();
+        let mut dialog = FileDialog::new().start_directory(&dir);
+        with_context(|context| {
+            draw(context, &mut dialog);
+            assert!(dialog.draw_stats().rebuilds > 0);
+            dialog.navigate_to(NavTarget::Entry(dir.join("inner")));
+            dialog.navigate_to(NavTarget::Parent);
+            draw(context, &mut dialog);
+            assert!(dialog.draw_stats().rebuilds > 0);
+            assert_eq!(dialog.draw_stats().cache_hits, 0);
+            for _ in 0..10 {
+                draw(context, &mut dialog);
+                assert_eq!(dialog.draw_stats(), DrawStats { entries_drawn: 4, rebuilds: 0, cache_hits: 4 });
+            }
+        });
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn entries_are_exposed_as_displayed() {
+        let dir = scratch_dir("entries", &["b.txt", "a.txt", ".hidden", "c.txt"]);
+        let mut dialog = FileDialog::new().start_directory(&dir).multi_select();
+        with_context(|context| {
+            draw(context, &mut dialog);
+        });
+        let names: Vec<&str> = dialog.entries().map(Entry::name).collect();
+        assert_eq!(names, ["a.txt", "b.txt", "c.txt"]);
+        assert!(dialog.selection_indices().is_empty());
+
+        dialog.selection = vec![dir.join("c.txt"), dir.join("a.txt")];
+        assert_eq!(dialog.selection_indices(), [0, 2]);
+        dialog.search = String::from("c");
+        dialog.update_search();
+        dialog.apply_filters();
+        assert_eq!(dialog.entries().map(Entry::path).collect::<Vec<_>>(), [dir.join("c.txt")]);
+        assert_eq!(dialog.selection_indices(), [0]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn chosen_files_are_checked_for_access() {
+        let dir = scratch_dir("access", &["notes.txt"]);
+        assert_eq!(check_access(&dir.join("notes.txt"), DialogMode::Open), Ok(()));
+        assert_eq!(check_access(&dir, DialogMode::Open), Ok(()));
+        assert_eq!(check_access(&dir.join("notes.txt"), DialogMode::Save), Ok(()));
+        assert_eq!(check_access(&dir.join("new.txt"), DialogMode::Save), Ok(()));
+        assert!(!dir.join("new.txt").exists());
+        assert!(check_access(&dir.join("gone.txt"), DialogMode::Open).unwrap_err().starts_with("\"gone.txt\" cannot be read"));
+        assert!(check_access(&dir.join("gone").join("new.txt"), DialogMode::Save).is_err());
+
+        /* A failed check waits for the user instead of returning */
+        let mut dialog = FileDialog::new().start_directory(&dir).verify_readable().accept_button_fn(|_, _| true);
+        with_context(|context| {
+            draw(context, &mut dialog);
+            dialog.highlighted = Some(dir.join("gone.txt"));
+            assert_eq!(draw(context, &mut dialog), DialogResult::StillOpen);
+            let pending = dialog.pending_choice.as_ref().unwrap();
+            assert_eq!(pending.chosen, DialogResult::Selected(dir.join("gone.txt")));
+            assert!(matches!(pending.question, AcceptQuestion::Access(_)));
+
+            assert_eq!(dialog.answer_accept(false), None);
+            dialog.highlighted = Some(dir.join("notes.txt"));
+            assert_eq!(draw(context, &mut dialog), DialogResult::Selected(dir.join("notes.txt")));
+        });
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn questions_resume_the_accept_stages() {
+        let dir = scratch_dir("accept-stages", &["taken.txt"]);
+        let taken = dir.join("taken.txt");
+        let config = DialogConfig { mode: DialogMode::Save, ..DialogConfig::default() };
+        let mut dialog = FileDialog::from_config(config).confirm_overwrite(true).verify_readable();
+        let validated = Rc::new(RefCell::new(0));
+        let count = validated.clone();
+        dialog = dialog.validator(move |_| {
+            *count.borrow_mut() += 1;
+            Ok(())
+        });
+
+        /* Validated before being asked about, and not again once resumed */
+        assert_eq!(dialog.run_accept(DialogResult::Selected(taken.clone()), 0), None);
+        let pending = dialog.pending_choice.as_ref().unwrap();
+        assert_eq!(pending.question, AcceptQuestion::Overwrite(taken.clone()));
+        assert_eq!(ACCEPT_STAGES[pending.next_stage], AcceptStage::CheckAccess);
+        assert_eq!(dialog.answer_accept(true), Some(DialogResult::Selected(taken.clone())));
+        assert_eq!(*validated.borrow(), 1);
+        assert!(dialog.pending_choice.is_none());
+
+        /* Answering no drops the choice */
+        assert_eq!(dialog.run_accept(DialogResult::Selected(taken.clone()), 0), None);
+        assert_eq!(dialog.answer_accept(false), None);
+        assert!(dialog.pending_choice.is_none());
+        assert_eq!(dialog.answer_accept(true), None);
+
+        /* A second question comes after the first one */
+        let unwritable = dir.join("missing").join("new.txt");
+        assert_eq!(dialog.run_accept(DialogResult::Selected(unwritable.clone()), 0), None);
+        assert!(matches!(dialog.pending_choice.as_ref().unwrap().question, AcceptQuestion::Access(_)));
+        assert_eq!(dialog.answer_accept(true), Some(DialogResult::Selected(unwritable)));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn state_is_restored_where_it_was_saved() {
+        let dir = scratch_dir("state", &["a.txt", "b.txt"]);
+        fs::create_dir(dir.join("inner")).unwrap();
+        let mut dialog = FileDialog::new().start_directory(&dir).multi_select();
+        dialog.navigate_to(NavTarget::Entry(dir.join("inner")));
+        dialog.navigate_to(NavTarget::Parent);
+        dialog.selection = vec![dir.join("b.txt"), dir.join("a.txt")];
+        dialog.search = String::from("txt");
+        dialog.preview_width = 250.0;
+        let state = dialog.save_state_full();
+
+        let mut restored = FileDialog::new().multi_select();
+        restored.apply_state(state.clone());
+        assert_eq!(restored.save_state_full(), state);
+        assert!(restored.started);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stale_states_fall_back_to_what_exists() {
+        let dir = scratch_dir("stale-state", &["kept.txt"]);
+        fs::create_dir(dir.join("gone")).unwrap();
+        let state = DialogState {
+            current_dir: dir.join("gone"),
+            selection: vec![dir.join("gone").join("file.txt"), dir.join("kept.txt")],
+            highlighted: Some(dir.join("gone").join("file.txt")),
+            search: String::from("kept"),
+            search_history: vec![String::from("kept"), String::new(), String::from("old")],
+            preview_width: f32::NAN,
+        };
+        /* Deleted between saving and restoring */
+        fs::remove_dir(dir.join("gone")).unwrap();
+        let mut dialog = FileDialog::new().multi_select();
+        dialog.apply_state(state);
+        assert_eq!(dialog.current_dir(), dir);
+        assert_eq!(dialog.selection, [dir.join("kept.txt")]);
+        assert_eq!(dialog.highlighted, None);
+        assert_eq!(dialog.search_history.queries(), ["kept", "old"]);
+        assert_eq!(dialog.preview_width, PREVIEW_WIDTH);
+        with_context(|context| {
+            draw(context, &mut dialog);
+            assert_eq!(dialog.entries().map(Entry::path).collect::<Vec<_>>(), [dir.join("kept.txt")]);
+        });
+
+        /* Nothing left to list keeps the dialog where it is */
+        let before = dialog.current_dir().to_path_buf();
+        dialog.apply_state(DialogState { current_dir: PathBuf::from("imfile-nowhere"), ..DialogState::default() });
+        assert_eq!(dialog.current_dir(), before);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
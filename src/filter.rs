@@ -0,0 +1,264 @@
+use std::path::Path;
+
+/// Turns an extension given by the user into the form it is stored in: lowercase, without the leading dot.
+pub(crate) fn normalize_extension(extension: &str) -> String {
+    extension.trim_start_matches('.').to_lowercase()
+}
+
+/// Returns whether the file at `path` has one of the (normalized) `extensions`.
+/// An empty list matches every file.
+pub(crate) fn matches_extensions(path: &Path, extensions: &[String]) -> bool {
+    if extensions.is_empty() {
+        return true;
+    }
+    has_extension(path, extensions)
+}
+
+/// Returns whether the file at `path` has one of the `extensions` or its name matches one of the `globs`.
+/// Empty lists match every file.
+pub(crate) fn matches_patterns(path: &Path, extensions: &[String], globs: &[String]) -> bool {
+    if extensions.is_empty() && globs.is_empty() {
+        return true;
+    }
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    has_extension(path, extensions) || globs.iter().any(|glob| glob_matches(glob, &name))
+}
+
+fn has_extension(path: &Path, extensions: &[String]) -> bool {
+    match path.extension() {
+        Some(extension) => {
+            let extension = extension.to_string_lossy().to_lowercase();
+            extensions.contains(&extension)
+        }
+        None => false,
+    }
+}
+
+/// A named group of extensions, selectable from the filter combo box of the dialog.
+/// See [`FileDialog::add_filter()`](crate::FileDialog::add_filter).
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FileFilter {
+    /// The name displayed in the combo box, eg. "Images".
+    pub name: String,
+    /// The extensions of the files listed, lowercase and without the dot. Empty lists every file.
+    pub extensions: Vec<String>,
+}
+
+impl FileFilter {
+    /// Creates a new filter. Extensions may be given with or without the leading dot.
+    pub fn new<S: Into<String>>(name: S, extensions: &[&str]) -> Self {
+        Self {
+            name: name.into(),
+            extensions: extensions.iter().map(|extension| normalize_extension(extension)).collect(),
+        }
+    }
+
+    /// Returns the text displayed in the combo box, eg. `"Images (*.png, *.jpg)"`.
+    pub fn label(&self) -> String {
+        if self.extensions.is_empty() {
+            return format!("{} (*.*)", self.name);
+        }
+        let patterns: Vec<_> = self.extensions.iter().map(|extension| format!("*.{}", extension)).collect();
+        format!("{} ({})", self.name, patterns.join(", "))
+    }
+
+    /// Returns whether the file at `path` passes the filter.
+    #[inline]
+    pub(crate) fn matches(&self, path: &Path) -> bool {
+        matches_extensions(path, &self.extensions)
+    }
+}
+
+/// Returns `filename` with its extension replaced by the first extension of the filter `to`, for a save
+/// dialog whose filter was changed to `to`.
+///
+/// Only an extension one of the `filters` lists is replaced, so that a deliberately unusual one is kept, like
+/// the rest of the name: only the last extension of `archive.tar.gz` is. Returns `None` when the name stays as it
+/// is: it has no extension, `to` already accepts it or lists every file.
+pub(crate) fn swap_extension(filename: &str, filters: &[FileFilter], to: &FileFilter) -> Option<String> {
+    let (stem, extension) = filename.rsplit_once('.')?;
+    /* A leading dot starts a hidden name, not an extension */
+    if stem.is_empty() {
+        return None;
+    }
+    let extension = extension.to_lowercase();
+    let known = filters.iter().any(|filter| filter.extensions.contains(&extension));
+    if !known || to.extensions.contains(&extension) {
+        return None;
+    }
+    to.extensions.first().map(|replacement| format!("{}.{}", stem, replacement))
+}
+
+/// Returns whether `name` matches the glob `pattern`.
+///
+/// `*` matches any run of characters, `?` a single character and `[...]` one character of a class,
+/// like `[abc]`, `[a-z]` or `[!0-9]`. Case is ignored, like for extensions, and there is no escaping.
+pub(crate) fn glob_matches(pattern: &str, name: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().flat_map(char::to_lowercase).collect();
+    let name: Vec<char> = name.chars().flat_map(char::to_lowercase).collect();
+    let (mut p, mut n) = (0, 0);
+    /* The pattern right after the last '*', and the last character of the name it swallowed */
+    let mut star: Option<(usize, usize)> = None;
+    while n < name.len() {
+        if pattern.get(p) == Some(&'*') {
+            p += 1;
+            star = Some((p, n));
+            continue;
+        }
+        if let Some(len) = pattern.get(p).and_then(|_| match_token(&pattern[p..], name[n])) {
+            p += len;
+            n += 1;
+            continue;
+        }
+        match star {
+            Some((star_p, star_n)) => {
+                p = star_p;
+                n = star_n + 1;
+                star = Some((star_p, n));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Matches a single character against the token at the start of `pattern` (anything but `*`).
+/// Returns the length of the token if it matches.
+fn match_token(pattern: &[char], c: char) -> Option<usize> {
+    match pattern[0] {
+        '?' => Some(1),
+        '[' => match class_end(pattern) {
+            Some(end) => {
+                let (negated, class) = match pattern[1] {
+                    '!' | '^' => (true, &pattern[2..end]),
+                    _ => (false, &pattern[1..end]),
+                };
+                let mut matched = false;
+                let mut i = 0;
+                while i < class.len() {
+                    if i + 2 < class.len() && class[i + 1] == '-' {
+                        matched |= class[i] <= c && c <= class[i + 2];
+                        i += 3;
+                    } else {
+                        matched |= class[i] == c;
+                        i += 1;
+                    }
+                }
+                (matched != negated).then_some(end + 1)
+            }
+            /* An unclosed bracket is a plain character */
+            None => (c == '[').then_some(1),
+        },
+        literal => (literal == c).then_some(1),
+    }
+}
+
+/// Returns the index of the `]` closing the class opened at the start of `pattern`.
+/// A `]` right after the opening bracket (or its negation) is part of the class.
+fn class_end(pattern: &[char]) -> Option<usize> {
+    let mut i = 1;
+    if matches!(pattern.get(i), Some('!' | '^')) {
+        i += 1;
+    }
+    if pattern.get(i) == Some(&']') {
+        i += 1;
+    }
+    pattern.get(i..)?.iter().position(|&c| c == ']').map(|pos| i + pos)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn star_matches_any_run() {
+        assert!(glob_matches("config-*.toml", "config-.toml"));
+        assert!(glob_matches("config-*.toml", "config-release.toml"));
+        assert!(!glob_matches("config-*.toml", "config-release.toml.bak"));
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("*", "anything"));
+        assert!(glob_matches("a*b*c", "aXXbYYbc"));
+        assert!(!glob_matches("a*b*c", "aXXbYYb"));
+    }
+
+    #[test]
+    fn question_mark_matches_one_character() {
+        assert!(glob_matches("shot_??.png", "shot_01.png"));
+        assert!(!glob_matches("shot_??.png", "shot_1.png"));
+        assert!(!glob_matches("shot_??.png", "shot_100.png"));
+        assert!(glob_matches("?", "é"));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert!(glob_matches("[abc].txt", "b.txt"));
+        assert!(!glob_matches("[abc].txt", "d.txt"));
+        assert!(glob_matches("log[0-9]", "log7"));
+        assert!(!glob_matches("log[0-9]", "logx"));
+        assert!(glob_matches("log[!0-9]", "logx"));
+        assert!(glob_matches("log[^0-9]", "logx"));
+        assert!(!glob_matches("log[!0-9]", "log7"));
+        assert!(glob_matches("[]]", "]"));
+        assert!(glob_matches("[!]]", "a"));
+        assert!(glob_matches("[a-]", "-"));
+        /* An unclosed bracket is matched literally */
+        assert!(glob_matches("[abc", "[abc"));
+        assert!(!glob_matches("[abc", "a"));
+    }
+
+    #[test]
+    fn case_is_ignored() {
+        assert!(glob_matches("*.png", "SHOT.PNG"));
+        assert!(glob_matches("README*", "readme.md"));
+        assert!(glob_matches("[a-z]?", "Qx"));
+        assert!(glob_matches("Élan.*", "élan.txt"));
+    }
+
+    #[test]
+    fn multi_dot_extensions() {
+        assert!(glob_matches("*.tar.gz", "archive.tar.gz"));
+        assert!(glob_matches("*.tar.gz", "archive.v2.tar.gz"));
+        assert!(glob_matches("*.tar.gz", "Archive.TAR.GZ"));
+        assert!(!glob_matches("*.tar.gz", "archive.gz"));
+        assert!(!glob_matches("*.tar.gz", "archive.tar.gz.part"));
+    }
+
+    #[test]
+    fn patterns_match_the_file_name_only() {
+        let globs = [String::from("*.tar.gz")];
+        assert!(matches_patterns(Path::new("/backups/archive.tar.gz"), &[], &globs));
+        assert!(!matches_patterns(Path::new("/backups.tar.gz/archive"), &[], &globs));
+        assert!(matches_patterns(Path::new("/photos/shot.PNG"), &[String::from("png")], &globs));
+        assert!(matches_patterns(Path::new("/anything"), &[], &[]));
+    }
+
+    #[test]
+    fn extensions_follow_the_filter() {
+        let filters = [
+            FileFilter::new("PNG", &["png"]),
+            FileFilter::new("JPEG", &["jpg", "jpeg"]),
+            FileFilter::new("Archives", &["gz", "xz"]),
+            FileFilter::new("All files", &[]),
+        ];
+        let swap = |filename: &str, to: usize| swap_extension(filename, &filters, &filters[to]);
+        assert_eq!(swap("render.png", 1).as_deref(), Some("render.jpg"));
+        assert_eq!(swap("render.PNG", 1).as_deref(), Some("render.jpg"));
+        /* Switching back and forth */
+        assert_eq!(swap("render.jpg", 0).as_deref(), Some("render.png"));
+        assert_eq!(swap("render.png", 1).as_deref(), Some("render.jpg"));
+
+        /* Only the last extension changes */
+        assert_eq!(swap("render.final.png", 1).as_deref(), Some("render.final.jpg"));
+        assert_eq!(swap("backup.tar.gz", 0).as_deref(), Some("backup.tar.png"));
+        assert_eq!(swap("png.backup", 1), None);
+
+        /* Names kept as they are */
+        assert_eq!(swap("render", 1), None);
+        assert_eq!(swap(".png", 1), None);
+        assert_eq!(swap("render.", 1), None);
+        assert_eq!(swap("render.jpeg", 1), None);
+        assert_eq!(swap("render.webp", 1), None);
+        assert_eq!(swap("render.png", 3), None);
+    }
+}
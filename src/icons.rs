@@ -0,0 +1,67 @@
+use crate::entry::Entry;
+
+/// The glyphs drawn in front of the entries of the listing, replacing the `[dir]` and `[file]` prefixes.
+///
+/// The defaults are the code points of [Font Awesome](https://fontawesome.com) 6. Set the fields to the
+/// code points of another icon font to use it instead. The dialog only uses icons it is given with
+/// [`FileDialog::icons()`](crate::FileDialog::icons), which tells it that the application loaded their
+/// font into the imgui font atlas (usually merged into the default font). Glyphs missing from the font
+/// are drawn as question marks.
+/// ```no_run
+/// use imfile::{FileDialog, IconSet};
+///
+/// // The application merged Font Awesome into its font
+/// let dialog = FileDialog::new()
+///     .icons(IconSet { archive: String::from("\u{f187}"), ..Default::default() });
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct IconSet {
+    /// Directories.
+    pub folder: String,
+    /// Files of any other type.
+    pub file: String,
+    /// Images: `png`, `jpg`, `svg`, ...
+    pub image: String,
+    /// Audio files: `mp3`, `ogg`, `flac`, ...
+    pub audio: String,
+    /// Archives: `zip`, `tar`, `gz`, ...
+    pub archive: String,
+    /// Source code and structured data: `rs`, `py`, `json`, ...
+    pub code: String,
+    /// Plain text and documentation: `txt`, `md`, `log`, ...
+    pub text: String,
+}
+
+impl Default for IconSet {
+    fn default() -> Self {
+        Self {
+            folder: String::from("\u{f07b}"),
+            file: String::from("\u{f15b}"),
+            image: String::from("\u{f1c5}"),
+            audio: String::from("\u{f1c7}"),
+            archive: String::from("\u{f1c6}"),
+            code: String::from("\u{f1c9}"),
+            text: String::from("\u{f15c}"),
+        }
+    }
+}
+
+impl IconSet {
+    /// Returns the glyph of an entry, based on its kind and extension.
+    pub(crate) fn for_entry(&self, entry: &Entry) -> &str {
+        if entry.kind.is_dir() {
+            return &self.folder;
+        }
+        let extension = entry.path.extension().map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref().unwrap_or_default() {
+            "png" | "jpg" | "jpeg" | "gif" | "bmp" | "webp" | "svg" | "tif" | "tiff" | "ico" | "tga" | "psd" => &self.image,
+            "mp3" | "wav" | "ogg" | "flac" | "aac" | "m4a" | "opus" | "mid" => &self.audio,
+            "zip" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "7z" | "rar" | "zst" => &self.archive,
+            "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "cs" | "py" | "js" | "ts" | "java" | "go" | "rb" | "lua" | "sh"
+            | "html" | "css" | "json" | "toml" | "yaml" | "yml" | "xml" => &self.code,
+            "txt" | "md" | "rst" | "log" | "csv" | "ini" | "cfg" => &self.text,
+            _ => &self.file,
+        }
+    }
+}
@@ -7,38 +7,74 @@
 //! # Features
 //! - Lightweight and simple file dialog with embedded file browser
 //! - Compatible with `imgui-rs` >= 0.11.0
-//! - No extra dependencies
+//! - No mandatory dependencies beyond `imgui` and `log`; the features below are optional
+//!
+//! # Optional features
+//! - `serde`: Serialization of [`DialogConfig`] and [`DialogState`], with the `serde` crate.
+//! - `collation`: Language-aware sorting of file names with [`FileDialog::collation_locale()`].
+//! - `icons`: Icons from a glyph font such as Font Awesome in the listing, see [`FileDialog::icons()`].
+//! - `regex`: Filtering file names with regular expressions from the `regex` crate, see [`FileDialog::filter_regex()`],
+//!   and searching with them, see [`FileDialog::regex_search()`].
+//! - `unicode-normalization`: Full Unicode normalization of file names, with the `unicode-normalization` crate.
+//!   Without it, only the common Latin accented letters are composed (eg. names written by macOS in decomposed form).
+//!
+//! `collation` and `icons` don't add any dependency.
 //!
 //! # Example
 //! Basic usage:
 //! ```no_run
 //! use imfile::FileDialog;
 //!
-//! fn main() {
-//!     // set up your imgui::Ui here
-//!
+//! # fn draw(ui: &imgui::Ui) {
 //!     // This returns None if no file was selected
 //!     if let Some(file) = FileDialog::new()
-//!         .to_save() // Default is open
 //!         .title("Title") // Default is "Open File" or "Save file" depending on the dialog type
-//!         .accept_text("Open file") // Default is open
-//!         .dir_only() // Only allow directories instead of files
-//!         .spawn(&ui); // Create the dialog using the imgui::Ui
+//!         .accept_text("Open folder") // Default is open
+//!         .dir_only() // Only allow directories instead of files, can't be used with for_save()
+//!         .spawn(ui) // Create the dialog using the imgui::Ui
+//!         .path() // Or match on the DialogResult to tell cancelling apart
 //!     {
 //!         println!("File chosen: {}", file.display());
 //!     } else {
 //!         println!("No file selected.");
 //!     }
-//! }
+//! # }
 //! ```
 //!
-//! # TODOs
-//! - Add icons for the widgets
-//! - Add file filters
-//! - Set side panel navigator (eg. Disk, Recents, ...)
+//! Every public type is exported at the crate root, and the most commonly used ones can be imported
+//! at once with `use imfile::prelude::*;`.
 //!
 //! # License
 //! The crate is licensed under the MIT license.
 
+#![forbid(unsafe_code)]
+
+#[cfg(feature = "collation")]
+mod collation;
+mod config;
+mod entry;
 mod file_dialog;
-pub use file_dialog::*;
+mod filter;
+#[cfg(feature = "icons")]
+mod icons;
+mod memory;
+mod navigation;
+mod normalize;
+mod places;
+pub mod prelude;
+mod search;
+mod sort;
+mod state;
+mod timezone;
+mod trash;
+pub use config::{ConfigError, DialogConfig, DialogMode, OptionCheckbox, ViewMode};
+pub use entry::{Entry, EntryKind};
+pub use file_dialog::{AcceptState, DialogResult, DrawStats, FileDialog};
+pub use filter::FileFilter;
+#[cfg(feature = "icons")]
+pub use icons::IconSet;
+pub use memory::DialogMemory;
+pub use navigation::{NavEvent, NavigationLog, NavTarget};
+pub use places::{CommonPlaces, Place, PlaceIcon, PlaceSection, PlacesProvider};
+pub use sort::{DotfilesPosition, SortBy, SortOrder};
+pub use state::DialogState;
@@ -0,0 +1,141 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// How many recently used files and directories are remembered.
+pub(crate) const RECENT_CAPACITY: usize = 10;
+
+/// Remembers where dialogs were left, the files and directories used recently and the bookmarked directories.
+///
+/// Keep one `DialogMemory` around for the lifetime of your application and hand it to every dialog
+/// with [`FileDialog::memory()`](crate::FileDialog::memory), along with a purpose like `"open texture"`
+/// or `"export"`. What is remembered is shared as follows:
+/// - The last directory is kept per purpose: a dialog opens where the last dialog with the same purpose was left.
+/// - The recently used paths and the bookmarks are global: every dialog lists them in the "Recent" and
+///   "Bookmarks" sections of its side panel, whatever its purpose.
+///
+/// Clones of a `DialogMemory` share the same memory.
+/// ```no_run
+/// use imfile::{DialogMemory, FileDialog};
+///
+/// let memory = DialogMemory::new();
+/// // Opens wherever the last "open texture" dialog was left
+/// let dialog = FileDialog::new().memory(&memory, "open texture");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DialogMemory {
+    state: Rc<RefCell<MemoryState>>,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    last_directories: HashMap<String, PathBuf>,
+    /// Most recent first.
+    recent: VecDeque<PathBuf>,
+    bookmarks: Vec<PathBuf>,
+}
+
+impl DialogMemory {
+    /// Creates an empty memory.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last directory a dialog with the given purpose was in.
+    pub fn last_directory(&self, purpose: &str) -> Option<PathBuf> {
+        self.state.borrow().last_directories.get(purpose).cloned()
+    }
+
+    /// Sets the directory the next dialog with the given purpose opens in,
+    /// eg. to restore what was saved by a previous run of the application.
+    pub fn set_last_directory<S: Into<String>, P: AsRef<Path>>(&self, purpose: S, dir: P) {
+        self.state.borrow_mut().last_directories.insert(purpose.into(), dir.as_ref().to_path_buf());
+    }
+
+    /// Returns the files and directories used recently, most recent first.
+    ///
+    /// Paths that don't exist anymore are forgotten when a path is recorded and when a dialog
+    /// using this memory opens, so this doesn't touch the filesystem.
+    pub fn recent(&self) -> Vec<PathBuf> {
+        self.state.borrow().recent.iter().cloned().collect()
+    }
+
+    /// Records a file or directory as used just now, eg. to restore what was saved by a previous
+    /// run of the application. Only the 10 most recent paths are kept.
+    pub fn push_recent<P: AsRef<Path>>(&self, path: P) {
+        let path = path.as_ref();
+        let mut state = self.state.borrow_mut();
+        state.recent.retain(|recent| recent != path && recent.exists());
+        state.recent.push_front(path.to_path_buf());
+        state.recent.truncate(RECENT_CAPACITY);
+    }
+
+    /// Forgets the recently used paths that don't exist anymore.
+    pub(crate) fn prune_recent(&self) {
+        self.state.borrow_mut().recent.retain(|path| path.exists());
+    }
+
+    /// Returns the bookmarked directories, in the order they were added. Bookmarks are kept
+    /// even if their directory disappears; dialogs list them greyed out.
+    pub fn bookmarks(&self) -> Vec<PathBuf> {
+        self.state.borrow().bookmarks.clone()
+    }
+
+    /// Bookmarks a directory. Adding an existing bookmark does nothing.
+    pub fn add_bookmark<P: AsRef<Path>>(&self, dir: P) {
+        let dir = dir.as_ref();
+        let mut state = self.state.borrow_mut();
+        if !state.bookmarks.iter().any(|bookmark| bookmark == dir) {
+            state.bookmarks.push(dir.to_path_buf());
+        }
+    }
+
+    /// Removes a bookmark, if it exists.
+    pub fn remove_bookmark<P: AsRef<Path>>(&self, dir: P) {
+        let dir = dir.as_ref();
+        self.state.borrow_mut().bookmarks.retain(|bookmark| bookmark != dir);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn recent_paths_are_most_recent_first() {
+        let memory = DialogMemory::new();
+        let dir = std::env::temp_dir();
+        memory.push_recent(&dir);
+        memory.push_recent(dir.join("..").join(".."));
+        memory.push_recent(&dir);
+        assert_eq!(memory.recent(), [dir.clone(), dir.join("..").join("..")]);
+    }
+
+    #[test]
+    fn missing_recent_paths_are_forgotten_when_pruned() {
+        let root = std::env::temp_dir().join(format!("imfile-recent-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let (kept, removed) = (root.join("kept.txt"), root.join("removed.txt"));
+        fs::write(&kept, "").unwrap();
+        fs::write(&removed, "").unwrap();
+
+        let memory = DialogMemory::new();
+        memory.push_recent(&kept);
+        memory.push_recent(&removed);
+        fs::remove_file(&removed).unwrap();
+        /* Reading them doesn't look at the filesystem */
+        assert_eq!(memory.recent(), [removed.clone(), kept.clone()]);
+        memory.prune_recent();
+        assert_eq!(memory.recent(), [kept.as_path()]);
+
+        memory.push_recent(&removed);
+        fs::remove_file(&kept).unwrap();
+        memory.push_recent(&root);
+        assert_eq!(memory.recent(), [root.as_path()]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
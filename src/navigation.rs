@@ -0,0 +1,90 @@
+use std::collections::VecDeque;
+use std::path::PathBuf;
+
+/// How many navigations the [`NavigationLog`] keeps before dropping the oldest.
+pub(crate) const NAVIGATION_LOG_CAPACITY: usize = 64;
+
+/// A request to change the directory displayed by the dialog, along with the
+/// part of the dialog it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NavTarget {
+    /// A directory of the listing was clicked.
+    Entry(PathBuf),
+    /// A component of the path bar was clicked.
+    PathBar(PathBuf),
+    /// A path was typed in the path bar. Typed files lead to their parent directory.
+    Typed(PathBuf),
+    /// An entry of the side panel was clicked. Starred files lead to their parent directory.
+    Place(PathBuf),
+    /// The "Back" button was clicked, leading to the parent directory.
+    Parent,
+}
+
+/// A navigation performed (or attempted) by the dialog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NavEvent {
+    /// What requested the navigation.
+    pub target: NavTarget,
+    /// The directory displayed before the navigation.
+    pub from: PathBuf,
+    /// The directory the navigation led to.
+    pub to: PathBuf,
+    /// Why the navigation failed, in which case the dialog stayed in `from`.
+    pub error: Option<String>,
+}
+
+/// The most recent navigations of a dialog, oldest first.
+///
+/// Cloning a dialog doesn't carry its history over, so the clone of a log is always empty.
+#[derive(Debug, Default)]
+pub struct NavigationLog {
+    events: VecDeque<NavEvent>,
+}
+
+impl NavigationLog {
+    pub(crate) fn push(&mut self, event: NavEvent) {
+        if self.events.len() == NAVIGATION_LOG_CAPACITY {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    /// Returns the recorded navigations, oldest first.
+    #[inline]
+    pub fn events(&self) -> impl DoubleEndedIterator<Item = &NavEvent> + ExactSizeIterator {
+        self.events.iter()
+    }
+
+    /// Returns the most recent navigation, if any.
+    #[inline]
+    pub fn last(&self) -> Option<&NavEvent> {
+        self.events.back()
+    }
+}
+
+impl Clone for NavigationLog {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(to: &str) -> NavEvent {
+        NavEvent { target: NavTarget::Entry(PathBuf::from(to)), from: PathBuf::from("/"), to: PathBuf::from(to), error: None }
+    }
+
+    #[test]
+    fn oldest_events_are_dropped() {
+        let mut log = NavigationLog::default();
+        for i in 0..NAVIGATION_LOG_CAPACITY + 2 {
+            log.push(event(&format!("/{}", i)));
+        }
+        assert_eq!(log.events().len(), NAVIGATION_LOG_CAPACITY);
+        assert_eq!(log.events().next(), Some(&event("/2")));
+        assert_eq!(log.last(), Some(&event(&format!("/{}", NAVIGATION_LOG_CAPACITY + 1))));
+        assert_eq!(log.clone().last(), None);
+    }
+}
@@ -0,0 +1,181 @@
+use std::borrow::Cow;
+
+/// Normalizes a file name to NFC (composed form) for display and matching.
+///
+/// macOS stores file names decomposed, so `café` may be written as `cafe` followed by a
+/// combining acute accent, which then neither looks nor compares like what the user typed.
+/// Only use the result for display and comparisons; filesystem operations must keep using
+/// the original name.
+///
+/// With the `unicode-normalization` feature enabled this performs full NFC normalization.
+/// Otherwise a small built-in pass composes the common Latin letters with their combining
+/// accents, which covers the vast majority of decomposed names in practice.
+#[cfg(feature = "unicode-normalization")]
+pub(crate) fn nfc(name: &str) -> Cow<'_, str> {
+    use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+    match is_nfc_quick(name.chars()) {
+        IsNormalized::Yes => Cow::Borrowed(name),
+        _ => Cow::Owned(name.nfc().collect()),
+    }
+}
+
+/// Same as above, using the built-in composition table.
+#[cfg(not(feature = "unicode-normalization"))]
+pub(crate) fn nfc(name: &str) -> Cow<'_, str> {
+    if !name.chars().any(is_combining_mark) {
+        return Cow::Borrowed(name);
+    }
+    let mut composed = String::with_capacity(name.len());
+    for c in name.chars() {
+        let precomposed = composed.chars().next_back()
+            .filter(|_| is_combining_mark(c))
+            .and_then(|base| compose(base, c));
+        match precomposed {
+            Some(precomposed) => {
+                composed.pop();
+                composed.push(precomposed);
+            }
+            None => composed.push(c),
+        }
+    }
+    Cow::Owned(composed)
+}
+
+#[cfg(not(feature = "unicode-normalization"))]
+#[inline]
+fn is_combining_mark(c: char) -> bool {
+    ('\u{300}'..='\u{36f}').contains(&c)
+}
+
+#[cfg(not(feature = "unicode-normalization"))]
+fn compose(base: char, mark: char) -> Option<char> {
+    LATIN_COMPOSITIONS
+        .binary_search_by(|&(b, m, _)| (b, m).cmp(&(base, mark)))
+        .ok()
+        .map(|i| LATIN_COMPOSITIONS[i].2)
+}
+
+/// Basic Latin letters combined with the most common accents, sorted by base letter then mark.
+#[cfg(not(feature = "unicode-normalization"))]
+const LATIN_COMPOSITIONS: &[(char, char, char)] = &[
+    ('A', '\u{300}', 'À'), ('A', '\u{301}', 'Á'), ('A', '\u{302}', 'Â'), ('A', '\u{303}', 'Ã'), ('A', '\u{304}', 'Ā'),
+    ('A', '\u{306}', 'Ă'), ('A', '\u{307}', 'Ȧ'), ('A', '\u{308}', 'Ä'), ('A', '\u{30a}', 'Å'), ('A', '\u{30c}', 'Ǎ'),
+    ('A', '\u{328}', 'Ą'),
+    ('B', '\u{307}', 'Ḃ'),
+    ('C', '\u{301}', 'Ć'), ('C', '\u{302}', 'Ĉ'), ('C', '\u{307}', 'Ċ'), ('C', '\u{30c}', 'Č'), ('C', '\u{327}', 'Ç'),
+    ('D', '\u{307}', 'Ḋ'), ('D', '\u{30c}', 'Ď'), ('D', '\u{327}', 'Ḑ'),
+    ('E', '\u{300}', 'È'), ('E', '\u{301}', 'É'), ('E', '\u{302}', 'Ê'), ('E', '\u{303}', 'Ẽ'), ('E', '\u{304}', 'Ē'),
+    ('E', '\u{306}', 'Ĕ'), ('E', '\u{307}', 'Ė'), ('E', '\u{308}', 'Ë'), ('E', '\u{30c}', 'Ě'), ('E', '\u{327}', 'Ȩ'),
+    ('E', '\u{328}', 'Ę'),
+    ('F', '\u{307}', 'Ḟ'),
+    ('G', '\u{301}', 'Ǵ'), ('G', '\u{302}', 'Ĝ'), ('G', '\u{304}', 'Ḡ'), ('G', '\u{306}', 'Ğ'), ('G', '\u{307}', 'Ġ'),
+    ('G', '\u{30c}', 'Ǧ'), ('G', '\u{327}', 'Ģ'),
+    ('H', '\u{302}', 'Ĥ'), ('H', '\u{307}', 'Ḣ'), ('H', '\u{308}', 'Ḧ'), ('H', '\u{30c}', 'Ȟ'), ('H', '\u{327}', 'Ḩ'),
+    ('I', '\u{300}', 'Ì'), ('I', '\u{301}', 'Í'), ('I', '\u{302}', 'Î'), ('I', '\u{303}', 'Ĩ'), ('I', '\u{304}', 'Ī'),
+    ('I', '\u{306}', 'Ĭ'), ('I', '\u{307}', 'İ'), ('I', '\u{308}', 'Ï'), ('I', '\u{30c}', 'Ǐ'), ('I', '\u{328}', 'Į'),
+    ('J', '\u{302}', 'Ĵ'),
+    ('K', '\u{301}', 'Ḱ'), ('K', '\u{30c}', 'Ǩ'), ('K', '\u{327}', 'Ķ'),
+    ('L', '\u{301}', 'Ĺ'), ('L', '\u{30c}', 'Ľ'), ('L', '\u{327}', 'Ļ'),
+    ('M', '\u{301}', 'Ḿ'), ('M', '\u{307}', 'Ṁ'),
+    ('N', '\u{300}', 'Ǹ'), ('N', '\u{301}', 'Ń'), ('N', '\u{303}', 'Ñ'), ('N', '\u{307}', 'Ṅ'), ('N', '\u{30c}', 'Ň'),
+    ('N', '\u{327}', 'Ņ'),
+    ('O', '\u{300}', 'Ò'), ('O', '\u{301}', 'Ó'), ('O', '\u{302}', 'Ô'), ('O', '\u{303}', 'Õ'), ('O', '\u{304}', 'Ō'),
+    ('O', '\u{306}', 'Ŏ'), ('O', '\u{307}', 'Ȯ'), ('O', '\u{308}', 'Ö'), ('O', '\u{30b}', 'Ő'), ('O', '\u{30c}', 'Ǒ'),
+    ('O', '\u{328}', 'Ǫ'),
+    ('P', '\u{301}', 'Ṕ'), ('P', '\u{307}', 'Ṗ'),
+    ('R', '\u{301}', 'Ŕ'), ('R', '\u{307}', 'Ṙ'), ('R', '\u{30c}', 'Ř'), ('R', '\u{327}', 'Ŗ'),
+    ('S', '\u{301}', 'Ś'), ('S', '\u{302}', 'Ŝ'), ('S', '\u{307}', 'Ṡ'), ('S', '\u{30c}', 'Š'), ('S', '\u{327}', 'Ş'),
+    ('T', '\u{307}', 'Ṫ'), ('T', '\u{30c}', 'Ť'), ('T', '\u{327}', 'Ţ'),
+    ('U', '\u{300}', 'Ù'), ('U', '\u{301}', 'Ú'), ('U', '\u{302}', 'Û'), ('U', '\u{303}', 'Ũ'), ('U', '\u{304}', 'Ū'),
+    ('U', '\u{306}', 'Ŭ'), ('U', '\u{308}', 'Ü'), ('U', '\u{30a}', 'Ů'), ('U', '\u{30b}', 'Ű'), ('U', '\u{30c}', 'Ǔ'),
+    ('U', '\u{328}', 'Ų'),
+    ('V', '\u{303}', 'Ṽ'),
+    ('W', '\u{300}', 'Ẁ'), ('W', '\u{301}', 'Ẃ'), ('W', '\u{302}', 'Ŵ'), ('W', '\u{307}', 'Ẇ'), ('W', '\u{308}', 'Ẅ'),
+    ('X', '\u{307}', 'Ẋ'), ('X', '\u{308}', 'Ẍ'),
+    ('Y', '\u{300}', 'Ỳ'), ('Y', '\u{301}', 'Ý'), ('Y', '\u{302}', 'Ŷ'), ('Y', '\u{303}', 'Ỹ'), ('Y', '\u{304}', 'Ȳ'),
+    ('Y', '\u{307}', 'Ẏ'), ('Y', '\u{308}', 'Ÿ'),
+    ('Z', '\u{301}', 'Ź'), ('Z', '\u{302}', 'Ẑ'), ('Z', '\u{307}', 'Ż'), ('Z', '\u{30c}', 'Ž'),
+    ('a', '\u{300}', 'à'), ('a', '\u{301}', 'á'), ('a', '\u{302}', 'â'), ('a', '\u{303}', 'ã'), ('a', '\u{304}', 'ā'),
+    ('a', '\u{306}', 'ă'), ('a', '\u{307}', 'ȧ'), ('a', '\u{308}', 'ä'), ('a', '\u{30a}', 'å'), ('a', '\u{30c}', 'ǎ'),
+    ('a', '\u{328}', 'ą'),
+    ('b', '\u{307}', 'ḃ'),
+    ('c', '\u{301}', 'ć'), ('c', '\u{302}', 'ĉ'), ('c', '\u{307}', 'ċ'), ('c', '\u{30c}', 'č'), ('c', '\u{327}', 'ç'),
+    ('d', '\u{307}', 'ḋ'), ('d', '\u{30c}', 'ď'), ('d', '\u{327}', 'ḑ'),
+    ('e', '\u{300}', 'è'), ('e', '\u{301}', 'é'), ('e', '\u{302}', 'ê'), ('e', '\u{303}', 'ẽ'), ('e', '\u{304}', 'ē'),
+    ('e', '\u{306}', 'ĕ'), ('e', '\u{307}', 'ė'), ('e', '\u{308}', 'ë'), ('e', '\u{30c}', 'ě'), ('e', '\u{327}', 'ȩ'),
+    ('e', '\u{328}', 'ę'),
+    ('f', '\u{307}', 'ḟ'),
+    ('g', '\u{301}', 'ǵ'), ('g', '\u{302}', 'ĝ'), ('g', '\u{304}', 'ḡ'), ('g', '\u{306}', 'ğ'), ('g', '\u{307}', 'ġ'),
+    ('g', '\u{30c}', 'ǧ'), ('g', '\u{327}', 'ģ'),
+    ('h', '\u{302}', 'ĥ'), ('h', '\u{307}', 'ḣ'), ('h', '\u{308}', 'ḧ'), ('h', '\u{30c}', 'ȟ'), ('h', '\u{327}', 'ḩ'),
+    ('i', '\u{300}', 'ì'), ('i', '\u{301}', 'í'), ('i', '\u{302}', 'î'), ('i', '\u{303}', 'ĩ'), ('i', '\u{304}', 'ī'),
+    ('i', '\u{306}', 'ĭ'), ('i', '\u{308}', 'ï'), ('i', '\u{30c}', 'ǐ'), ('i', '\u{328}', 'į'),
+    ('j', '\u{302}', 'ĵ'), ('j', '\u{30c}', 'ǰ'),
+    ('k', '\u{301}', 'ḱ'), ('k', '\u{30c}', 'ǩ'), ('k', '\u{327}', 'ķ'),
+    ('l', '\u{301}', 'ĺ'), ('l', '\u{30c}', 'ľ'), ('l', '\u{327}', 'ļ'),
+    ('m', '\u{301}', 'ḿ'), ('m', '\u{307}', 'ṁ'),
+    ('n', '\u{300}', 'ǹ'), ('n', '\u{301}', 'ń'), ('n', '\u{303}', 'ñ'), ('n', '\u{307}', 'ṅ'), ('n', '\u{30c}', 'ň'),
+    ('n', '\u{327}', 'ņ'),
+    ('o', '\u{300}', 'ò'), ('o', '\u{301}', 'ó'), ('o', '\u{302}', 'ô'), ('o', '\u{303}', 'õ'), ('o', '\u{304}', 'ō'),
+    ('o', '\u{306}', 'ŏ'), ('o', '\u{307}', 'ȯ'), ('o', '\u{308}', 'ö'), ('o', '\u{30b}', 'ő'), ('o', '\u{30c}', 'ǒ'),
+    ('o', '\u{328}', 'ǫ'),
+    ('p', '\u{301}', 'ṕ'), ('p', '\u{307}', 'ṗ'),
+    ('r', '\u{301}', 'ŕ'), ('r', '\u{307}', 'ṙ'), ('r', '\u{30c}', 'ř'), ('r', '\u{327}', 'ŗ'),
+    ('s', '\u{301}', 'ś'), ('s', '\u{302}', 'ŝ'), ('s', '\u{307}', 'ṡ'), ('s', '\u{30c}', 'š'), ('s', '\u{327}', 'ş'),
+    ('t', '\u{307}', 'ṫ'), ('t', '\u{308}', 'ẗ'), ('t', '\u{30c}', 'ť'), ('t', '\u{327}', 'ţ'),
+    ('u', '\u{300}', 'ù'), ('u', '\u{301}', 'ú'), ('u', '\u{302}', 'û'), ('u', '\u{303}', 'ũ'), ('u', '\u{304}', 'ū'),
+    ('u', '\u{306}', 'ŭ'), ('u', '\u{308}', 'ü'), ('u', '\u{30a}', 'ů'), ('u', '\u{30b}', 'ű'), ('u', '\u{30c}', 'ǔ'),
+    ('u', '\u{328}', 'ų'),
+    ('v', '\u{303}', 'ṽ'),
+    ('w', '\u{300}', 'ẁ'), ('w', '\u{301}', 'ẃ'), ('w', '\u{302}', 'ŵ'), ('w', '\u{307}', 'ẇ'), ('w', '\u{308}', 'ẅ'),
+    ('w', '\u{30a}', 'ẘ'),
+    ('x', '\u{307}', 'ẋ'), ('x', '\u{308}', 'ẍ'),
+    ('y', '\u{300}', 'ỳ'), ('y', '\u{301}', 'ý'), ('y', '\u{302}', 'ŷ'), ('y', '\u{303}', 'ỹ'), ('y', '\u{304}', 'ȳ'),
+    ('y', '\u{307}', 'ẏ'), ('y', '\u{308}', 'ÿ'), ('y', '\u{30a}', 'ẙ'),
+    ('z', '\u{301}', 'ź'), ('z', '\u{302}', 'ẑ'), ('z', '\u{307}', 'ż'), ('z', '\u{30c}', 'ž'),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn composed_names_are_borrowed() {
+        assert!(matches!(nfc("caf\u{e9}.png"), Cow::Borrowed("caf\u{e9}.png")));
+        assert!(matches!(nfc("plain.txt"), Cow::Borrowed("plain.txt")));
+        assert!(matches!(nfc(""), Cow::Borrowed("")));
+    }
+
+    #[test]
+    fn latin_letters_are_composed() {
+        assert_eq!(nfc("cafe\u{301}.png"), "caf\u{e9}.png");
+        assert_eq!(nfc("A\u{30a}ngstro\u{308}m"), "\u{c5}ngstr\u{f6}m");
+        assert_eq!(nfc("Z\u{30c}ivot"), "\u{17d}ivot");
+    }
+
+    #[test]
+    fn lone_marks_are_kept() {
+        assert_eq!(nfc("\u{301}a"), "\u{301}a");
+        assert_eq!(nfc("e\u{301}\u{301}"), "\u{e9}\u{301}");
+    }
+
+    /// The built-in table only knows about Latin letters.
+    #[test]
+    #[cfg(not(feature = "unicode-normalization"))]
+    fn table_leaves_other_letters_alone() {
+        assert_eq!(nfc("x\u{301}"), "x\u{301}");
+        assert_eq!(nfc("\u{3b1}\u{301}"), "\u{3b1}\u{301}");
+        assert_eq!(nfc("\u{1100}\u{1161}"), "\u{1100}\u{1161}");
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-normalization")]
+    fn full_normalization_composes_everything() {
+        assert_eq!(nfc("\u{3b1}\u{301}"), "\u{3ac}");
+        assert_eq!(nfc("\u{1100}\u{1161}"), "\u{ac00}");
+        assert_eq!(nfc("x\u{301}"), "x\u{301}");
+        /* Marks are reordered before composing */
+        assert_eq!(nfc("a\u{323}\u{302}"), nfc("a\u{302}\u{323}"));
+    }
+}
@@ -0,0 +1,433 @@
+use std::cell::OnceCell;
+use std::path::{Path, PathBuf};
+
+/// The section of the side panel a [`Place`] is listed under.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlaceSection {
+    /// Well-known locations such as the home directory.
+    Places,
+    /// Drives, mounted volumes and removable media.
+    Devices,
+    /// Directories pinned by the user.
+    Bookmarks,
+    /// Recently used files and directories.
+    Recent,
+    /// Any other section, displayed with the given title.
+    Custom(String),
+}
+
+impl PlaceSection {
+    /// Returns the title displayed above the section in the side panel.
+    pub fn title(&self) -> &str {
+        match self {
+            PlaceSection::Places    => "Places",
+            PlaceSection::Devices   => "Devices",
+            PlaceSection::Bookmarks => "Bookmarks",
+            PlaceSection::Recent    => "Recent",
+            PlaceSection::Custom(title) => title,
+        }
+    }
+}
+
+/// A hint about how a [`Place`] should be decorated.
+///
+/// The dialog doesn't render icons yet, but providers should still set this so
+/// that the information is there once it does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PlaceIcon {
+    #[default]
+    Folder,
+    Home,
+    Desktop,
+    Documents,
+    Downloads,
+    Pictures,
+    Music,
+    Videos,
+    Drive,
+    Removable,
+    Network,
+    Bookmark,
+    File,
+}
+
+/// A single entry of the side panel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Place {
+    /// The text displayed for the entry.
+    pub label: String,
+    /// Where the dialog navigates when the entry is clicked.
+    pub path: PathBuf,
+    /// How the entry should be decorated.
+    pub icon: PlaceIcon,
+    /// The section the entry is grouped under.
+    pub section: PlaceSection,
+}
+
+impl Place {
+    /// Creates a new place listed under [`PlaceSection::Places`] with the default icon.
+    pub fn new<S: Into<String>, P: Into<PathBuf>>(label: S, path: P) -> Self {
+        Self {
+            label: label.into(),
+            path: path.into(),
+            icon: PlaceIcon::default(),
+            section: PlaceSection::Places,
+        }
+    }
+
+    /// Sets the icon hint of the place.
+    #[inline]
+    pub fn icon(mut self, icon: PlaceIcon) -> Self {
+        self.icon = icon;
+        self
+    }
+
+    /// Sets the section the place is listed under.
+    #[inline]
+    pub fn section(mut self, section: PlaceSection) -> Self {
+        self.section = section;
+        self
+    }
+}
+
+/// A source of entries for the side panel of the dialog.
+///
+/// Implement this to inject your own locations (project workspaces, sync roots, ...)
+/// and register it with [`FileDialog::places_provider()`](crate::FileDialog::places_provider).
+///
+/// Providers are queried when the dialog opens, that is the first time it is drawn and the first time
+/// after it returned a result, and when [`FileDialog::refresh_places()`](crate::FileDialog::refresh_places)
+/// is called. With [`FileDialog::places_refresh_interval()`](crate::FileDialog::places_refresh_interval),
+/// they are also queried again at that interval while the dialog is open. In between, the dialog keeps
+/// the places they returned. Their results are displayed in registration order.
+///
+/// Queries happen on the thread drawing the dialog, so avoid blocking on slow devices inside
+/// [`places()`](PlacesProvider::places).
+///
+/// Closures returning a `Vec<Place>` and plain `Vec<Place>`s implement this trait too:
+/// ```no_run
+/// use imfile::{FileDialog, Place, PlaceSection};
+///
+/// let dialog = FileDialog::new()
+///     .places_provider(Box::new(vec![
+///         Place::new("Game", "/home/me/projects/game").section(PlaceSection::Custom("Projects".into())),
+///     ]));
+/// ```
+pub trait PlacesProvider {
+    /// Returns the places this provider currently knows about.
+    fn places(&self) -> Vec<Place>;
+}
+
+impl<F: Fn() -> Vec<Place>> PlacesProvider for F {
+    fn places(&self) -> Vec<Place> {
+        self()
+    }
+}
+
+impl PlacesProvider for Vec<Place> {
+    fn places(&self) -> Vec<Place> {
+        self.clone()
+    }
+}
+
+/// The usual places of the current user: the home directory, the user directories (desktop,
+/// documents, downloads, pictures, music and videos), and the root of the filesystem. On Windows,
+/// every drive is listed under [`PlaceSection::Devices`] instead of the root, and on macOS the
+/// mounted volumes are listed there too. Places that don't exist are left out.
+///
+/// Finding the drives means checking every letter from `A:` to `Z:`, which can be slow for drives
+/// that aren't ready. The dialog only does it when it queries its places (see [`PlacesProvider`]),
+/// never on every frame, and queries them again when a place it lists can't be opened anymore.
+///
+/// On Linux, the user directories are read from `user-dirs.dirs`, so localized and relocated
+/// directories are found. The file is parsed the first time the places are queried, and the result
+/// kept for the following queries. Elsewhere, or when the file is missing, the conventional English
+/// names inside the home directory are used.
+///
+/// Listed at the top of the side panel unless disabled with
+/// [`FileDialog::common_places()`](crate::FileDialog::common_places).
+#[derive(Debug, Clone, Default)]
+pub struct CommonPlaces {
+    /// The keys and paths read from `user-dirs.dirs`.
+    user_dirs: OnceCell<Vec<(String, PathBuf)>>,
+}
+
+impl CommonPlaces {
+    /// Creates the provider. Nothing is read until its places are queried.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl PlacesProvider for CommonPlaces {
+    fn places(&self) -> Vec<Place> {
+        let mut places = Vec::new();
+        if let Some(home) = home_dir() {
+            let configured = self.user_dirs.get_or_init(|| {
+                if cfg!(target_os = "linux") { read_user_dirs(&home) } else { Vec::new() }
+            });
+            places.push(Place::new("Home", &home).icon(PlaceIcon::Home));
+            places.extend(user_dirs(&home, configured));
+        }
+        if cfg!(windows) {
+            places.extend(drives());
+        } else {
+            places.push(Place::new("File System", "/").icon(PlaceIcon::Drive));
+        }
+        if cfg!(target_os = "macos") {
+            places.extend(volumes());
+        }
+        places.retain(|place| place.path.is_dir());
+        places
+    }
+}
+
+/// The user directories, with the key identifying them in `user-dirs.dirs` and their conventional name.
+const USER_DIRS: [(&str, &str, PlaceIcon); 6] = [
+    ("XDG_DESKTOP_DIR", "Desktop", PlaceIcon::Desktop),
+    ("XDG_DOCUMENTS_DIR", "Documents", PlaceIcon::Documents),
+    ("XDG_DOWNLOAD_DIR", "Downloads", PlaceIcon::Downloads),
+    ("XDG_PICTURES_DIR", "Pictures", PlaceIcon::Pictures),
+    ("XDG_MUSIC_DIR", "Music", PlaceIcon::Music),
+    ("XDG_VIDEOS_DIR", "Videos", PlaceIcon::Videos),
+];
+
+/// Returns the user directories, labelled with their directory name when it was `configured`.
+fn user_dirs(home: &Path, configured: &[(String, PathBuf)]) -> Vec<Place> {
+    let mut places = Vec::new();
+    for (key, name, icon) in USER_DIRS {
+        let place = match configured.iter().find(|(k, _)| k == key) {
+            /* The spec disables a directory by pointing it at the home directory. */
+            Some((_, path)) if path == home => continue,
+            Some((_, path)) => {
+                let label = path.file_name().map_or_else(|| name.into(), |label| label.to_string_lossy().into_owned());
+                Place::new(label, path)
+            }
+            None => Place::new(name, home.join(name)),
+        };
+        places.push(place.icon(icon));
+    }
+    places
+}
+
+/// Reads the `user-dirs.dirs` file of the XDG configuration directory, returning its keys and paths.
+/// Returns nothing if the file doesn't exist.
+fn read_user_dirs(home: &Path) -> Vec<(String, PathBuf)> {
+    let config_dir = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| home.join(".config"));
+    match std::fs::read_to_string(config_dir.join("user-dirs.dirs")) {
+        Ok(contents) => parse_user_dirs(&contents, home),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Parses the contents of a `user-dirs.dirs` file.
+///
+/// Each line is a `KEY="value"` shell assignment, where the value is either an absolute path
+/// or starts with `$HOME/`. Comments and malformed lines are skipped.
+fn parse_user_dirs(contents: &str, home: &Path) -> Vec<(String, PathBuf)> {
+    let mut dirs = Vec::new();
+    for line in contents.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else { continue };
+        let Some(value) = unquote(value.trim()) else { continue };
+        let path = if value == "$HOME" {
+            home.to_path_buf()
+        } else if let Some(relative) = value.strip_prefix("$HOME/") {
+            home.join(relative)
+        } else if value.starts_with('/') {
+            PathBuf::from(value)
+        } else {
+            continue;
+        };
+        dirs.push((key.trim().to_string(), path));
+    }
+    dirs
+}
+
+/// Removes the double quotes around a value and resolves its backslash escapes.
+fn unquote(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unquoted.push(chars.next()?),
+            c => unquoted.push(c),
+        }
+    }
+    Some(unquoted)
+}
+
+/// Returns the drive letters from `A:` to `Z:`, listed under [`PlaceSection::Devices`].
+/// Whether they exist is left to the caller, which checks every place at once.
+fn drives() -> Vec<Place> {
+    (b'A'..=b'Z')
+        .map(|letter| {
+            let letter = letter as char;
+            Place::new(format!("{}:", letter), format!("{}:\\", letter))
+                .icon(PlaceIcon::Drive)
+                .section(PlaceSection::Devices)
+        })
+        .collect()
+}
+
+/// Returns the volumes mounted in `/Volumes`, listed under [`PlaceSection::Devices`].
+/// Volumes unmounted since are pruned the next time the places are queried, which refreshing
+/// the dialog does.
+fn volumes() -> Vec<Place> {
+    let entries = match std::fs::read_dir("/Volumes") {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut volumes: Vec<Place> = entries
+        .flatten()
+        /* The boot volume is a symlink to the root, which is already listed. */
+        .filter(|entry| entry.file_type().is_ok_and(|file_type| !file_type.is_symlink()))
+        .map(|entry| {
+            Place::new(entry.file_name().to_string_lossy(), entry.path())
+                .icon(PlaceIcon::Removable)
+                .section(PlaceSection::Devices)
+        })
+        .collect();
+    volumes.sort_by(|a, b| a.label.cmp(&b.label));
+    volumes
+}
+
+/// Returns the home directory of the current user, if it can be found.
+pub(crate) fn home_dir() -> Option<PathBuf> {
+    let var = if cfg!(windows) { "USERPROFILE" } else { "HOME" };
+    std::env::var_os(var).filter(|home| !home.is_empty()).map(PathBuf::from)
+}
+
+/// Queries every provider and groups the results by section, keeping the order
+/// in which sections and places first appeared.
+pub(crate) fn collect_places<'a, I>(providers: I) -> Vec<(PlaceSection, Vec<Place>)>
+where
+    I: IntoIterator<Item = &'a dyn PlacesProvider>,
+{
+    let mut sections: Vec<(PlaceSection, Vec<Place>)> = Vec::new();
+    for place in providers.into_iter().flat_map(|provider| provider.places()) {
+        match sections.iter_mut().find(|(section, _)| *section == place.section) {
+            Some((_, places)) => places.push(place),
+            None => sections.push((place.section.clone(), vec![place])),
+        }
+    }
+    sections
+}
+
+/// Returns the paths of the places listed under [`PlaceSection::Devices`] in `sections` but not in `previous`,
+/// like a drive plugged in between two queries.
+pub(crate) fn added_devices(previous: &[(PlaceSection, Vec<Place>)], sections: &[(PlaceSection, Vec<Place>)]) -> Vec<PathBuf> {
+    let devices = |sections: &[(PlaceSection, Vec<Place>)]| -> Vec<PathBuf> {
+        sections.iter()
+            .filter(|(section, _)| *section == PlaceSection::Devices)
+            .flat_map(|(_, places)| places.iter().map(|place| place.path.clone()))
+            .collect()
+    };
+    let known = devices(previous);
+    devices(sections).into_iter().filter(|path| !known.contains(path)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOME: &str = "/home/me";
+
+    fn parse(contents: &str) -> Vec<(String, PathBuf)> {
+        parse_user_dirs(contents, Path::new(HOME))
+    }
+
+    #[test]
+    fn home_relative_paths_are_expanded() {
+        assert_eq!(
+            parse("XDG_PICTURES_DIR=\"$HOME/Bilder\"\nXDG_DESKTOP_DIR=\"$HOME\"\n"),
+            [
+                (String::from("XDG_PICTURES_DIR"), PathBuf::from("/home/me/Bilder")),
+                (String::from("XDG_DESKTOP_DIR"), PathBuf::from(HOME)),
+            ]
+        );
+        assert_eq!(
+            parse("XDG_DOWNLOAD_DIR=\"/mnt/data/Downloads\""),
+            [(String::from("XDG_DOWNLOAD_DIR"), PathBuf::from("/mnt/data/Downloads"))]
+        );
+    }
+
+    #[test]
+    fn values_are_unquoted() {
+        assert_eq!(unquote("\"$HOME/Mes documents\""), Some(String::from("$HOME/Mes documents")));
+        assert_eq!(unquote(r#""$HOME/say \"hi\"""#), Some(String::from("$HOME/say \"hi\"")));
+        assert_eq!(unquote(r#""back\\slash""#), Some(String::from("back\\slash")));
+        assert_eq!(unquote("\"\""), Some(String::new()));
+        assert_eq!(unquote("$HOME/Music"), None);
+        assert_eq!(unquote("\"$HOME/Music"), None);
+        assert_eq!(unquote("\""), None);
+        /* A trailing backslash escapes nothing */
+        assert_eq!(unquote(r#""dangling\""#), None);
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let contents = "# This file is written by xdg-user-dirs-update\n\n  # indented comment\n   \nXDG_MUSIC_DIR=\"$HOME/Musik\"\n";
+        assert_eq!(parse(contents), [(String::from("XDG_MUSIC_DIR"), PathBuf::from("/home/me/Musik"))]);
+    }
+
+    #[test]
+    fn malformed_lines_are_skipped() {
+        let contents = concat!(
+            "XDG_DESKTOP_DIR\n",
+            "XDG_DOCUMENTS_DIR=$HOME/Documents\n",
+            "XDG_DOWNLOAD_DIR=\"Downloads\"\n",
+            "XDG_PICTURES_DIR=\"$HOMEPictures\"\n",
+            "XDG_MUSIC_DIR=\"$HOME/Music\n",
+            " XDG_VIDEOS_DIR = \"$HOME/Videos\" \n",
+        );
+        assert_eq!(parse(contents), [(String::from("XDG_VIDEOS_DIR"), PathBuf::from("/home/me/Videos"))]);
+    }
+
+    #[test]
+    fn configured_user_dirs_replace_the_conventional_ones() {
+        let home = Path::new(HOME);
+        let configured = parse("XDG_PICTURES_DIR=\"$HOME/Bilder\"\nXDG_DESKTOP_DIR=\"$HOME\"\n");
+        let places: Vec<_> = user_dirs(home, &configured).into_iter().map(|place| (place.label, place.path)).collect();
+        assert_eq!(places, [
+            (String::from("Documents"), PathBuf::from("/home/me/Documents")),
+            (String::from("Downloads"), PathBuf::from("/home/me/Downloads")),
+            (String::from("Bilder"), PathBuf::from("/home/me/Bilder")),
+            (String::from("Music"), PathBuf::from("/home/me/Music")),
+            (String::from("Videos"), PathBuf::from("/home/me/Videos")),
+        ]);
+    }
+
+    #[test]
+    fn user_dirs_are_read_once() {
+        let common_places = CommonPlaces::new();
+        assert!(common_places.user_dirs.get().is_none());
+        common_places.places();
+        if home_dir().is_some() {
+            let read = common_places.user_dirs.get().cloned();
+            assert!(read.is_some());
+            common_places.places();
+            assert_eq!(common_places.user_dirs.get().cloned(), read);
+        }
+    }
+
+    #[test]
+    fn plugged_devices_are_told_apart() {
+        let device = |path: &str| Place::new(path, path).section(PlaceSection::Devices);
+        let previous = [(PlaceSection::Devices, vec![device("C:\\"), device("D:\\")])];
+        let sections = [
+            (PlaceSection::Places, vec![Place::new("Home", HOME)]),
+            (PlaceSection::Devices, vec![device("C:\\"), device("E:\\")]),
+        ];
+        assert_eq!(added_devices(&previous, &sections), [PathBuf::from("E:\\")]);
+        assert_eq!(added_devices(&sections, &sections), Vec::<PathBuf>::new());
+        assert_eq!(added_devices(&[], &previous).len(), 2);
+    }
+}
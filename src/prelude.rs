@@ -0,0 +1,19 @@
+//! The commonly used types of the crate, for glob importing.
+//!
+//! ```no_run
+//! use imfile::prelude::*;
+//!
+//! # fn draw(ui: &imgui::Ui, dialog: &mut FileDialog) {
+//! if let DialogResult::Selected(file) = dialog.spawn_borrowed(ui) {
+//!     println!("File chosen: {}", file.display());
+//! }
+//! # }
+//! ```
+
+pub use crate::config::{ConfigError, DialogConfig, DialogMode, OptionCheckbox, ViewMode};
+pub use crate::entry::EntryKind;
+pub use crate::file_dialog::{AcceptState, DialogResult, FileDialog};
+pub use crate::filter::FileFilter;
+pub use crate::memory::DialogMemory;
+pub use crate::places::{Place, PlaceIcon, PlaceSection, PlacesProvider};
+pub use crate::sort::{DotfilesPosition, SortBy, SortOrder};
@@ -0,0 +1,261 @@
+use std::ops::Range;
+use std::time::Duration;
+use crate::normalize::nfc;
+
+/// Matches the names of the entries against the query of the search box.
+#[derive(Debug, Clone)]
+pub(crate) enum SearchMatcher {
+    /// Names containing the query.
+    Text { query: String, case_sensitive: bool },
+    /// Names matching a regular expression.
+    #[cfg(feature = "regex")]
+    Regex(regex::Regex),
+}
+
+impl SearchMatcher {
+    /// Matches names containing `query`. The query is normalized, like the names of the entries are when
+    /// the directory is read.
+    pub(crate) fn new(query: &str, case_sensitive: bool) -> Self {
+        let query = nfc(query);
+        Self::Text {
+            query: if case_sensitive { query.into_owned() } else { query.to_lowercase() },
+            case_sensitive,
+        }
+    }
+
+    /// Matches names matching the regular expression `pattern`, normalized like the query of [`new()`](Self::new).
+    #[cfg(feature = "regex")]
+    pub(crate) fn regex(pattern: &str, case_sensitive: bool) -> Result<Self, regex::Error> {
+        let regex = regex::RegexBuilder::new(&nfc(pattern)).case_insensitive(!case_sensitive).build()?;
+        Ok(Self::Regex(regex))
+    }
+
+    /// Returns the byte ranges of `name` matching the query, so that they can be highlighted, or `None` if
+    /// `name` doesn't match. Every name matches an empty query, with nothing to highlight.
+    pub(crate) fn find(&self, name: &str) -> Option<Vec<Range<usize>>> {
+        let (query, case_sensitive) = match self {
+            Self::Text { query, case_sensitive } => (query, *case_sensitive),
+            #[cfg(feature = "regex")]
+            Self::Regex(regex) => {
+                /* Patterns like `a*` also match the empty string, which has nothing to highlight */
+                let spans = regex.find_iter(name).map(|found| found.range()).filter(|span| !span.is_empty());
+                return regex.is_match(name).then(|| spans.collect());
+            }
+        };
+        if query.is_empty() {
+            return Some(Vec::new());
+        }
+        let spans: Vec<_> = if case_sensitive {
+            name.match_indices(query.as_str()).map(|(start, found)| start..start + found.len()).collect()
+        } else {
+            /* Lowercasing can change the length of characters, so every byte of the lowercased name
+             * remembers the character of `name` it comes from */
+            let mut lowercase = String::with_capacity(name.len());
+            let mut origins = Vec::with_capacity(name.len());
+            for (i, c) in name.char_indices() {
+                lowercase.extend(c.to_lowercase());
+                origins.resize(lowercase.len(), i..i + c.len_utf8());
+            }
+            lowercase.match_indices(query.as_str())
+                .map(|(start, found)| origins[start].start..origins[start + found.len() - 1].end)
+                .collect()
+        };
+        (!spans.is_empty()).then_some(spans)
+    }
+}
+
+/// How many queries the search history keeps.
+pub(crate) const SEARCH_HISTORY_CAPACITY: usize = 10;
+
+/// The last distinct queries of the search box, most recent first.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SearchHistory {
+    queries: Vec<String>,
+}
+
+impl SearchHistory {
+    /// Records `query` as the most recent one. A query that was already there, whatever its case, moves first
+    /// instead, and the oldest one is forgotten past [`SEARCH_HISTORY_CAPACITY`]. Empty queries aren't recorded.
+    pub(crate) fn push(&mut self, query: &str) {
+        if query.trim().is_empty() {
+            return;
+        }
+        let lowercase = query.to_lowercase();
+        self.queries.retain(|recorded| recorded.to_lowercase() != lowercase);
+        self.queries.insert(0, query.to_string());
+        self.queries.truncate(SEARCH_HISTORY_CAPACITY);
+    }
+
+    /// Returns the recorded queries, most recent first.
+    pub(crate) fn queries(&self) -> &[String] {
+        &self.queries
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.queries.clear();
+    }
+}
+
+/// How long the search box waits for the user to stop typing before filtering the listing.
+pub(crate) const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// Waits for a value to stop changing, counting the time of the frames drawn since it last changed.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Debounce {
+    /// The time left before the last change is due, if one is pending.
+    remaining: Option<Duration>,
+}
+
+impl Debounce {
+    /// Records a change, due once `delay` passes without another one.
+    pub(crate) fn changed(&mut self, delay: Duration) {
+        self.remaining = Some(delay);
+    }
+
+    /// Forgets the pending change, if any.
+    pub(crate) fn cancel(&mut self) {
+        self.remaining = None;
+    }
+
+    /// Counts `elapsed` time, returning whether the pending change is due now. It is due only once.
+    pub(crate) fn tick(&mut self, elapsed: Duration) -> bool {
+        match self.remaining {
+            Some(remaining) if remaining <= elapsed => {
+                self.remaining = None;
+                true
+            }
+            Some(remaining) => {
+                self.remaining = Some(remaining - elapsed);
+                false
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 5] = ["Makefile", "makefile", "MAKEFILE.bak", "README.md", "Über.txt"];
+
+    fn matching(query: &str, case_sensitive: bool) -> Vec<&'static str> {
+        let matcher = SearchMatcher::new(query, case_sensitive);
+        NAMES.into_iter().filter(|name| matcher.find(name).is_some()).collect()
+    }
+
+    #[test]
+    fn case_insensitive_search() {
+        assert_eq!(matching("makefile", false), ["Makefile", "makefile", "MAKEFILE.bak"]);
+        assert_eq!(matching("MakeFile", false), ["Makefile", "makefile", "MAKEFILE.bak"]);
+        assert_eq!(matching("über", false), ["Über.txt"]);
+        assert_eq!(matching("", false), NAMES);
+    }
+
+    #[test]
+    fn case_sensitive_search() {
+        assert_eq!(matching("makefile", true), ["makefile"]);
+        assert_eq!(matching("Make", true), ["Makefile"]);
+        assert_eq!(matching("MAKE", true), ["MAKEFILE.bak"]);
+        assert_eq!(matching("über", true), Vec::<&str>::new());
+        assert_eq!(matching("", true), NAMES);
+    }
+
+    #[test]
+    fn queries_are_normalized() {
+        /* "U" followed by a combining diaeresis */
+        assert_eq!(matching("U\u{308}ber", true), ["Über.txt"]);
+    }
+
+    /// Returns the spans of `name` matching `query` as tuples, which read better in assertions.
+    fn find(query: &str, case_sensitive: bool, name: &str) -> Option<Vec<(usize, usize)>> {
+        let spans = SearchMatcher::new(query, case_sensitive).find(name)?;
+        Some(spans.into_iter().map(|span| (span.start, span.end)).collect())
+    }
+
+    #[test]
+    fn spans_cover_every_occurrence() {
+        assert_eq!(find("shot", false, "Screenshot.png"), Some(vec![(6, 10)]));
+        assert_eq!(find("shot", false, "ShotSHOT"), Some(vec![(0, 4), (4, 8)]));
+        assert_eq!(find("shot", true, "ShotSHOTshot"), Some(vec![(8, 12)]));
+        assert_eq!(find("", false, "Screenshot.png"), Some(Vec::new()));
+        assert_eq!(find("shot", false, "Screen.png"), None);
+    }
+
+    #[test]
+    fn spans_index_the_original_name() {
+        /* "Ü" takes two bytes */
+        assert_eq!(find("über", false, "ÜBER über"), Some(vec![(0, 5), (6, 11)]));
+        assert_eq!(find("über", true, "ÜBER über"), Some(vec![(6, 11)]));
+        /* "İ" lowercases to an "i" and a combining dot, three bytes instead of two */
+        assert_eq!(find("stan", false, "İstanbul"), Some(vec![(2, 6)]));
+        assert_eq!(find("i", false, "İstanbul"), Some(vec![(0, 2)]));
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regex_search() {
+        let find = |pattern, case_sensitive, name| {
+            let spans = SearchMatcher::regex(pattern, case_sensitive).unwrap().find(name)?;
+            Some(spans.into_iter().map(|span| (span.start, span.end)).collect::<Vec<_>>())
+        };
+        assert_eq!(find(r"shot\d+", false, "Screenshot12.png"), Some(vec![(6, 12)]));
+        assert_eq!(find(r"^make", false, "Makefile"), Some(vec![(0, 4)]));
+        assert_eq!(find(r"^make", true, "Makefile"), None);
+        assert_eq!(find(r"\.png$", true, "a.png.bak"), None);
+        assert_eq!(find(r"o", true, "foo"), Some(vec![(1, 2), (2, 3)]));
+        /* Matches, with nothing to highlight */
+        assert_eq!(find(r"x*", true, "foo"), Some(Vec::new()));
+        assert!(SearchMatcher::regex(r"shot(", false).is_err());
+    }
+
+    #[test]
+    fn search_history_keeps_the_last_distinct_queries() {
+        let mut history = SearchHistory::default();
+        history.push("report");
+        history.push("");
+        history.push("  ");
+        history.push("notes");
+        assert_eq!(history.queries(), ["notes", "report"]);
+
+        /* Searching again moves the query first, with the case it was typed with last */
+        history.push("REPORT");
+        assert_eq!(history.queries(), ["REPORT", "notes"]);
+
+        for i in 0..SEARCH_HISTORY_CAPACITY {
+            history.push(&format!("query {i}"));
+        }
+        assert_eq!(history.queries().len(), SEARCH_HISTORY_CAPACITY);
+        assert_eq!(history.queries()[0], format!("query {}", SEARCH_HISTORY_CAPACITY - 1));
+        assert!(!history.queries().iter().any(|query| query == "notes"));
+
+        history.clear();
+        assert!(history.queries().is_empty());
+    }
+
+    #[test]
+    fn changes_are_due_once_they_stop() {
+        let frame = Duration::from_millis(16);
+        let mut debounce = Debounce::default();
+        assert!(!debounce.tick(frame));
+
+        /* Typing a character every other frame keeps postponing the change */
+        for _ in 0..20 {
+            debounce.changed(SEARCH_DEBOUNCE);
+            assert!(!debounce.tick(frame));
+            assert!(!debounce.tick(frame));
+        }
+        let frames = std::iter::repeat(frame).take(20).map(|frame| debounce.tick(frame));
+        /* 150 ms minus the 32 ms that already passed take 8 frames of 16 ms */
+        assert_eq!(frames.collect::<Vec<_>>().iter().position(|&due| due), Some(7));
+        assert!(!debounce.tick(frame));
+
+        /* A long frame makes it due right away */
+        debounce.changed(SEARCH_DEBOUNCE);
+        assert!(debounce.tick(Duration::from_millis(200)));
+
+        debounce.changed(SEARCH_DEBOUNCE);
+        debounce.cancel();
+        assert!(!debounce.tick(Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,397 @@
+use crate::config::DialogConfig;
+use crate::entry::Entry;
+use std::cmp::Ordering;
+use std::rc::Rc;
+
+#[cfg(feature = "collation")]
+use crate::collation::Collator;
+
+/// Compares two file names the way the listing orders them.
+///
+/// Names are compared case-insensitively first, and names that are equal ignoring case
+/// (`Readme.md` and `README.md`) are then ordered case-sensitively.
+pub(crate) fn compare_names(a: &str, b: &str) -> Ordering {
+    a.chars().flat_map(char::to_lowercase)
+        .cmp(b.chars().flat_map(char::to_lowercase))
+        .then_with(|| a.cmp(b))
+}
+
+/// Compares two file names like [`compare_names()`], except that runs of digits are compared
+/// by their numeric value, so that `frame_2.png` comes before `frame_10.png`.
+///
+/// Numbers differing only by their leading zeros (`img007` and `img7`) are then ordered like
+/// [`compare_names()`] would. Digit runs are compared as text, so they can be of any length.
+pub(crate) fn compare_natural(a: &str, b: &str) -> Ordering {
+    let mut a_chars = a.chars().flat_map(char::to_lowercase).peekable();
+    let mut b_chars = b.chars().flat_map(char::to_lowercase).peekable();
+    loop {
+        let ordering = match (a_chars.peek().copied(), b_chars.peek().copied()) {
+            (None, None) => return compare_names(a, b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let x = take_digits(&mut a_chars);
+                let y = take_digits(&mut b_chars);
+                let (x, y) = (x.trim_start_matches('0'), y.trim_start_matches('0'));
+                x.len().cmp(&y.len()).then_with(|| x.cmp(y))
+            }
+            (Some(x), Some(y)) => {
+                a_chars.next();
+                b_chars.next();
+                x.cmp(&y)
+            }
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Consumes the run of ASCII digits at the start of `chars`.
+fn take_digits(chars: &mut std::iter::Peekable<impl Iterator<Item = char>>) -> String {
+    let mut digits = String::new();
+    while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+        digits.push(digit);
+    }
+    digits
+}
+
+/// Where dotfiles (`.config`, `.gitignore`, ...) are placed in the listing when hidden files are shown.
+///
+/// When directories are listed before files, this applies within each of the two groups.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DotfilesPosition {
+    /// Before every other entry of their group.
+    #[default]
+    First,
+    /// Among the other entries, sorted by their name without the leading dot.
+    Mixed,
+    /// After every other entry of their group.
+    Last,
+}
+
+/// The column the listing is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SortBy {
+    /// The name of the entries.
+    #[default]
+    Name,
+    /// The size of the files. Directories are ordered by name.
+    Size,
+    /// The time the entries were last modified.
+    Modified,
+}
+
+/// The direction the listing is sorted in. Directories listed first stay first either way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SortOrder {
+    /// Smallest, oldest or alphabetically first entries first.
+    #[default]
+    Ascending,
+    /// Largest, newest or alphabetically last entries first.
+    Descending,
+}
+
+/// Compares two file names, set with [`FileDialog::sort_with()`](crate::FileDialog::sort_with).
+pub(crate) type NameComparator = Rc<dyn Fn(&str, &str) -> Ordering>;
+
+/// How the entries of the listing are ordered, derived from the [`DialogConfig`].
+#[derive(Clone, Default)]
+pub(crate) struct SortOptions {
+    pub(crate) by: SortBy,
+    pub(crate) order: SortOrder,
+    pub(crate) dirs_first: bool,
+    /// Compares the numbers in names by value.
+    pub(crate) natural: bool,
+    pub(crate) dotfiles: DotfilesPosition,
+    /// Orders names by the rules of a language instead of by code point.
+    #[cfg(feature = "collation")]
+    pub(crate) collator: Option<Collator>,
+    /// Orders names with a comparator of the application, before any other rule.
+    pub(crate) comparator: Option<NameComparator>,
+}
+
+impl SortOptions {
+    pub(crate) fn new(config: &DialogConfig, comparator: Option<&NameComparator>) -> Self {
+        Self {
+            by: config.sort_by,
+            order: config.sort_order,
+            dirs_first: config.dirs_first,
+            natural: config.natural_sort,
+            dotfiles: config.dotfiles_position,
+            #[cfg(feature = "collation")]
+            collator: config.collation_locale.as_deref().map(Collator::new),
+            comparator: comparator.cloned(),
+        }
+    }
+
+    /// Compares two entries of the listing: directories first unless disabled, then by the sort column, then by name.
+    ///
+    /// Names are compared in their normalized form. Entries whose normalized names are identical
+    /// (the same name in composed and decomposed form, or invalid Unicode replaced during the lossy
+    /// conversion) are ordered by their raw bytes, so the ordering is total and never depends on the
+    /// order `read_dir` yields them in.
+    pub(crate) fn compare_entries(&self, a: &Entry, b: &Entry) -> Ordering {
+        let (a_name, b_name) = (a.display_name.as_str(), b.display_name.as_str());
+        let (a_dotfile, b_dotfile) = (a_name.starts_with('.'), b_name.starts_with('.'));
+        let dotfiles = match self.dotfiles {
+            DotfilesPosition::First => b_dotfile.cmp(&a_dotfile),
+            DotfilesPosition::Last  => a_dotfile.cmp(&b_dotfile),
+            DotfilesPosition::Mixed => Ordering::Equal,
+        };
+        let column = match self.by {
+            SortBy::Name => Ordering::Equal,
+            SortBy::Size => a.size.cmp(&b.size),
+            SortBy::Modified => a.modified.cmp(&b.modified),
+        };
+        let ordering = column
+            .then_with(|| match self.dotfiles {
+                DotfilesPosition::Mixed => self.compare_names(a_name.trim_start_matches('.'), b_name.trim_start_matches('.')),
+                _ => Ordering::Equal,
+            })
+            .then_with(|| self.compare_names(a_name, b_name))
+            .then_with(|| a.name.cmp(&b.name));
+        let ordering = match self.order {
+            SortOrder::Ascending => ordering,
+            SortOrder::Descending => ordering.reverse(),
+        };
+        /* Sorting directories first to make it easier to navigate */
+        let dirs = if self.dirs_first { b.kind.is_dir().cmp(&a.kind.is_dir()) } else { Ordering::Equal };
+        dirs.then(dotfiles)
+            .then(ordering)
+    }
+
+    /// Compares two names with the comparator of the application, falling back to the built-in rules for
+    /// the names it considers equal.
+    fn compare_names(&self, a: &str, b: &str) -> Ordering {
+        match &self.comparator {
+            Some(comparator) => comparator(a, b).then_with(|| self.compare_names_builtin(a, b)),
+            None => self.compare_names_builtin(a, b),
+        }
+    }
+
+    /// Compares two names by the rules set in the configuration: collation locale, natural order or neither.
+    fn compare_names_builtin(&self, a: &str, b: &str) -> Ordering {
+        #[cfg(feature = "collation")]
+        if let Some(collator) = &self.collator {
+            return collator.compare(a, b).then_with(|| compare_names(a, b));
+        }
+        if self.natural {
+            compare_natural(a, b)
+        } else {
+            compare_names(a, b)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::EntryKind;
+    use std::ffi::OsString;
+
+    fn sorted_naturally(names: &[&str]) -> Vec<String> {
+        let mut names: Vec<String> = names.iter().map(|name| name.to_string()).collect();
+        names.sort_by(|a, b| compare_natural(a, b));
+        names
+    }
+
+    #[test]
+    fn digit_runs_compare_by_value() {
+        assert_eq!(
+            sorted_naturally(&["frame_100.png", "frame_10.png", "frame_2.png", "frame_1.png"]),
+            ["frame_1.png", "frame_2.png", "frame_10.png", "frame_100.png"]
+        );
+        assert_eq!(sorted_naturally(&["v1.10.2", "v1.9.12", "v1.10"]), ["v1.9.12", "v1.10", "v1.10.2"]);
+        assert_eq!(compare_natural("a2b", "a2c"), Ordering::Less);
+        assert_eq!(compare_natural("2", "a"), Ordering::Less);
+    }
+
+    #[test]
+    fn long_digit_runs_do_not_overflow() {
+        let small = "scan_99999999999999999999999999999999";
+        let large = "scan_100000000000000000000000000000000";
+        assert_eq!(compare_natural(small, large), Ordering::Less);
+        assert_eq!(compare_natural(large, small), Ordering::Greater);
+    }
+
+    #[test]
+    fn leading_zeros() {
+        /* Same value: the digits decide, consistently in both directions */
+        assert_eq!(compare_natural("img007", "img7"), Ordering::Less);
+        assert_eq!(compare_natural("img7", "img007"), Ordering::Greater);
+        assert_eq!(compare_natural("img007", "img8"), Ordering::Less);
+        assert_eq!(compare_natural("img010", "img9"), Ordering::Greater);
+        assert_eq!(sorted_naturally(&["img7", "img08", "img007", "img6"]), ["img6", "img007", "img7", "img08"]);
+    }
+
+    #[test]
+    fn mixed_case() {
+        assert_eq!(compare_natural("File2", "file10"), Ordering::Less);
+        assert_eq!(compare_natural("beta", "Alpha"), Ordering::Greater);
+        assert_eq!(sorted_naturally(&["b1", "A10", "a2", "B"]), ["a2", "A10", "B", "b1"]);
+    }
+
+    #[test]
+    fn ties_are_broken() {
+        assert_eq!(compare_natural("README.md", "Readme.md"), Ordering::Less);
+        assert_eq!(compare_natural("Readme.md", "README.md"), Ordering::Greater);
+        assert_eq!(compare_natural("Readme.md", "Readme.md"), Ordering::Equal);
+        assert_eq!(compare_natural("", ""), Ordering::Equal);
+        assert_eq!(compare_natural("", "a"), Ordering::Less);
+    }
+
+    /// Names comparing equal under one rule or another: by case, by normalization, by their
+    /// numbers, without their leading dot, or not at all.
+    const ADVERSARIAL_NAMES: [&str; 20] = [
+        "README.md", "Readme.md", "readme.md", "README.MD",
+        "caf\u{e9}", "cafe\u{301}", "Caf\u{e9}",
+        "a2", "a02", "a002", "A2", "a10",
+        ".config", ".Config", "config", "Config",
+        "", "_", "~", "\u{1f4c1}",
+    ];
+
+    fn adversarial_entries() -> Vec<Entry> {
+        let files = ADVERSARIAL_NAMES.iter().map(|name| Entry::fake(name, EntryKind::File));
+        let dirs = ADVERSARIAL_NAMES.iter().map(|name| Entry::fake(&format!("{}-dir", name), EntryKind::Dir));
+        files.chain(dirs).collect()
+    }
+
+    /// Every combination of the options changing how names are compared.
+    fn all_options() -> Vec<SortOptions> {
+        let mut all = Vec::new();
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            for natural in [false, true] {
+                for dirs_first in [false, true] {
+                    for dotfiles in [DotfilesPosition::First, DotfilesPosition::Mixed, DotfilesPosition::Last] {
+                        all.push(SortOptions { order, natural, dirs_first, dotfiles, ..SortOptions::default() });
+                    }
+                }
+            }
+        }
+        all
+    }
+
+    /// Shuffles `entries` with a xorshift generator, so every run of the tests is the same.
+    fn shuffle(entries: &mut [Entry], seed: &mut u64) {
+        for i in (1..entries.len()).rev() {
+            *seed ^= *seed << 13;
+            *seed ^= *seed >> 7;
+            *seed ^= *seed << 17;
+            entries.swap(i, (*seed % (i as u64 + 1)) as usize);
+        }
+    }
+
+    fn names(entries: &[Entry]) -> Vec<OsString> {
+        entries.iter().map(|entry| entry.name.clone()).collect()
+    }
+
+    #[test]
+    fn ordering_is_total() {
+        let entries = adversarial_entries();
+        for options in all_options() {
+            for a in &entries {
+                for b in &entries {
+                    let ordering = options.compare_entries(a, b);
+                    assert_eq!(ordering == Ordering::Equal, a.name == b.name, "{:?} and {:?}", a.name, b.name);
+                    assert_eq!(ordering, options.compare_entries(b, a).reverse());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn order_does_not_depend_on_the_read_order() {
+        let mut seed = 0x9e37_79b9_7f4a_7c15;
+        for options in all_options() {
+            let mut expected = adversarial_entries();
+            expected.sort_by(|a, b| options.compare_entries(a, b));
+            for _ in 0..20 {
+                let mut entries = adversarial_entries();
+                shuffle(&mut entries, &mut seed);
+                entries.sort_by(|a, b| options.compare_entries(a, b));
+                assert_eq!(names(&entries), names(&expected));
+            }
+        }
+    }
+
+    #[test]
+    fn refreshing_keeps_the_order() {
+        /* A refresh sorts the new listing, which may come with entries created since */
+        let mut seed = 42;
+        for options in all_options() {
+            let mut expected = adversarial_entries();
+            expected.sort_by(|a, b| options.compare_entries(a, b));
+            let mut entries = adversarial_entries();
+            let added = entries.split_off(entries.len() / 2);
+            entries.sort_by(|a, b| options.compare_entries(a, b));
+            entries.extend(added);
+            shuffle(&mut entries, &mut seed);
+            entries.sort_by(|a, b| options.compare_entries(a, b));
+            assert_eq!(names(&entries), names(&expected));
+            entries.sort_by(|a, b| options.compare_entries(a, b));
+            assert_eq!(names(&entries), names(&expected));
+        }
+    }
+
+    #[test]
+    fn names_equal_once_normalized_are_ordered_by_bytes() {
+        let options = SortOptions::default();
+        let (composed, decomposed) = (Entry::fake("caf\u{e9}", EntryKind::File), Entry::fake("cafe\u{301}", EntryKind::File));
+        assert_eq!(composed.display_name, decomposed.display_name);
+        assert_eq!(options.compare_entries(&decomposed, &composed), Ordering::Less);
+    }
+
+    fn sorted_listing(dirs_first: bool, dotfiles: DotfilesPosition) -> Vec<String> {
+        let options = SortOptions { dirs_first, dotfiles, ..SortOptions::default() };
+        let mut entries = vec![
+            Entry::fake("src", EntryKind::Dir),
+            Entry::fake("b.txt", EntryKind::File),
+            Entry::fake(".gitignore", EntryKind::File),
+            Entry::fake(".git", EntryKind::Dir),
+            Entry::fake("a.txt", EntryKind::File),
+            Entry::fake(".env", EntryKind::File),
+        ];
+        entries.sort_by(|a, b| options.compare_entries(a, b));
+        entries.into_iter().map(|entry| entry.display_name).collect()
+    }
+
+    #[test]
+    fn dotfiles_within_dirs_and_files() {
+        assert_eq!(sorted_listing(true, DotfilesPosition::First), [".git", "src", ".env", ".gitignore", "a.txt", "b.txt"]);
+        assert_eq!(sorted_listing(true, DotfilesPosition::Mixed), [".git", "src", "a.txt", "b.txt", ".env", ".gitignore"]);
+        assert_eq!(sorted_listing(true, DotfilesPosition::Last), ["src", ".git", "a.txt", "b.txt", ".env", ".gitignore"]);
+    }
+
+    #[test]
+    fn dotfiles_without_dirs_first() {
+        assert_eq!(sorted_listing(false, DotfilesPosition::First), [".env", ".git", ".gitignore", "a.txt", "b.txt", "src"]);
+        assert_eq!(sorted_listing(false, DotfilesPosition::Mixed), ["a.txt", "b.txt", ".env", ".git", ".gitignore", "src"]);
+        assert_eq!(sorted_listing(false, DotfilesPosition::Last), ["a.txt", "b.txt", "src", ".env", ".git", ".gitignore"]);
+    }
+
+    #[test]
+    fn mixed_dotfiles_next_to_their_namesake() {
+        let options = SortOptions { dotfiles: DotfilesPosition::Mixed, ..SortOptions::default() };
+        let (dotfile, file) = (Entry::fake(".config", EntryKind::File), Entry::fake("config", EntryKind::File));
+        assert_eq!(options.compare_entries(&dotfile, &file), Ordering::Less);
+        assert_eq!(options.compare_entries(&file, &Entry::fake(".configure", EntryKind::File)), Ordering::Less);
+    }
+
+    #[test]
+    fn comparator_ties_follow_the_configured_rules() {
+        /* Orders names by their first letter only, so that every frame is a tie */
+        let comparator: NameComparator = Rc::new(|a: &str, b: &str| a[..1].cmp(&b[..1]));
+        let sorted = |natural: bool| {
+            let options = SortOptions { natural, comparator: Some(comparator.clone()), ..SortOptions::default() };
+            let mut entries: Vec<_> = ["frame_10", "frame_2", "b"].iter().map(|name| Entry::fake(name, EntryKind::File)).collect();
+            entries.sort_by(|a, b| options.compare_entries(a, b));
+            entries.into_iter().map(|entry| entry.display_name).collect::<Vec<_>>()
+        };
+        assert_eq!(sorted(true), ["b", "frame_2", "frame_10"]);
+        assert_eq!(sorted(false), ["b", "frame_10", "frame_2"]);
+    }
+}
@@ -0,0 +1,56 @@
+use std::path::PathBuf;
+
+/// What the user was doing in a [`FileDialog`](crate::FileDialog), to pick up where they left off after the
+/// application restarts.
+///
+/// [`DialogConfig`](crate::DialogConfig) holds how the dialog is set up; this holds what the user changed while
+/// using it. Take it with [`FileDialog::save_state_full()`](crate::FileDialog::save_state_full) and give it back
+/// with [`FileDialog::apply_state()`](crate::FileDialog::apply_state), which checks every path first: the
+/// filesystem may have changed in between. With the `serde` feature enabled, the struct can be serialized, and
+/// deserialized with missing fields taking their default value.
+///
+/// ```no_run
+/// use imfile::FileDialog;
+///
+/// let dialog = FileDialog::new();
+/// let state = dialog.save_state_full();
+/// // After a restart
+/// let mut dialog = FileDialog::new();
+/// dialog.apply_state(state);
+/// ```
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct DialogState {
+    /// The directory displayed. When it is gone, the dialog opens in its closest parent that can be listed.
+    pub current_dir: PathBuf,
+    /// The selected entries of a multi-select dialog. Entries that are gone or aren't in the directory are left out.
+    pub selection: Vec<PathBuf>,
+    /// The entry clicked last, left out like the selection.
+    pub highlighted: Option<PathBuf>,
+    /// The query of the search box.
+    pub search: String,
+    /// The last queries of the search box, most recent first.
+    pub search_history: Vec<String>,
+    /// The width of the preview pane. Widths too small to be dragged back are replaced by the default one.
+    pub preview_width: f32,
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serde_round_trip() {
+        let state = DialogState {
+            current_dir: PathBuf::from("/home/me/Pictures"),
+            selection: vec![PathBuf::from("/home/me/Pictures/cat.png")],
+            highlighted: None,
+            search: String::from("cat"),
+            search_history: vec![String::from("dog")],
+            preview_width: 250.0,
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(serde_json::from_str::<DialogState>(&json).unwrap(), state);
+        assert_eq!(serde_json::from_str::<DialogState>("{}").unwrap(), DialogState::default());
+    }
+}
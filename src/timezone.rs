@@ -0,0 +1,333 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, UNIX_EPOCH};
+use crate::entry::utc_date;
+
+/// Where the tz database keeps the zones on Linux and the BSDs.
+const ZONEINFO: &str = "/usr/share/zoneinfo";
+
+/// Returns the offset of the local time zone from UTC at `time`, in seconds since the Unix epoch.
+/// The offset is in seconds east of UTC, and 0 if the zone can't be found.
+///
+/// The zone is found the way the C library does on Unix: `$TZ` if it is set, either a POSIX rule like
+/// `CET-1CEST,M3.5.0,M10.5.0/3` or the name of a zone of the tz database, and `/etc/localtime` otherwise.
+pub(crate) fn local_offset(time: i64) -> i64 {
+    let zone = match std::env::var("TZ") {
+        Ok(tz) => {
+            let tz = tz.strip_prefix(':').unwrap_or(&tz);
+            if tz.is_empty() {
+                return 0;
+            }
+            if let Some(offset) = posix_offset(tz, time) {
+                return offset;
+            }
+            let path = if tz.starts_with('/') { PathBuf::from(tz) } else { Path::new(ZONEINFO).join(tz) };
+            fs::read(path)
+        }
+        Err(_) => fs::read("/etc/localtime"),
+    };
+    zone.ok().and_then(|zone| tzif_offset(&zone, time)).unwrap_or(0)
+}
+
+/// Returns the offset from UTC at `time` given by a TZif file, the format of the zones of the tz database
+/// ([RFC 8536](https://www.rfc-editor.org/rfc/rfc8536)).
+fn tzif_offset(data: &[u8], time: i64) -> Option<i64> {
+    let (version, counts, body) = tzif_header(data)?;
+    let (time_size, counts, body, footer) = if version >= b'2' {
+        /* The first block only has 32-bit times, the second one repeats it with 64-bit times */
+        let (_, counts, body) = tzif_header(body.get(block_len(&counts, 4)..)?)?;
+        (8, counts, body, body.get(block_len(&counts, 8)..)?)
+    } else {
+        (4, counts, body, &[][..])
+    };
+    let [_, _, _, transition_count, type_count, _] = counts;
+    let indices_start = transition_count * time_size;
+    let types_start = indices_start + transition_count;
+    let passed = (0..transition_count)
+        .take_while(|&i| body.get(i * time_size..).and_then(|bytes| read_int(bytes, time_size)).is_some_and(|at| at <= time))
+        .count();
+    /* The rule of the footer applies after the last transition */
+    if passed == transition_count {
+        let rule = footer.strip_prefix(b"\n").and_then(|footer| footer.split(|&byte| byte == b'\n').next());
+        if let Some(offset) = rule.and_then(|rule| std::str::from_utf8(rule).ok()).and_then(|rule| posix_offset(rule, time)) {
+            return Some(offset);
+        }
+    }
+    /* Times before the first transition use the first type */
+    let index = match passed {
+        0 => 0,
+        passed => usize::from(*body.get(indices_start + passed - 1)?),
+    };
+    if index >= type_count {
+        return None;
+    }
+    read_int(body.get(types_start + index * 6..)?, 4)
+}
+
+/// Reads the header of a block of a TZif file. Returns the version, the counts of the block
+/// (`isutcnt`, `isstdcnt`, `leapcnt`, `timecnt`, `typecnt` and `charcnt`) and what follows.
+fn tzif_header(data: &[u8]) -> Option<(u8, [usize; 6], &[u8])> {
+    if data.get(..4)? != b"TZif" {
+        return None;
+    }
+    let mut counts = [0; 6];
+    for (i, count) in counts.iter_mut().enumerate() {
+        *count = usize::try_from(read_int(data.get(20 + i * 4..)?, 4)?).ok()?;
+    }
+    Some((*data.get(4)?, counts, data.get(44..)?))
+}
+
+/// Returns the length of the data of a block of a TZif file, after its header.
+fn block_len(counts: &[usize; 6], time_size: usize) -> usize {
+    let [utc_count, std_count, leap_count, transition_count, type_count, char_count] = *counts;
+    transition_count * (time_size + 1) + type_count * 6 + char_count + leap_count * (time_size + 4) + std_count + utc_count
+}
+
+/// Reads a big-endian signed integer of `size` bytes, 4 or 8, at the start of `bytes`.
+fn read_int(bytes: &[u8], size: usize) -> Option<i64> {
+    let bytes = bytes.get(..size)?;
+    match size {
+        4 => Some(i64::from(i32::from_be_bytes(bytes.try_into().ok()?))),
+        _ => Some(i64::from_be_bytes(bytes.try_into().ok()?)),
+    }
+}
+
+/// Returns the offset from UTC at `time` given by a POSIX time zone rule, like `CET-1CEST,M3.5.0,M10.5.0/3`,
+/// or `None` if `rule` isn't one.
+fn posix_offset(rule: &str, time: i64) -> Option<i64> {
+    let mut rest = rule;
+    zone_name(&mut rest)?;
+    /* POSIX offsets count the time west of UTC */
+    let std = -signed_time(&mut rest)?;
+    if rest.is_empty() {
+        return Some(std);
+    }
+    zone_name(&mut rest)?;
+    let dst = match rest.is_empty() || rest.starts_with(',') {
+        true => std + 3600,
+        false => -signed_time(&mut rest)?,
+    };
+    let (start, end) = match rest.strip_prefix(',') {
+        Some(rules) => rules.split_once(',')?,
+        /* POSIX leaves the default to the implementation, the C library uses the rules of the United States */
+        None if rest.is_empty() => ("M3.2.0", "M11.1.0"),
+        None => return None,
+    };
+    let (year, ..) = utc_date(UNIX_EPOCH + Duration::from_secs(u64::try_from(time + std).ok()?))?;
+    /* Daylight saving time starts at a standard time, and ends at a daylight saving time */
+    let start = transition(start, year)? - std;
+    let end = transition(end, year)? - dst;
+    let daylight = match start < end {
+        true => start <= time && time < end,
+        /* On the southern hemisphere, daylight saving time spans the new year */
+        false => !(end <= time && time < start),
+    };
+    Some(if daylight { dst } else { std })
+}
+
+/// Consumes the name of a zone at the start of `rule`, either three letters or more, or anything between `<>`.
+fn zone_name<'a>(rule: &mut &'a str) -> Option<&'a str> {
+    let (name, rest) = match rule.strip_prefix('<') {
+        Some(quoted) => {
+            let (name, rest) = quoted.split_once('>')?;
+            (name, rest)
+        }
+        None => rule.split_at(rule.find(|c: char| !c.is_ascii_alphabetic()).unwrap_or(rule.len())),
+    };
+    if name.len() < 3 {
+        return None;
+    }
+    *rule = rest;
+    Some(name)
+}
+
+/// Consumes a time like `-5`, `+5:30` or `2:00:00` at the start of `rule`, returned in seconds.
+fn signed_time(rule: &mut &str) -> Option<i64> {
+    let (sign, rest) = match rule.as_bytes().first() {
+        Some(b'-') => (-1, &rule[1..]),
+        Some(b'+') => (1, &rule[1..]),
+        _ => (1, *rule),
+    };
+    let end = rest.find(|c: char| !c.is_ascii_digit() && c != ':').unwrap_or(rest.len());
+    let (time, rest) = rest.split_at(end);
+    let mut seconds = 0;
+    let mut parts = time.split(':');
+    for unit in [3600, 60, 1] {
+        match parts.next() {
+            Some(part) => seconds += part.parse::<i64>().ok()? * unit,
+            None if unit == 3600 => return None,
+            None => break,
+        }
+    }
+    if parts.next().is_some() {
+        return None;
+    }
+    *rule = rest;
+    Some(sign * seconds)
+}
+
+/// Returns when a transition rule like `M3.5.0/3` happens in `year`, in seconds since the Unix epoch,
+/// as if the local time was UTC.
+fn transition(rule: &str, year: i64) -> Option<i64> {
+    let (date, time) = match rule.split_once('/') {
+        Some((date, mut time)) => {
+            let seconds = signed_time(&mut time)?;
+            if !time.is_empty() {
+                return None;
+            }
+            (date, seconds)
+        }
+        None => (rule, 2 * 3600),
+    };
+    let january_first = days_from_civil(year, 1, 1);
+    let leap = days_from_civil(year + 1, 1, 1) - january_first == 366;
+    let day = if let Some(date) = date.strip_prefix('M') {
+        /* The day of the week of the given week of the month, 5 being the last one */
+        let mut fields = date.split('.').map(|field| field.parse::<i64>().ok());
+        let (month, week, weekday) = (fields.next()??, fields.next()??, fields.next()??);
+        if !(1..=12).contains(&month) || !(1..=5).contains(&week) || !(0..=6).contains(&weekday) || fields.next().is_some() {
+            return None;
+        }
+        let first = days_from_civil(year, month, 1);
+        let next_month = if month == 12 { days_from_civil(year + 1, 1, 1) } else { days_from_civil(year, month + 1, 1) };
+        /* The Unix epoch was a Thursday */
+        let mut day = first + (weekday - (first + 4).rem_euclid(7)).rem_euclid(7) + (week - 1) * 7;
+        while day >= next_month {
+            day -= 7;
+        }
+        day
+    } else if let Some(julian) = date.strip_prefix('J') {
+        /* From 1 to 365, never counting February 29 */
+        let julian = julian.parse::<i64>().ok().filter(|julian| (1..=365).contains(julian))?;
+        january_first + julian - 1 + i64::from(leap && julian >= 60)
+    } else {
+        let day = date.parse::<i64>().ok().filter(|day| (0..=365).contains(day))?;
+        january_first + day
+    };
+    Some(day * 86400 + time)
+}
+
+/// Returns the number of days between the Unix epoch and a date, see
+/// <http://howardhinnant.github.io/date_algorithms.html>.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the seconds since the Unix epoch of a UTC date and time.
+    fn at(year: i64, month: i64, day: i64, hour: i64, minute: i64, second: i64) -> i64 {
+        days_from_civil(year, month, day) * 86400 + hour * 3600 + minute * 60 + second
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11017);
+        assert_eq!(days_from_civil(1969, 12, 31), -1);
+        let (year, month, day, _) = utc_date(UNIX_EPOCH + Duration::from_secs(at(2024, 2, 29, 12, 0, 0) as u64)).unwrap();
+        assert_eq!((year, month, day), (2024, 2, 29));
+    }
+
+    #[test]
+    fn fixed_offsets() {
+        assert_eq!(posix_offset("UTC0", 0), Some(0));
+        assert_eq!(posix_offset("<+0530>-5:30", 0), Some(5 * 3600 + 30 * 60));
+        assert_eq!(posix_offset("HST10", 0), Some(-10 * 3600));
+        assert_eq!(posix_offset("Europe/Paris", 0), None);
+        assert_eq!(posix_offset("UTC", 0), None);
+    }
+
+    #[test]
+    fn daylight_saving_time() {
+        let paris = "CET-1CEST,M3.5.0,M10.5.0/3";
+        assert_eq!(posix_offset(paris, at(2024, 1, 15, 12, 0, 0)), Some(3600));
+        assert_eq!(posix_offset(paris, at(2024, 7, 1, 12, 0, 0)), Some(7200));
+        /* March 31 and October 27, at 01:00 UTC */
+        assert_eq!(posix_offset(paris, at(2024, 3, 31, 0, 59, 59)), Some(3600));
+        assert_eq!(posix_offset(paris, at(2024, 3, 31, 1, 0, 0)), Some(7200));
+        assert_eq!(posix_offset(paris, at(2024, 10, 27, 0, 59, 59)), Some(7200));
+        assert_eq!(posix_offset(paris, at(2024, 10, 27, 1, 0, 0)), Some(3600));
+        /* Without rules, the ones of the United States: March 10 and November 3 in 2024 */
+        assert_eq!(posix_offset("EST5EDT", at(2024, 3, 10, 6, 59, 59)), Some(-5 * 3600));
+        assert_eq!(posix_offset("EST5EDT", at(2024, 3, 10, 7, 0, 0)), Some(-4 * 3600));
+        assert_eq!(posix_offset("EST5EDT", at(2024, 11, 3, 6, 0, 0)), Some(-5 * 3600));
+    }
+
+    #[test]
+    fn southern_daylight_saving_time() {
+        let sydney = "AEST-10AEDT,M10.1.0,M4.1.0/3";
+        assert_eq!(posix_offset(sydney, at(2024, 1, 15, 0, 0, 0)), Some(11 * 3600));
+        assert_eq!(posix_offset(sydney, at(2024, 7, 1, 0, 0, 0)), Some(10 * 3600));
+        assert_eq!(posix_offset(sydney, at(2024, 12, 31, 23, 0, 0)), Some(11 * 3600));
+    }
+
+    #[test]
+    fn julian_day_rules() {
+        /* Day 60 is March 1, even in leap years */
+        assert_eq!(transition("J60", 2024), Some(at(2024, 3, 1, 2, 0, 0)));
+        assert_eq!(transition("59", 2024), Some(at(2024, 2, 29, 2, 0, 0)));
+        assert_eq!(transition("M2.5.4/-1", 2024), Some(at(2024, 2, 28, 23, 0, 0)));
+        assert_eq!(transition("M13.1.0", 2024), None);
+    }
+
+    /// Builds a TZif file with the given transitions (time and type) and offsets of the types.
+    /// Files of version 2 repeat the transitions with 64-bit times and end with `footer`.
+    fn tzif(version: u8, transitions: &[(i64, u8)], offsets: &[i32], footer: &str) -> Vec<u8> {
+        let block = |data: &mut Vec<u8>, time_size: usize| {
+            data.extend_from_slice(b"TZif");
+            data.push(version);
+            data.extend_from_slice(&[0; 15]);
+            for count in [0, 0, 0, transitions.len(), offsets.len(), 1] {
+                data.extend_from_slice(&(count as u32).to_be_bytes());
+            }
+            for &(time, _) in transitions {
+                match time_size {
+                    4 => data.extend_from_slice(&(time as i32).to_be_bytes()),
+                    _ => data.extend_from_slice(&time.to_be_bytes()),
+                }
+            }
+            data.extend(transitions.iter().map(|&(_, index)| index));
+            for offset in offsets {
+                data.extend_from_slice(&offset.to_be_bytes());
+                data.extend_from_slice(&[0, 0]);
+            }
+            data.push(0);
+        };
+        let mut data = Vec::new();
+        block(&mut data, 4);
+        if version >= b'2' {
+            block(&mut data, 8);
+            data.extend_from_slice(format!("\n{}\n", footer).as_bytes());
+        }
+        data
+    }
+
+    #[test]
+    fn tzif_transitions() {
+        let data = tzif(0, &[(1000, 1), (2000, 0)], &[3600, 7200], "");
+        assert_eq!(tzif_offset(&data, 0), Some(3600));
+        assert_eq!(tzif_offset(&data, 1000), Some(7200));
+        assert_eq!(tzif_offset(&data, 1999), Some(7200));
+        assert_eq!(tzif_offset(&data, 5000), Some(3600));
+    }
+
+    #[test]
+    fn tzif_footer_applies_after_the_last_transition() {
+        let data = tzif(b'2', &[(1000, 1)], &[3600, 7200], "<+03>-3");
+        assert_eq!(tzif_offset(&data, 999), Some(3600));
+        assert_eq!(tzif_offset(&data, 1000), Some(3 * 3600));
+        /* Without a usable footer, the last transition stays in effect */
+        let data = tzif(b'2', &[(1000, 1)], &[3600, 7200], "");
+        assert_eq!(tzif_offset(&data, 1000), Some(7200));
+        assert_eq!(tzif_offset(b"TZif", 0), None);
+        assert_eq!(tzif_offset(b"not a time zone", 0), None);
+    }
+}
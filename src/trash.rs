@@ -0,0 +1,296 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::entry::utc_date;
+use crate::places::home_dir;
+use crate::timezone::local_offset;
+
+/// Where an entry moved to the trash went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Trashed {
+    /// The entry itself, in the `files` directory of the trash.
+    file: PathBuf,
+    /// Its `.trashinfo` file, in the `info` directory of the trash.
+    info: PathBuf,
+}
+
+/// Moves `path` to the trash of the current user, following the
+/// [FreeDesktop.org trash specification](https://specifications.freedesktop.org/trash-spec/trashspec-latest.html).
+/// Returns where it went, or a message explaining what went wrong on failure, in which case `path` is left untouched.
+///
+/// Only Linux and the other Unix desktops following the specification have a trash this can use.
+pub(crate) fn move_to_trash(path: &Path) -> Result<Trashed, String> {
+    if cfg!(any(windows, target_os = "macos")) {
+        return Err(String::from("Cannot move to the trash: not supported on this platform"));
+    }
+    let trash = trash_dir().ok_or_else(|| String::from("Cannot move to the trash: no home directory"))?;
+    let path = std::path::absolute(path).map_err(|err| format!("Cannot move to the trash: {}", err.kind()))?;
+    trash_entry(&trash, &path).map_err(|err| format!("Cannot move to the trash: {}", err.kind()))
+}
+
+/// Moves an entry moved to the trash back to `path`, where it was, and forgets about it in the trash.
+/// Returns a message explaining what went wrong on failure: the entry may have been emptied from the trash
+/// or restored already, and something else may be at `path` now.
+pub(crate) fn restore(trashed: &Trashed, path: &Path) -> Result<(), String> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    if fs::symlink_metadata(&trashed.file).is_err() {
+        return Err(format!("\"{}\" is no longer in the trash", name));
+    }
+    if fs::symlink_metadata(path).is_ok() {
+        return Err(format!("\"{}\" already exists", name));
+    }
+    move_entry(&trashed.file, path).map_err(|err| format!("Cannot restore: {}", err.kind()))?;
+    let _ = fs::remove_file(&trashed.info);
+    Ok(())
+}
+
+/// Returns whether this platform has a trash entries can be moved to.
+pub(crate) fn is_available() -> bool {
+    !cfg!(any(windows, target_os = "macos")) && trash_dir().is_some()
+}
+
+/// Returns the trash of the home directory, `$XDG_DATA_HOME/Trash`.
+fn trash_dir() -> Option<PathBuf> {
+    let data_dir = std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| home_dir().map(|home| home.join(".local").join("share")))?;
+    Some(data_dir.join("Trash"))
+}
+
+fn trash_entry(trash: &Path, path: &Path) -> io::Result<Trashed> {
+    let (files, info) = (trash.join("files"), trash.join("info"));
+    fs::create_dir_all(&files)?;
+    fs::create_dir_all(&info)?;
+    let name = path.file_name().ok_or(io::ErrorKind::InvalidInput)?;
+
+    /* Creating the info file first reserves the name, as the specification requires */
+    let mut attempt = 1;
+    let (trashed, info_file) = loop {
+        let mut trashed_name = name.to_os_string();
+        if attempt > 1 {
+            trashed_name.push(format!(".{}", attempt));
+        }
+        let mut info_name = trashed_name.clone();
+        info_name.push(".trashinfo");
+        let info_file = info.join(info_name);
+        match fs::OpenOptions::new().write(true).create_new(true).open(&info_file) {
+            Ok(_) if !files.join(&trashed_name).exists() => break (files.join(trashed_name), info_file),
+            Ok(_) => fs::remove_file(&info_file)?,
+            Err(err) if err.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(err) => return Err(err),
+        }
+        attempt += 1;
+    };
+    let result = fs::write(&info_file, trash_info(path, local_now())).and_then(|_| move_entry(path, &trashed));
+    /* A copy left behind by a failed move still needs its info file to be restored or emptied */
+    if result.is_err() && fs::symlink_metadata(&trashed).is_err() {
+        let _ = fs::remove_file(&info_file);
+    }
+    result.map(|_| Trashed { file: trashed, info: info_file })
+}
+
+/// Returns the current time shifted to the local time zone, as the `DeletionDate` key requires.
+fn local_now() -> SystemTime {
+    let now = SystemTime::now();
+    let offset = local_offset(now.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs() as i64));
+    match u64::try_from(offset) {
+        Ok(offset) => now + Duration::from_secs(offset),
+        Err(_) => now - Duration::from_secs(offset.unsigned_abs()),
+    }
+}
+
+/// Returns the contents of the `.trashinfo` file of `path`, deleted at the local time `deleted`.
+fn trash_info(path: &Path, deleted: SystemTime) -> String {
+    let date = match utc_date(deleted) {
+        Some((year, month, day, seconds)) => format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year, month, day, seconds / 3600, seconds % 3600 / 60, seconds % 60
+        ),
+        None => String::new(),
+    };
+    format!("[Trash Info]\nPath={}\nDeletionDate={}\n", percent_encode(path), date)
+}
+
+/// Escapes a path the way URLs are, as the `Path` key of `.trashinfo` files requires.
+fn percent_encode(path: &Path) -> String {
+    let mut encoded = String::new();
+    for &byte in path.as_os_str().as_encoded_bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Moves an entry, copying it when the trash is on another filesystem.
+fn move_entry(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(_) if crosses_devices(from, to) => {
+            if let Err(err) = copy_entry(from, to) {
+                let _ = remove_entry(to);
+                return Err(err);
+            }
+            let is_dir = fs::symlink_metadata(from)?.is_dir();
+            let result = remove_entry(from);
+            /* Removing a file either happens or not, but a directory may be partly gone already, and then the copy
+             * in the trash is the only complete one */
+            if result.is_err() && !is_dir {
+                let _ = remove_entry(to);
+            }
+            result
+        }
+        result => result,
+    }
+}
+
+/// Returns whether `from` is on another filesystem than the directory of `to`, so it can't be renamed there.
+#[cfg(unix)]
+fn crosses_devices(from: &Path, to: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    let parent = to.parent().unwrap_or(to);
+    match (fs::symlink_metadata(from), fs::metadata(parent)) {
+        (Ok(from), Ok(parent)) => from.dev() != parent.dev(),
+        _ => false,
+    }
+}
+
+/// Only reached on platforms with a trash, which are all Unix.
+#[cfg(not(unix))]
+fn crosses_devices(_from: &Path, _to: &Path) -> bool {
+    false
+}
+
+/// Copies an entry, and everything in it for directories. Symbolic links are copied as links.
+fn copy_entry(from: &Path, to: &Path) -> io::Result<()> {
+    let file_type = fs::symlink_metadata(from)?.file_type();
+    if file_type.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_entry(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else if file_type.is_symlink() {
+        copy_symlink(from, to)
+    } else {
+        fs::copy(from, to).map(|_| ())
+    }
+}
+
+#[cfg(unix)]
+fn copy_symlink(from: &Path, to: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(fs::read_link(from)?, to)
+}
+
+/// Only reached on platforms with a trash, which are all Unix.
+#[cfg(not(unix))]
+fn copy_symlink(_from: &Path, _to: &Path) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+fn remove_entry(path: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(path)?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns an empty directory for `test` in the temporary directory.
+    fn scratch_dir(test: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("imfile-trash-{}-{}", test, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn paths_are_percent_encoded() {
+        assert_eq!(percent_encode(Path::new("/home/me/My File \u{fc}%.txt")), "/home/me/My%20File%20%C3%BC%25.txt");
+        assert_eq!(percent_encode(Path::new("/tmp/a-b_c.d~")), "/tmp/a-b_c.d~");
+    }
+
+    #[test]
+    fn info_files_record_the_path_and_local_date() {
+        /* 2024-07-01T12:34:56 */
+        let deleted = UNIX_EPOCH + Duration::from_secs(1_719_837_296);
+        assert_eq!(
+            trash_info(Path::new("/home/me/a b.txt"), deleted),
+            "[Trash Info]\nPath=/home/me/a%20b.txt\nDeletionDate=2024-07-01T12:34:56\n"
+        );
+    }
+
+    #[test]
+    fn colliding_names_are_numbered() {
+        let dir = scratch_dir("collisions");
+        let trash = dir.join("Trash");
+        for folder in ["a", "b"] {
+            fs::create_dir(dir.join(folder)).unwrap();
+            fs::write(dir.join(folder).join("notes.txt"), folder).unwrap();
+            trash_entry(&trash, &dir.join(folder).join("notes.txt")).unwrap();
+            assert!(!dir.join(folder).join("notes.txt").exists());
+        }
+        assert_eq!(fs::read_to_string(trash.join("files/notes.txt")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(trash.join("files/notes.txt.2")).unwrap(), "b");
+        let info = fs::read_to_string(trash.join("info/notes.txt.2.trashinfo")).unwrap();
+        assert!(info.contains(&format!("Path={}\n", percent_encode(&dir.join("b/notes.txt")))));
+        assert!(trash.join("info/notes.txt.trashinfo").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn info_files_are_removed_when_the_move_fails() {
+        let dir = scratch_dir("failed-move");
+        let trash = dir.join("Trash");
+        assert!(trash_entry(&trash, &dir.join("missing.txt")).is_err());
+        assert_eq!(fs::read_dir(trash.join("info")).unwrap().count(), 0);
+        assert_eq!(fs::read_dir(trash.join("files")).unwrap().count(), 0);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    #[cfg(all(unix, not(target_os = "macos")))]
+    fn entries_go_to_the_trash_of_the_data_directory() {
+        let dir = scratch_dir("data-home");
+        fs::write(dir.join("old.txt"), "old").unwrap();
+        std::env::set_var("XDG_DATA_HOME", &dir);
+        let result = move_to_trash(&dir.join("old.txt"));
+        std::env::remove_var("XDG_DATA_HOME");
+        result.unwrap();
+        assert!(!dir.join("old.txt").exists());
+        assert_eq!(fs::read_to_string(dir.join("Trash/files/old.txt")).unwrap(), "old");
+        assert!(dir.join("Trash/info/old.txt.trashinfo").exists());
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn entries_are_restored_where_they_were() {
+        let dir = scratch_dir("restore");
+        let trash = dir.join("Trash");
+        fs::write(dir.join("notes.txt"), "notes").unwrap();
+        let trashed = trash_entry(&trash, &dir.join("notes.txt")).unwrap();
+        assert_eq!(trashed.file, trash.join("files/notes.txt"));
+
+        /* Something took its place meanwhile */
+        fs::write(dir.join("notes.txt"), "other").unwrap();
+        assert!(restore(&trashed, &dir.join("notes.txt")).is_err());
+        fs::remove_file(dir.join("notes.txt")).unwrap();
+
+        restore(&trashed, &dir.join("notes.txt")).unwrap();
+        assert_eq!(fs::read_to_string(dir.join("notes.txt")).unwrap(), "notes");
+        assert!(!trashed.file.exists());
+        assert!(!trashed.info.exists());
+
+        /* Already restored */
+        fs::remove_file(dir.join("notes.txt")).unwrap();
+        assert!(restore(&trashed, &dir.join("notes.txt")).is_err());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,351 @@
+//! Uses every public item of the crate, the way applications do, so that moving, renaming or
+//! changing one of them breaks this test before it breaks them. Nothing is drawn: the test only
+//! has to compile, and the few calls it makes don't need an imgui context.
+//!
+//! Enums are matched without wildcards and structs are built field by field, so adding a variant
+//! or a field shows up here too. Update this file along with any intended change of the API.
+
+use std::cmp::Ordering;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "icons")]
+use imfile::IconSet;
+use imfile::{
+    AcceptState, CommonPlaces, ConfigError, DialogConfig, DialogMemory, DialogMode, DialogResult, DialogState,
+    DotfilesPosition, DrawStats, Entry, EntryKind, FileDialog, FileFilter, NavEvent, NavTarget, NavigationLog,
+    OptionCheckbox, Place, PlaceIcon, PlaceSection, PlacesProvider, SortBy, SortOrder, ViewMode,
+};
+
+/// Compiles only if both types are the same.
+fn same_type<T: ?Sized>(_: PhantomData<T>, _: PhantomData<T>) {}
+
+#[test]
+fn prelude_matches_the_root() {
+    use imfile::prelude as p;
+    same_type(PhantomData::<p::ConfigError>, PhantomData::<ConfigError>);
+    same_type(PhantomData::<p::DialogConfig>, PhantomData::<DialogConfig>);
+    same_type(PhantomData::<p::DialogMode>, PhantomData::<DialogMode>);
+    same_type(PhantomData::<p::OptionCheckbox>, PhantomData::<OptionCheckbox>);
+    same_type(PhantomData::<p::ViewMode>, PhantomData::<ViewMode>);
+    same_type(PhantomData::<p::EntryKind>, PhantomData::<EntryKind>);
+    same_type(PhantomData::<p::AcceptState>, PhantomData::<AcceptState>);
+    same_type(PhantomData::<p::DialogResult>, PhantomData::<DialogResult>);
+    same_type(PhantomData::<p::FileDialog>, PhantomData::<FileDialog>);
+    same_type(PhantomData::<p::FileFilter>, PhantomData::<FileFilter>);
+    same_type(PhantomData::<p::DialogMemory>, PhantomData::<DialogMemory>);
+    same_type(PhantomData::<p::Place>, PhantomData::<Place>);
+    same_type(PhantomData::<p::PlaceIcon>, PhantomData::<PlaceIcon>);
+    same_type(PhantomData::<p::PlaceSection>, PhantomData::<PlaceSection>);
+    same_type(PhantomData::<dyn p::PlacesProvider>, PhantomData::<dyn PlacesProvider>);
+    same_type(PhantomData::<p::DotfilesPosition>, PhantomData::<DotfilesPosition>);
+    same_type(PhantomData::<p::SortBy>, PhantomData::<SortBy>);
+    same_type(PhantomData::<p::SortOrder>, PhantomData::<SortOrder>);
+}
+
+/// Draws the dialog, the two ways applications do.
+#[allow(dead_code)]
+fn spawn(ui: &imgui::Ui, dialog: &mut FileDialog) {
+    let _: DialogResult = dialog.spawn_borrowed(ui);
+    let _: DialogResult = FileDialog::new().spawn(ui);
+}
+
+struct Bookmarks;
+
+impl PlacesProvider for Bookmarks {
+    fn places(&self) -> Vec<Place> {
+        vec![Place::new("Projects", "/projects").icon(PlaceIcon::Bookmark).section(PlaceSection::Bookmarks)]
+    }
+}
+
+fn every_dialog_mode(mode: DialogMode) {
+    match mode {
+        DialogMode::Open | DialogMode::Save => {}
+    }
+}
+
+fn every_view_mode(mode: ViewMode) {
+    match mode {
+        ViewMode::List | ViewMode::Grid => {}
+    }
+}
+
+fn every_entry_kind(kind: EntryKind) -> bool {
+    match kind {
+        EntryKind::Dir | EntryKind::File | EntryKind::Symlink { to_dir: _ } | EntryKind::BrokenSymlink | EntryKind::Other => {
+            kind.is_dir() || kind.is_file() || kind.is_symlink()
+        }
+    }
+}
+
+fn every_result(result: DialogResult) -> Vec<PathBuf> {
+    let open = result.is_open();
+    match result {
+        DialogResult::StillOpen | DialogResult::Cancelled => assert!(open || result.clone().path().is_none()),
+        DialogResult::Selected(_) | DialogResult::SelectedMany(_) => {}
+    }
+    result.paths()
+}
+
+fn every_config_error(error: ConfigError) -> String {
+    match error {
+        ConfigError::DirOnlyInSaveMode
+        | ConfigError::DirOnlyWithFilters
+        | ConfigError::DirOnlyWithoutControls
+        | ConfigError::SaveWithoutControls
+        | ConfigError::AcceptDirsTooInSaveMode
+        | ConfigError::AcceptDirsTooWithoutControls
+        | ConfigError::MultiSelectInSaveMode
+        | ConfigError::MultiSelectWithoutControls
+        | ConfigError::MultiSelectWithSingleClick
+        | ConfigError::EmptyOptionId
+        | ConfigError::ActiveFilterOutOfRange => error.to_string(),
+    }
+}
+
+fn every_nav_target(target: &NavTarget) -> Option<&Path> {
+    match target {
+        NavTarget::Entry(path) | NavTarget::PathBar(path) | NavTarget::Typed(path) | NavTarget::Place(path) => Some(path),
+        NavTarget::Parent => None,
+    }
+}
+
+fn every_place_section(section: &PlaceSection) -> &str {
+    match section {
+        PlaceSection::Places | PlaceSection::Devices | PlaceSection::Bookmarks | PlaceSection::Recent | PlaceSection::Custom(_) => {
+            section.title()
+        }
+    }
+}
+
+fn every_place_icon(icon: PlaceIcon) {
+    match icon {
+        PlaceIcon::Folder
+        | PlaceIcon::Home
+        | PlaceIcon::Desktop
+        | PlaceIcon::Documents
+        | PlaceIcon::Downloads
+        | PlaceIcon::Pictures
+        | PlaceIcon::Music
+        | PlaceIcon::Videos
+        | PlaceIcon::Drive
+        | PlaceIcon::Removable
+        | PlaceIcon::Network
+        | PlaceIcon::Bookmark
+        | PlaceIcon::File => {}
+    }
+}
+
+fn every_sort_option(dotfiles: DotfilesPosition, by: SortBy, order: SortOrder) {
+    match dotfiles {
+        DotfilesPosition::First | DotfilesPosition::Mixed | DotfilesPosition::Last => {}
+    }
+    match by {
+        SortBy::Name | SortBy::Size | SortBy::Modified => {}
+    }
+    match order {
+        SortOrder::Ascending | SortOrder::Descending => {}
+    }
+}
+
+#[test]
+fn builder() {
+    let memory = DialogMemory::new();
+    let dialog = FileDialog::new()
+        .title("Export")
+        .window_size([800.0, 500.0])
+        .accept_text("Export")
+        .cancel_text("Back")
+        .select_folder_text("Use this folder")
+        .for_save()
+        .filter(&["png"])
+        .filter_glob("scene-*")
+        .add_filter("Images", &["png", "jpg"])
+        .common_places(false)
+        .places_provider(Box::new(Bookmarks))
+        .places_providers(vec![Box::new(CommonPlaces::new())])
+        .places_refresh_interval(Duration::from_secs(5))
+        .sort_by(SortBy::Modified, SortOrder::Descending)
+        .dirs_first(false)
+        .natural_sort(false)
+        .case_sensitive_search(true)
+        .sort_with(|a: &str, b: &str| -> Ordering { a.len().cmp(&b.len()) })
+        .dotfiles_position(DotfilesPosition::Last)
+        .star("/tmp/starred.txt")
+        .option_checkbox("layers", "Keep layers", true)
+        .extra_toggle("Compress", false)
+        .default_filename("scene")
+        .default_extension("png")
+        .allow_file_operations()
+        .permanent_delete_fallback()
+        .allow_new_folder(false)
+        .confirm_overwrite(false)
+        .verify_readable()
+        .resolve_symlinks(true)
+        .select_broken_symlinks()
+        .force_decision()
+        .with_close_button()
+        .start_directory(std::env::temp_dir())
+        .memory(&memory, "export")
+        .show_path_bar(false)
+        .show_sidebar(false)
+        .view_mode(ViewMode::Grid)
+        .show_modified_column(false)
+        .show_controls(true)
+        .show_status_bar(false)
+        .with_preview()
+        .with_details()
+        .thumbnail_provider([64.0, 64.0], |_: &Path| -> Option<imgui::TextureId> { None })
+        .accept_button_fn(|ui: &imgui::Ui, state: &AcceptState| -> bool {
+            let AcceptState { label, enabled, tooltip } = state;
+            *enabled && tooltip.is_none() && ui.button(label)
+        })
+        .cancel_button_fn(|ui: &imgui::Ui, label: &str| -> bool { ui.button(label) })
+        .entry_color(|_: &Path, is_dir: bool| -> Option<[f32; 4]> { is_dir.then_some([1.0; 4]) })
+        .validator(|_: &Path| -> Result<(), String> { Ok(()) })
+        .footer(|_: &imgui::Ui| {});
+    #[cfg(feature = "collation")]
+    let dialog = dialog.collation_locale("de");
+    #[cfg(feature = "icons")]
+    let dialog = dialog.icons(IconSet::default());
+    #[cfg(feature = "regex")]
+    let dialog = dialog.filter_regex(r"^scene-\d+$").unwrap().regex_search(true);
+
+    let mut dialog = dialog.clone();
+    let _: &DialogConfig = dialog.config();
+    let _: Option<(usize, &str)> = dialog.active_filter();
+    let _: &[PathBuf] = dialog.starred_files();
+    let _: Option<bool> = dialog.option("layers");
+    let _: &[OptionCheckbox] = dialog.options();
+    let _: Result<(), ConfigError> = dialog.validate();
+    let _: &NavigationLog = dialog.navigation_log();
+    let _: String = dialog.accessibility_summary();
+    let _: Vec<(String, String)> = dialog.shortcut_descriptions();
+    let DrawStats { entries_drawn, rebuilds, cache_hits } = dialog.draw_stats();
+    let _: [usize; 3] = [entries_drawn, rebuilds, cache_hits];
+    let entries: Vec<&Entry> = dialog.entries().collect();
+    let _: Vec<usize> = dialog.selection_indices();
+    for entry in entries {
+        let _: (&Path, &str, EntryKind) = (entry.path(), entry.name(), entry.kind());
+        let _: (Option<u64>, Option<SystemTime>) = (entry.size(), entry.modified());
+        let _: (Option<&Path>, bool) = (entry.link_target(), entry.is_hidden());
+    }
+    let _: &Path = dialog.current_dir();
+    let DialogState { current_dir, selection, highlighted, search, search_history, preview_width } = dialog.save_state_full();
+    dialog.apply_state(DialogState { current_dir, selection, highlighted, search, search_history, preview_width });
+    dialog.refresh_places();
+    dialog.invalidate();
+    let _ = format!("{:?}", dialog);
+    let _ = FileDialog::default();
+
+    let _ = FileDialog::new().dir_only();
+    let _ = FileDialog::new().accept_dirs_too().single_click_activates();
+    let _ = FileDialog::new().multi_select();
+}
+
+#[test]
+fn config() {
+    let config = DialogConfig {
+        mode: DialogMode::Open,
+        start_directory: None,
+        title: String::from("Open"),
+        window_size: [600.0, 400.0],
+        accept_text: String::from("Open"),
+        cancel_text: String::from("Cancel"),
+        select_folder_text: String::from("Select this folder"),
+        filename: String::new(),
+        default_extension: None,
+        allow_file_operations: false,
+        permanent_delete_fallback: false,
+        allow_new_folder: true,
+        confirm_overwrite: true,
+        verify_readable: false,
+        dirs_only: false,
+        extensions: Vec::new(),
+        globs: Vec::new(),
+        filters: vec![FileFilter::new("Images", &["png"])],
+        active_filter: 0,
+        accept_dirs_too: false,
+        single_click_activates: false,
+        multi_select: false,
+        resolve_symlinks: false,
+        select_broken_symlinks: false,
+        force_decision: false,
+        close_button: false,
+        show_hidden_files: false,
+        case_sensitive_search: false,
+        #[cfg(feature = "regex")]
+        regex_search: false,
+        sort_by: SortBy::Name,
+        sort_order: SortOrder::Ascending,
+        dirs_first: true,
+        natural_sort: true,
+        dotfiles_position: DotfilesPosition::Mixed,
+        #[cfg(feature = "collation")]
+        collation_locale: None,
+        show_path_bar: true,
+        show_sidebar: true,
+        common_places: true,
+        places_refresh_interval: None,
+        #[cfg(feature = "icons")]
+        icons: None,
+        show_preview: false,
+        show_details: false,
+        view_mode: ViewMode::List,
+        show_modified_column: true,
+        show_controls: true,
+        show_status_bar: true,
+        starred: Vec::new(),
+        options: vec![OptionCheckbox { id: String::from("id"), label: String::from("Label"), value: false }],
+    };
+    let _: Result<(), ConfigError> = config.validate();
+    let dialog = FileDialog::from_config(config.clone());
+    assert_eq!(dialog.config(), &config);
+    assert!(config != DialogConfig::default());
+
+    let FileFilter { name, extensions } = FileFilter::new("Images", &[".png"]);
+    assert_eq!((name.as_str(), extensions), ("Images", vec![String::from("png")]));
+    let _: String = FileFilter::new("All", &[]).label();
+
+    #[cfg(feature = "icons")]
+    {
+        let IconSet { folder: _, file: _, image: _, audio: _, archive: _, code: _, text: _ } = IconSet::default();
+    }
+}
+
+#[test]
+fn memory_and_places() {
+    let memory = DialogMemory::new();
+    memory.set_last_directory("export", std::env::temp_dir());
+    let _: Option<PathBuf> = memory.last_directory("export");
+    memory.push_recent(std::env::temp_dir());
+    let _: Vec<PathBuf> = memory.recent();
+    memory.add_bookmark(std::env::temp_dir());
+    let _: Vec<PathBuf> = memory.bookmarks();
+    memory.remove_bookmark(std::env::temp_dir());
+    let _ = memory.clone();
+
+    let Place { label: _, path: _, icon, section } = Bookmarks.places().remove(0);
+    every_place_icon(icon);
+    every_place_section(&section);
+    let _: Vec<Place> = CommonPlaces::new().places();
+    let _ = CommonPlaces::default();
+}
+
+#[test]
+fn enums() {
+    every_dialog_mode(DialogMode::default());
+    every_view_mode(ViewMode::default());
+    every_entry_kind(EntryKind::File);
+    every_result(DialogResult::default());
+    every_config_error(ConfigError::EmptyOptionId);
+    every_nav_target(&NavTarget::Parent);
+    every_sort_option(DotfilesPosition::default(), SortBy::default(), SortOrder::default());
+
+    let log = NavigationLog::default();
+    let _: Option<&NavEvent> = log.last();
+    for NavEvent { target, from: _, to: _, error: _ } in log.events().rev() {
+        every_nav_target(target);
+    }
+}